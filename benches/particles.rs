@@ -0,0 +1,216 @@
+//! Headless version of the `particles` stress test, comparing the cost of rollback when the
+//! snapshot schedules ([`LoadWorld`], [`SaveWorld`] and [`ChecksumWorld`]) run on a single thread
+//! versus the multi-threaded executor. Each rolled back type is saved, loaded and hashed by its own
+//! system, so independent types are handled in parallel.
+//!
+//! Entities are only mapped, by exclusive systems, when they are respawned. Particles are never
+//! despawned, so this does not measure mapping.
+//!
+//! cargo bench --bench particles
+
+use std::hash::{Hash, Hasher};
+
+use bevy::{
+    ecs::schedule::ExecutorKind, math::vec3, prelude::*, time::TimeUpdateStrategy, utils::Duration,
+    utils::HashMap,
+};
+use bevy_ggrs::{prelude::*, ChecksumWorld, LoadWorld, LocalInputs, SaveWorld};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{Rng, SeedableRng};
+
+type Config = GgrsConfig<u8>;
+
+/// Every frame is rolled back and resimulated this many frames.
+const CHECK_DISTANCE: usize = 7;
+
+const FPS: usize = 60;
+
+#[derive(Component, Clone, Copy, Deref, DerefMut)]
+struct Velocity(Vec3);
+
+impl Hash for Velocity {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.x.to_bits().hash(state);
+        self.0.y.to_bits().hash(state);
+        self.0.z.to_bits().hash(state);
+    }
+}
+
+#[derive(Component, Clone, Copy, Deref, DerefMut, Hash)]
+struct Ttl(usize);
+
+type GameRng = rand_xoshiro::Xoshiro256PlusPlus;
+
+#[derive(Resource, Clone, Deref, DerefMut)]
+struct ParticleRng(GameRng);
+
+#[derive(Resource, Clone, Copy)]
+struct Particles(usize);
+
+fn read_local_inputs(mut commands: Commands) {
+    commands.insert_resource(LocalInputs::<Config>(HashMap::from([(0, 0)])));
+}
+
+fn spawn_particles(mut commands: Commands, count: Res<Particles>, mut rng: ResMut<ParticleRng>) {
+    let s = 200.0;
+
+    for _ in 0..count.0 {
+        commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::ORANGE,
+                        custom_size: Some(Vec2::splat(5.0)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                Velocity(vec3(rng.gen_range(-s..s), rng.gen_range(-s..s), 0.0)),
+                Ttl(FPS * 5),
+            ))
+            .add_rollback();
+    }
+}
+
+fn update_particles(
+    mut particles: Query<(&mut Transform, &mut Velocity, &mut Ttl)>,
+    time: Res<Time>,
+) {
+    let time_step = time.delta_seconds();
+    let gravity = Vec3::NEG_Y * 200.0;
+
+    // Particles are never despawned, so every frame snapshots the same amount of data
+    for (mut transform, mut velocity, mut ttl) in &mut particles {
+        **velocity += gravity * time_step;
+        transform.translation += **velocity * time_step;
+        **ttl = ttl.saturating_sub(1);
+    }
+}
+
+fn create_app(executor: ExecutorKind, particles: usize) -> App {
+    let session = SessionBuilder::<Config>::new()
+        .with_num_players(1)
+        .with_check_distance(CHECK_DISTANCE)
+        .add_player(PlayerType::Local, 0)
+        .unwrap()
+        .start_synctest_session()
+        .unwrap();
+
+    let mut app = App::new();
+
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(GgrsPlugin::<Config>::default())
+        .set_rollback_schedule_fps(FPS)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / FPS as f64,
+        )))
+        .add_systems(ReadInputs, read_local_inputs)
+        .rollback_component_with_clone::<Sprite>()
+        .rollback_component_with_clone::<Transform>()
+        .rollback_component_with_clone::<GlobalTransform>()
+        .rollback_component_with_clone::<Handle<Image>>()
+        .rollback_component_with_clone::<Visibility>()
+        .rollback_component_with_clone::<InheritedVisibility>()
+        .rollback_component_with_clone::<ViewVisibility>()
+        .rollback_component_with_copy::<Velocity>()
+        .rollback_component_with_copy::<Ttl>()
+        .rollback_resource_with_clone::<ParticleRng>()
+        .checksum_component_with_hash::<Velocity>()
+        .checksum_component_with_hash::<Ttl>()
+        .insert_resource(Particles(particles))
+        .insert_resource(ParticleRng(GameRng::seed_from_u64(123)))
+        .add_systems(Startup, spawn_particles)
+        .add_systems(GgrsSchedule, update_particles)
+        .insert_resource(Session::SyncTest(session));
+
+    let set_executor = |schedule: &mut Schedule| {
+        schedule.set_executor_kind(executor);
+    };
+
+    app.edit_schedule(LoadWorld, set_executor)
+        .edit_schedule(SaveWorld, set_executor)
+        .edit_schedule(ChecksumWorld, set_executor);
+
+    // Fill the snapshot buffers so every measured frame performs a full rollback
+    for _ in 0..=CHECK_DISTANCE {
+        app.update();
+    }
+
+    app
+}
+
+const EXECUTORS: [(&str, ExecutorKind); 2] = [
+    ("single_threaded", ExecutorKind::SingleThreaded),
+    ("multi_threaded", ExecutorKind::MultiThreaded),
+];
+
+const PARTICLES: [usize; 2] = [1_000, 10_000];
+
+/// Runs the whole [`SaveWorld`] schedule for the current frame, including checksums.
+fn save_world(c: &mut Criterion) {
+    let mut group = c.benchmark_group("particles_save_world");
+
+    for particles in PARTICLES {
+        for (name, executor) in EXECUTORS {
+            group.bench_with_input(
+                BenchmarkId::new(name, particles),
+                &particles,
+                |b, &particles| {
+                    let mut app = create_app(executor, particles);
+
+                    // Saving the same frame again replaces the previous snapshot
+                    b.iter(|| app.world.run_schedule(SaveWorld));
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+/// Runs the whole [`LoadWorld`] schedule for the current frame.
+fn load_world(c: &mut Criterion) {
+    let mut group = c.benchmark_group("particles_load_world");
+
+    for particles in PARTICLES {
+        for (name, executor) in EXECUTORS {
+            group.bench_with_input(
+                BenchmarkId::new(name, particles),
+                &particles,
+                |b, &particles| {
+                    let mut app = create_app(executor, particles);
+
+                    // The current frame is only saved once the next frame is requested
+                    app.world.run_schedule(SaveWorld);
+
+                    b.iter(|| app.world.run_schedule(LoadWorld));
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+/// Runs a whole frame, saving, loading and resimulating [`CHECK_DISTANCE`] frames.
+fn particles(c: &mut Criterion) {
+    let mut group = c.benchmark_group("particles");
+
+    for particles in PARTICLES {
+        for (name, executor) in EXECUTORS {
+            group.bench_with_input(
+                BenchmarkId::new(name, particles),
+                &particles,
+                |b, &particles| {
+                    let mut app = create_app(executor, particles);
+                    b.iter(|| app.update());
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, save_world, load_world, particles);
+criterion_main!(benches);
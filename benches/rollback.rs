@@ -0,0 +1,335 @@
+//! Measures the overhead rollback adds to a frame, using a [`SyncTestSession`](`ggrs::SyncTestSession`)
+//! so every frame is rolled back and resimulated without any networking.
+//!
+//! Each group documents what it includes. `save_world` and `load_world` time whole schedules, so
+//! they include the bookkeeping shared by every type, not just the snapshot systems.
+//!
+//! cargo bench --bench rollback
+
+use std::time::Instant;
+
+use bevy::{
+    ecs::entity::{EntityMapper, MapEntities},
+    prelude::*,
+    time::TimeUpdateStrategy,
+    utils::{Duration, HashMap},
+};
+use bevy_ggrs::{prelude::*, ComponentMapEntitiesPlugin, LoadWorld, LocalInputs, SaveWorld};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+type Config = GgrsConfig<u8>;
+
+/// Every frame is rolled back and resimulated this many frames.
+const CHECK_DISTANCE: usize = 7;
+
+const FPS: usize = 60;
+
+const ENTITIES: [usize; 3] = [100, 1_000, 10_000];
+
+/// Number of frames a churned [`Entity`] is alive for.
+const LIFETIME: u32 = 10;
+
+#[derive(Component, Reflect, Default, Clone, Copy)]
+#[reflect(Component)]
+struct Position(Vec3);
+
+#[derive(Component, Reflect, Default, Clone, Copy)]
+#[reflect(Component)]
+struct Velocity(Vec3);
+
+#[derive(Component, Clone, Copy)]
+struct Lifetime(u32);
+
+/// Refers to another rollback [`Entity`], which must be mapped whenever it is respawned.
+#[derive(Component, Clone, Copy)]
+struct Target(Entity);
+
+impl MapEntities for Target {
+    fn map_entities(&mut self, entity_mapper: &mut EntityMapper) {
+        self.0 = entity_mapper.get_or_reserve(self.0);
+    }
+}
+
+#[derive(Resource, Clone, Copy)]
+struct Entities(usize);
+
+/// When present, a fraction of all entities are despawned and replaced every frame.
+#[derive(Resource, Clone, Copy)]
+struct Churn {
+    linked: bool,
+}
+
+#[derive(Clone, Copy)]
+enum Snapshot {
+    Copy,
+    Clone,
+    Reflect,
+}
+
+impl Snapshot {
+    const ALL: [Self; 3] = [Self::Copy, Self::Clone, Self::Reflect];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Copy => "copy",
+            Self::Clone => "clone",
+            Self::Reflect => "reflect",
+        }
+    }
+
+    fn register(self, app: &mut App) {
+        match self {
+            Self::Copy => app
+                .rollback_component_with_copy::<Position>()
+                .rollback_component_with_copy::<Velocity>(),
+            Self::Clone => app
+                .rollback_component_with_clone::<Position>()
+                .rollback_component_with_clone::<Velocity>(),
+            Self::Reflect => app
+                .rollback_component_with_reflect::<Position>()
+                .rollback_component_with_reflect::<Velocity>(),
+        };
+    }
+}
+
+fn read_local_inputs(mut commands: Commands) {
+    commands.insert_resource(LocalInputs::<Config>(HashMap::from([(0, 0)])));
+}
+
+fn spawn_entities(mut commands: Commands, entities: Res<Entities>, churn: Option<Res<Churn>>) {
+    for index in 0..entities.0 {
+        let velocity = Velocity(Vec3::splat(index as f32));
+        let entity = commands
+            .spawn((Position(Vec3::ZERO), velocity))
+            .add_rollback()
+            .id();
+
+        // Stagger lifetimes so a similar number of entities are replaced every frame
+        if let Some(churn) = churn.as_deref() {
+            let lifetime = Lifetime(index as u32 % LIFETIME + 1);
+            commands.entity(entity).insert(lifetime);
+
+            if churn.linked {
+                commands.spawn((Target(entity), lifetime)).add_rollback();
+            }
+        }
+    }
+}
+
+fn move_entities(mut query: Query<(&mut Position, &Velocity)>) {
+    for (mut position, velocity) in query.iter_mut() {
+        position.0 += velocity.0;
+    }
+}
+
+fn churn_entities(
+    mut commands: Commands,
+    entities: Res<Entities>,
+    churn: Res<Churn>,
+    mut query: Query<(Entity, &mut Lifetime)>,
+) {
+    for (entity, mut lifetime) in query.iter_mut() {
+        lifetime.0 -= 1;
+
+        if lifetime.0 == 0 {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for _ in 0..entities.0 / LIFETIME as usize {
+        let entity = commands
+            .spawn((
+                Position(Vec3::ZERO),
+                Velocity(Vec3::ONE),
+                Lifetime(LIFETIME),
+            ))
+            .add_rollback()
+            .id();
+
+        if churn.linked {
+            commands
+                .spawn((Target(entity), Lifetime(LIFETIME)))
+                .add_rollback();
+        }
+    }
+}
+
+fn create_app(entities: usize, setup: impl FnOnce(&mut App)) -> App {
+    let session = SessionBuilder::<Config>::new()
+        .with_num_players(1)
+        .with_check_distance(CHECK_DISTANCE)
+        .add_player(PlayerType::Local, 0)
+        .unwrap()
+        .start_synctest_session()
+        .unwrap();
+
+    let mut app = App::new();
+
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(GgrsPlugin::<Config>::default())
+        .set_rollback_schedule_fps(FPS)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / FPS as f64,
+        )))
+        .add_systems(ReadInputs, read_local_inputs)
+        .insert_resource(Entities(entities))
+        .add_systems(Startup, spawn_entities)
+        .add_systems(GgrsSchedule, move_entities)
+        .insert_resource(Session::SyncTest(session));
+
+    setup(&mut app);
+
+    // Fill the snapshot buffers so every measured frame performs a full rollback
+    for _ in 0..=CHECK_DISTANCE {
+        app.update();
+    }
+
+    app
+}
+
+fn create_churn_app(entities: usize, linked: bool) -> App {
+    create_app(entities, |app| {
+        Snapshot::Copy.register(app);
+
+        app.insert_resource(Churn { linked })
+            .rollback_component_with_copy::<Lifetime>()
+            .rollback_component_with_copy::<Target>()
+            .add_plugins(ComponentMapEntitiesPlugin::<Target>::default())
+            .add_systems(GgrsSchedule, churn_entities);
+    })
+}
+
+/// Runs the whole [`SaveWorld`] schedule for the current frame. As well as the snapshot systems
+/// for each type, this includes the [`ChecksumWorld`](`bevy_ggrs::ChecksumWorld`) schedule,
+/// discarding old snapshots, and saving and compacting [`Rollback`] entities.
+fn save_world(c: &mut Criterion) {
+    let mut group = c.benchmark_group("save_world");
+
+    for snapshot in Snapshot::ALL {
+        for entities in ENTITIES {
+            group.bench_with_input(
+                BenchmarkId::new(snapshot.name(), entities),
+                &entities,
+                |b, &entities| {
+                    let mut app = create_app(entities, |app| snapshot.register(app));
+
+                    // Saving the same frame again replaces the previous snapshot
+                    b.iter(|| app.world.run_schedule(SaveWorld));
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+/// Runs the whole [`LoadWorld`] schedule for the current frame. As well as the snapshot systems
+/// for each type, this includes restoring [`Rollback`] entities and building the
+/// [`RollbackEntityMap`](`bevy_ggrs::RollbackEntityMap`), though nothing has changed since the
+/// frame was saved, so no entities are respawned.
+fn load_world(c: &mut Criterion) {
+    let mut group = c.benchmark_group("load_world");
+
+    for snapshot in Snapshot::ALL {
+        for entities in ENTITIES {
+            group.bench_with_input(
+                BenchmarkId::new(snapshot.name(), entities),
+                &entities,
+                |b, &entities| {
+                    let mut app = create_app(entities, |app| snapshot.register(app));
+
+                    // The current frame is only saved once the next frame is requested
+                    app.world.run_schedule(SaveWorld);
+
+                    b.iter(|| app.world.run_schedule(LoadWorld));
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+/// Runs a whole frame, saving, loading and resimulating [`CHECK_DISTANCE`] frames.
+fn rollback(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rollback");
+
+    for snapshot in Snapshot::ALL {
+        for entities in ENTITIES {
+            group.bench_with_input(
+                BenchmarkId::new(snapshot.name(), entities),
+                &entities,
+                |b, &entities| {
+                    let mut app = create_app(entities, |app| snapshot.register(app));
+                    b.iter(|| app.update());
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+/// Runs a whole frame as for `rollback`, while entities are despawned and replaced every frame.
+fn churn(c: &mut Criterion) {
+    let mut group = c.benchmark_group("churn");
+
+    for entities in ENTITIES {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(entities),
+            &entities,
+            |b, &entities| {
+                let mut app = create_churn_app(entities, false);
+                b.iter(|| app.update());
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// Runs only the [`LoadWorld`] schedule, restoring a frame after entities have been despawned and
+/// replaced. This respawns the despawned entities and maps every [`Target`] to their new IDs.
+fn map_entities(c: &mut Criterion) {
+    let mut group = c.benchmark_group("map_entities");
+
+    for entities in ENTITIES {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(entities),
+            &entities,
+            |b, &entities| {
+                let mut app = create_churn_app(entities, true);
+
+                // The current frame is only saved once the next frame is requested
+                app.world.run_schedule(SaveWorld);
+
+                b.iter_custom(|iters| {
+                    let mut elapsed = Duration::ZERO;
+
+                    for _ in 0..iters {
+                        // Churn entities without saving, so they must be restored on load
+                        app.world.run_schedule(GgrsSchedule);
+
+                        let start = Instant::now();
+                        app.world.run_schedule(LoadWorld);
+                        elapsed += start.elapsed();
+                    }
+
+                    elapsed
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    save_world,
+    load_world,
+    rollback,
+    churn,
+    map_entities
+);
+criterion_main!(benches);
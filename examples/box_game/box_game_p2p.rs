@@ -93,29 +93,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             TimerMode::Repeating,
         )))
         .add_systems(Update, print_network_stats_system)
-        .add_systems(Update, print_events_system)
         .run();
 
     Ok(())
 }
 
-fn print_events_system(mut session: ResMut<Session<BoxConfig>>) {
-    match session.as_mut() {
-        Session::P2P(s) => {
-            for event in s.events() {
-                match event {
-                    GgrsEvent::Disconnected { .. } | GgrsEvent::NetworkInterrupted { .. } => {
-                        warn!("GGRS event: {event:?}")
-                    }
-                    GgrsEvent::DesyncDetected { .. } => error!("GGRS event: {event:?}"),
-                    _ => info!("GGRS event: {event:?}"),
-                }
-            }
-        }
-        _ => panic!("This example focuses on p2p."),
-    }
-}
-
 fn print_network_stats_system(
     time: Res<Time>,
     mut timer: ResMut<NetworkStatsTimer>,
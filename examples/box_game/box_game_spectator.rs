@@ -63,23 +63,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             TimerMode::Repeating,
         )))
         .add_systems(Update, print_network_stats_system)
-        .add_systems(Update, print_events_system)
         .run();
 
     Ok(())
 }
 
-fn print_events_system(mut session: ResMut<Session<BoxConfig>>) {
-    match session.as_mut() {
-        Session::Spectator(s) => {
-            for event in s.events() {
-                println!("GGRS Event: {:?}", event);
-            }
-        }
-        _ => panic!("This example focuses on spectators."),
-    }
-}
-
 fn print_network_stats_system(
     time: Res<Time>,
     mut timer: ResMut<NetworkStatsTimer>,
@@ -1,5 +1,5 @@
 use bevy::{math::vec3, prelude::*, utils::HashMap, window::WindowResolution};
-use bevy_ggrs::{prelude::*, LocalInputs, LocalPlayers};
+use bevy_ggrs::{prelude::*, DesyncDetected, LocalInputs, LocalPlayers};
 use clap::Parser;
 use ggrs::{DesyncDetection, UdpNonBlockingSocket};
 use rand::{Rng, SeedableRng};
@@ -292,30 +292,20 @@ fn despawn_particles(mut commands: Commands, mut particles: Query<(Entity, &mut
     }
 }
 
-fn print_events_system(mut session: ResMut<Session<Config>>, args: Res<Args>) {
-    match session.as_mut() {
-        Session::P2P(s) => {
-            for event in s.events() {
-                match event {
-                    GgrsEvent::Disconnected { .. } | GgrsEvent::NetworkInterrupted { .. } => {
-                        warn!("GGRS event: {event:?}")
-                    }
-                    GgrsEvent::DesyncDetected {
-                        local_checksum,
-                        remote_checksum,
-                        frame,
-                        ..
-                    } => {
-                        if args.continue_after_desync {
-                            error!("Desync on frame {frame}. Local checksum: {local_checksum:X}, remote checksum: {remote_checksum:X}");
-                        } else {
-                            panic!("Desync on frame {frame}. Local checksum: {local_checksum:X}, remote checksum: {remote_checksum:X}");
-                        }
-                    }
-                    _ => info!("GGRS event: {event:?}"),
-                }
-            }
-        }
-        _ => panic!("This example focuses on p2p."),
+fn print_events_system(mut desyncs: EventReader<DesyncDetected<Config>>, args: Res<Args>) {
+    // Desyncs are already logged as errors by the SessionEventsPlugin
+    if args.continue_after_desync {
+        desyncs.clear();
+        return;
+    }
+
+    for DesyncDetected {
+        local_checksum,
+        remote_checksum,
+        frame,
+        ..
+    } in desyncs.read()
+    {
+        panic!("Desync on frame {frame}. Local checksum: {local_checksum:X}, remote checksum: {remote_checksum:X}");
     }
 }
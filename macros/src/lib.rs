@@ -0,0 +1,174 @@
+//! Derive macros for `bevy_ggrs`.
+//!
+//! These are re-exported by `bevy_ggrs`, which should be depended on instead.
+#![forbid(unsafe_code)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput, Error, Ident, Result};
+
+const ROLLBACK: &str = "rollback";
+
+/// Implements `RegisterRollback` for a component or resource, based on its `#[rollback(...)]`
+/// attributes. The type can then be registered with `GgrsApp::register_rollback_types`.
+///
+/// Supported attributes:
+/// - `component` (default) or `resource`, selecting which kind of type this is.
+/// - `copy`, `clone`, or `reflect`, selecting how the type is snapshotted.
+/// - `checksum`, including the type in checksums using its `Hash` implementation.
+/// - `map_entities`, updating the type after rollback using its `MapEntities` implementation.
+#[proc_macro_derive(Rollback, attributes(rollback))]
+pub fn derive_rollback(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+
+    let attrs = match parse_rollback_attr(&ast) {
+        Ok(attrs) => attrs,
+        Err(e) => return e.into_compile_error().into(),
+    };
+
+    let registrations = registrations(&attrs);
+
+    let struct_name = &ast.ident;
+    let (impl_generics, type_generics, where_clause) = &ast.generics.split_for_impl();
+
+    TokenStream::from(quote! {
+        impl #impl_generics ::bevy_ggrs::RegisterRollback for #struct_name #type_generics #where_clause {
+            fn register_rollback(app: &mut ::bevy_ggrs::__macro_exports::App) {
+                #(#registrations)*
+            }
+        }
+    })
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Component,
+    Resource,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Strategy {
+    Copy,
+    Clone,
+    Reflect,
+}
+
+struct Attrs {
+    kind: Kind,
+    strategy: Option<Strategy>,
+    checksum: bool,
+    map_entities: bool,
+}
+
+// values for the `rollback` attribute
+const COMPONENT: &str = "component";
+const RESOURCE: &str = "resource";
+const COPY: &str = "copy";
+const CLONE: &str = "clone";
+const REFLECT: &str = "reflect";
+const CHECKSUM: &str = "checksum";
+const MAP_ENTITIES: &str = "map_entities";
+
+fn parse_rollback_attr(ast: &DeriveInput) -> Result<Attrs> {
+    let mut attrs = Attrs {
+        kind: Kind::Component,
+        strategy: None,
+        checksum: false,
+        map_entities: false,
+    };
+
+    let mut found = false;
+
+    for meta in ast.attrs.iter().filter(|a| a.path().is_ident(ROLLBACK)) {
+        meta.parse_nested_meta(|nested| {
+            found = true;
+
+            let kind = if nested.path.is_ident(COMPONENT) {
+                Some(Kind::Component)
+            } else if nested.path.is_ident(RESOURCE) {
+                Some(Kind::Resource)
+            } else {
+                None
+            };
+
+            let strategy = if nested.path.is_ident(COPY) {
+                Some(Strategy::Copy)
+            } else if nested.path.is_ident(CLONE) {
+                Some(Strategy::Clone)
+            } else if nested.path.is_ident(REFLECT) {
+                Some(Strategy::Reflect)
+            } else {
+                None
+            };
+
+            if let Some(kind) = kind {
+                attrs.kind = kind;
+            } else if let Some(strategy) = strategy {
+                if attrs.strategy.is_some_and(|existing| existing != strategy) {
+                    return Err(nested.error(format!(
+                        "Only one of `{COPY}`, `{CLONE}`, or `{REFLECT}` can be used."
+                    )));
+                }
+                attrs.strategy = Some(strategy);
+            } else if nested.path.is_ident(CHECKSUM) {
+                attrs.checksum = true;
+            } else if nested.path.is_ident(MAP_ENTITIES) {
+                attrs.map_entities = true;
+            } else {
+                return Err(nested.error("Unsupported attribute"));
+            }
+
+            Ok(())
+        })?;
+    }
+
+    if !found {
+        return Err(Error::new_spanned(
+            &ast.ident,
+            format!(
+                "Expected at least one `#[{ROLLBACK}(...)]` attribute, such as `#[{ROLLBACK}({CLONE}, {CHECKSUM})]`."
+            ),
+        ));
+    }
+
+    Ok(attrs)
+}
+
+fn registrations(attrs: &Attrs) -> Vec<TokenStream2> {
+    let kind = match attrs.kind {
+        Kind::Component => COMPONENT,
+        Kind::Resource => RESOURCE,
+    };
+
+    let mut methods = Vec::new();
+
+    if let Some(strategy) = attrs.strategy {
+        let strategy = match strategy {
+            Strategy::Copy => COPY,
+            Strategy::Clone => CLONE,
+            Strategy::Reflect => REFLECT,
+        };
+
+        methods.push(format!("rollback_{kind}_with_{strategy}"));
+    }
+
+    if attrs.checksum {
+        methods.push(format!("checksum_{kind}_with_hash"));
+    }
+
+    if attrs.map_entities {
+        methods.push(format!("update_{kind}_with_map_entities"));
+    }
+
+    methods
+        .into_iter()
+        .map(|method| {
+            let method = Ident::new(&method, Span::call_site());
+
+            quote! {
+                ::bevy_ggrs::GgrsApp::#method::<Self>(app);
+            }
+        })
+        .collect()
+}
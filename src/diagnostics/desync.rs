@@ -0,0 +1,405 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Checksum, ChecksumPart, ChecksumPartEntities, ChecksumPartName, ChecksumPlugin,
+    RollbackFrameCount, SaveWorld, SaveWorldSet,
+};
+
+/// Name used in a [`FrameChecksumReport`] for [`ChecksumParts`](`ChecksumPart`) which do not
+/// have a [`ChecksumPartName`].
+pub const UNNAMED_CHECKSUM_PART: &str = "<unnamed>";
+
+/// The checksum contribution of a single type for a given frame.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ChecksumPartReport {
+    /// The name of the type this checksum was generated for.
+    pub name: String,
+    /// The [`ChecksumPart`] for this type.
+    pub checksum: u128,
+    /// Per-entity contributions as `(order, hash)` pairs, if they were tracked.
+    pub entities: Vec<(usize, u64)>,
+}
+
+/// All [`ChecksumParts`](`ChecksumPart`) which made up the [`Checksum`] of a frame.
+///
+/// Reports are [`Serialize`] and [`Deserialize`], allowing them to be sent to a remote peer
+/// with any transport and format. Once received, provide it to the [`DesyncDiagnosticsPlugin`]
+/// with a [`RemoteChecksumReport`] event, or compare it directly with [`diff`](`Self::diff`).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FrameChecksumReport {
+    /// The frame this report was created for.
+    pub frame: i32,
+    /// The total [`Checksum`] for this frame.
+    pub checksum: u128,
+    /// Individual [`ChecksumParts`](`ChecksumPart`), sorted by name.
+    pub parts: Vec<ChecksumPartReport>,
+}
+
+impl FrameChecksumReport {
+    /// Get the [`ChecksumPartReport`] for a particular type name, if it exists.
+    pub fn part(&self, name: &str) -> Option<&ChecksumPartReport> {
+        self.parts
+            .binary_search_by(|part| part.name.as_str().cmp(name))
+            .ok()
+            .map(|index| &self.parts[index])
+    }
+
+    /// Compare this (local) report with a report from another peer, returning every type which
+    /// differs between the two. An empty result means the two reports agree.
+    pub fn diff(&self, remote: &Self) -> Vec<ChecksumMismatch> {
+        let mut names = self
+            .parts
+            .iter()
+            .chain(remote.parts.iter())
+            .map(|part| part.name.as_str())
+            .collect::<Vec<_>>();
+
+        names.sort_unstable();
+        names.dedup();
+
+        names
+            .into_iter()
+            .filter_map(|name| {
+                let local = self.part(name);
+                let remote = remote.part(name);
+
+                if local.map(|part| part.checksum) == remote.map(|part| part.checksum) {
+                    return None;
+                }
+
+                let entities = diff_entities(
+                    local
+                        .map(|part| part.entities.as_slice())
+                        .unwrap_or_default(),
+                    remote
+                        .map(|part| part.entities.as_slice())
+                        .unwrap_or_default(),
+                );
+
+                Some(ChecksumMismatch {
+                    name: name.to_owned(),
+                    local: local.map(|part| part.checksum),
+                    remote: remote.map(|part| part.checksum),
+                    entities,
+                })
+            })
+            .collect()
+    }
+}
+
+fn diff_entities(local: &[(usize, u64)], remote: &[(usize, u64)]) -> Vec<EntityChecksumMismatch> {
+    let mut local = local.iter().copied().peekable();
+    let mut remote = remote.iter().copied().peekable();
+    let mut mismatches = Vec::new();
+
+    // Both lists are sorted by order, so they can be merged in a single pass
+    loop {
+        let mismatch = match (local.peek().copied(), remote.peek().copied()) {
+            (None, None) => break,
+            (Some((order, hash)), None) => {
+                local.next();
+                EntityChecksumMismatch::new(order, Some(hash), None)
+            }
+            (None, Some((order, hash))) => {
+                remote.next();
+                EntityChecksumMismatch::new(order, None, Some(hash))
+            }
+            (Some((local_order, local_hash)), Some((remote_order, remote_hash))) => {
+                match local_order.cmp(&remote_order) {
+                    std::cmp::Ordering::Less => {
+                        local.next();
+                        EntityChecksumMismatch::new(local_order, Some(local_hash), None)
+                    }
+                    std::cmp::Ordering::Greater => {
+                        remote.next();
+                        EntityChecksumMismatch::new(remote_order, None, Some(remote_hash))
+                    }
+                    std::cmp::Ordering::Equal => {
+                        local.next();
+                        remote.next();
+
+                        if local_hash == remote_hash {
+                            continue;
+                        }
+
+                        EntityChecksumMismatch::new(
+                            local_order,
+                            Some(local_hash),
+                            Some(remote_hash),
+                        )
+                    }
+                }
+            }
+        };
+
+        mismatches.push(mismatch);
+    }
+
+    mismatches
+}
+
+/// Describes a type whose [`ChecksumPart`] differs between two [`FrameChecksumReports`](`FrameChecksumReport`).
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ChecksumMismatch {
+    /// The name of the type which diverged.
+    pub name: String,
+    /// The local [`ChecksumPart`], if this type was present locally.
+    pub local: Option<u128>,
+    /// The remote [`ChecksumPart`], if this type was present remotely.
+    pub remote: Option<u128>,
+    /// The [`Rollback`](`crate::Rollback`) entities which diverged, if per-entity checksums were tracked.
+    pub entities: Vec<EntityChecksumMismatch>,
+}
+
+/// Describes a [`Rollback`](`crate::Rollback`) entity whose contribution to a [`ChecksumPart`] diverged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EntityChecksumMismatch {
+    /// The [`order`](`crate::RollbackOrdered::order`) of the diverging [`Rollback`](`crate::Rollback`).
+    pub order: usize,
+    /// The local hash, if the component was present locally.
+    pub local: Option<u64>,
+    /// The remote hash, if the component was present remotely.
+    pub remote: Option<u64>,
+}
+
+impl EntityChecksumMismatch {
+    fn new(order: usize, local: Option<u64>, remote: Option<u64>) -> Self {
+        Self {
+            order,
+            local,
+            remote,
+        }
+    }
+}
+
+/// A [`Resource`] holding the [`FrameChecksumReports`](`FrameChecksumReport`) for recently saved frames.
+#[derive(Resource, Debug)]
+pub struct ChecksumHistory {
+    reports: BTreeMap<i32, FrameChecksumReport>,
+    capacity: usize,
+    track_entities: bool,
+}
+
+impl ChecksumHistory {
+    /// Create a new [`ChecksumHistory`] holding at most `capacity` frames.
+    pub fn new(capacity: usize, track_entities: bool) -> Self {
+        Self {
+            reports: default(),
+            capacity,
+            track_entities,
+        }
+    }
+
+    /// Returns `true` if per-entity checksums should be recorded, `false` otherwise.
+    pub fn tracks_entities(&self) -> bool {
+        self.track_entities
+    }
+
+    /// Store a report, replacing any existing report for the same frame.
+    pub fn insert(&mut self, report: FrameChecksumReport) -> Option<FrameChecksumReport> {
+        let previous = self.reports.insert(report.frame, report);
+
+        while self.reports.len() > self.capacity {
+            self.reports.pop_first();
+        }
+
+        previous
+    }
+
+    /// Get the report for a particular frame, if it is still held.
+    pub fn get(&self, frame: i32) -> Option<&FrameChecksumReport> {
+        self.reports.get(&frame)
+    }
+
+    /// Iterate over all held reports, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &FrameChecksumReport> + '_ {
+        self.reports.values()
+    }
+}
+
+/// An [`Event`] used to provide a [`FrameChecksumReport`] received from a remote peer.
+#[derive(Event, Clone, Debug)]
+pub struct RemoteChecksumReport(pub FrameChecksumReport);
+
+/// An [`Event`] describing exactly which types diverged between the local and a remote peer.
+#[derive(Event, Clone, Debug)]
+pub struct DesyncReport {
+    /// The frame which was compared.
+    pub frame: i32,
+    /// All types which differed.
+    pub mismatches: Vec<ChecksumMismatch>,
+}
+
+/// A [`Plugin`] which records the individual [`ChecksumParts`](`ChecksumPart`) for every saved
+/// frame in a [`ChecksumHistory`], allowing a desync to be narrowed down to a particular type
+/// (and [`Rollback`](`crate::Rollback`) entity) instead of just a frame.
+///
+/// When [`GgrsEvent::DesyncDetected`](`ggrs::GgrsEvent::DesyncDetected`) is raised, send the
+/// [`FrameChecksumReport`] for that frame to the remote peer. When a report is received from a
+/// peer, send it as a [`RemoteChecksumReport`] and a [`DesyncReport`] will be produced.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, DesyncDiagnosticsPlugin};
+/// #
+/// # fn start(session: Session<GgrsConfig<u8>>) {
+/// # let mut app = App::new();
+/// #[derive(Component, Clone, Copy, Hash)]
+/// struct Health(u32);
+///
+/// app.rollback_component_with_clone::<Health>();
+/// app.checksum_component_with_hash::<Health>();
+///
+/// // Keep the individual checksums for the last 5 seconds of frames
+/// app.add_plugins(DesyncDiagnosticsPlugin {
+///     history: 300,
+///     ..default()
+/// });
+/// # }
+/// ```
+pub struct DesyncDiagnosticsPlugin {
+    /// The number of frames to keep [`FrameChecksumReports`](`FrameChecksumReport`) for.
+    pub history: usize,
+    /// Record a checksum for every individual [`Rollback`](`crate::Rollback`) entity.
+    pub track_entities: bool,
+}
+
+impl Default for DesyncDiagnosticsPlugin {
+    fn default() -> Self {
+        Self {
+            history: 128,
+            track_entities: true,
+        }
+    }
+}
+
+impl DesyncDiagnosticsPlugin {
+    /// Builds a [`FrameChecksumReport`] from all [`ChecksumParts`](`ChecksumPart`) currently in the [`World`].
+    pub fn report<'a>(
+        frame: i32,
+        checksum: u128,
+        parts: impl IntoIterator<
+            Item = (
+                &'a ChecksumPart,
+                Option<&'a ChecksumPartName>,
+                Option<&'a ChecksumPartEntities>,
+            ),
+        >,
+    ) -> FrameChecksumReport {
+        let mut unnamed = None;
+        let mut reports = Vec::new();
+
+        for (part, name, entities) in parts {
+            match name {
+                Some(name) => reports.push(ChecksumPartReport {
+                    name: name.0.to_owned(),
+                    checksum: part.0,
+                    entities: entities
+                        .map(|entities| entities.0.clone())
+                        .unwrap_or_default(),
+                }),
+                // Unnamed parts cannot be told apart, so they are combined
+                None => *unnamed.get_or_insert(0) ^= part.0,
+            }
+        }
+
+        if let Some(checksum) = unnamed {
+            reports.push(ChecksumPartReport {
+                name: UNNAMED_CHECKSUM_PART.to_owned(),
+                checksum,
+                entities: Vec::new(),
+            });
+        }
+
+        reports.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+        FrameChecksumReport {
+            frame,
+            checksum,
+            parts: reports,
+        }
+    }
+
+    /// A [`System`] which records the [`FrameChecksumReport`] for the frame being saved.
+    pub fn record(
+        mut history: ResMut<ChecksumHistory>,
+        frame: Res<RollbackFrameCount>,
+        checksum: Res<Checksum>,
+        parts: Query<(
+            &ChecksumPart,
+            Option<&ChecksumPartName>,
+            Option<&ChecksumPartEntities>,
+        )>,
+    ) {
+        let report = Self::report(frame.0, checksum.0, parts.iter());
+
+        trace!(
+            "Recorded {} checksum part(s) for frame {}",
+            report.parts.len(),
+            report.frame
+        );
+
+        history.insert(report);
+    }
+
+    /// A [`System`] which compares [`RemoteChecksumReports`](`RemoteChecksumReport`) against
+    /// the [`ChecksumHistory`], producing a [`DesyncReport`] for each.
+    pub fn compare(
+        history: Res<ChecksumHistory>,
+        mut remote_reports: EventReader<RemoteChecksumReport>,
+        mut desync_reports: EventWriter<DesyncReport>,
+    ) {
+        for RemoteChecksumReport(remote) in remote_reports.read() {
+            let Some(local) = history.get(remote.frame) else {
+                warn!(
+                    "Received checksum report for frame {}, which is no longer in the history",
+                    remote.frame
+                );
+                continue;
+            };
+
+            let mismatches = local.diff(remote);
+
+            for mismatch in &mismatches {
+                error!(
+                    "Desync on frame {} in {}: local {:X?}, remote {:X?}",
+                    remote.frame,
+                    bevy::utils::get_short_name(&mismatch.name),
+                    mismatch.local,
+                    mismatch.remote
+                );
+
+                for entity in &mismatch.entities {
+                    error!(
+                        "  Rollback entity #{}: local {:X?}, remote {:X?}",
+                        entity.order, entity.local, entity.remote
+                    );
+                }
+            }
+
+            desync_reports.send(DesyncReport {
+                frame: remote.frame,
+                mismatches,
+            });
+        }
+    }
+}
+
+impl Plugin for DesyncDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ChecksumHistory::new(self.history, self.track_entities))
+            .add_event::<RemoteChecksumReport>()
+            .add_event::<DesyncReport>()
+            .add_systems(
+                SaveWorld,
+                Self::record
+                    .after(ChecksumPlugin::update)
+                    .before(SaveWorldSet::Snapshot),
+            )
+            .add_systems(Update, Self::compare);
+    }
+}
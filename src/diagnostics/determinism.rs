@@ -0,0 +1,416 @@
+use std::collections::BTreeSet;
+
+use bevy::{
+    ecs::schedule::{InternedSystemSet, NodeId, ScheduleGraph},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    ChecksumMismatch, ChecksumPart, ChecksumPartEntities, ChecksumPartName, ChecksumWorld,
+    DesyncDiagnosticsPlugin, FrameChecksumReport, GgrsSchedule, RollbackEntityMap,
+    RollbackFrameCount,
+};
+
+/// Name used in a [`NonDeterminismDetected`] event when the divergence only appeared once the
+/// whole frame had been advanced, such as from deferred [`Commands`].
+pub const END_OF_FRAME: &str = "<end of frame>";
+
+/// A [`Resource`] which, while present, causes every frame with a snapshot to be advanced twice
+/// to check for non-determinism. Added by the [`DeterminismCheckPlugin`].
+///
+/// Remove this resource to stop checking.
+#[derive(Resource, Default)]
+pub struct DeterminismCheck {
+    probes: Option<Vec<(String, FrameChecksumReport)>>,
+}
+
+/// The systems in the [`GgrsSchedule`] to probe, in order. Captured before the schedule is first
+/// run, as running it moves its systems out of the [`ScheduleGraph`].
+#[derive(Resource)]
+struct ProbedSystems {
+    systems: Vec<(String, InternedSystemSet)>,
+    instrumented: bool,
+}
+
+/// An [`Event`] describing the first system in the [`GgrsSchedule`] whose output differed when
+/// the same frame was advanced twice from the same snapshot.
+///
+/// In each [`ChecksumMismatch`], `local` refers to the first run, and `remote` to the second.
+#[derive(Event, Clone, Debug)]
+pub struct NonDeterminismDetected {
+    /// The frame which was advanced to.
+    pub frame: i32,
+    /// The name of the first system whose output differed, or [`END_OF_FRAME`].
+    pub system: String,
+    /// All types which differed after that system ran.
+    pub mismatches: Vec<ChecksumMismatch>,
+}
+
+/// A debugging [`Plugin`] which detects non-determinism in the [`GgrsSchedule`] locally, without
+/// a second peer.
+///
+/// Every frame is advanced twice from the same snapshot, by running the [`LoadWorld`](`crate::LoadWorld`)
+/// schedule again for the frame GGRS has just saved or loaded. No additional snapshots are saved,
+/// so frames without a snapshot, such as those of a [`Spectator`](`crate::Session::Spectator`)
+/// session, are not checked. After each system in the [`GgrsSchedule`], the
+/// [`ChecksumWorld`] schedule is run and the [`ChecksumParts`](`ChecksumPart`) recorded. The first
+/// system whose output differs between the two runs is logged and sent as a
+/// [`NonDeterminismDetected`] event. Typical culprits are iterating a [`Query`] in archetype
+/// order, or reading [`Time<Real>`](`bevy::time::Real`).
+///
+/// To allow each system to be checked in isolation, the systems in the [`GgrsSchedule`] are run
+/// one at a time, in an order consistent with their dependencies. Systems which cannot be
+/// ordered individually, such as multiple instances of the same function, are checked together
+/// with the system before them. Only types registered for checksums are compared.
+///
+/// The systems are captured when the app is [finished](`App::finish`), so a check can be started
+/// at any time by inserting a [`DeterminismCheck`]. If the app is updated without being finished,
+/// as is common in tests, checks must start before the first frame is advanced.
+///
+/// This is expensive, and only intended for use while debugging. This plugin adds the
+/// [`DesyncDiagnosticsPlugin`] if it has not been added already.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, DeterminismCheckPlugin};
+/// #
+/// # fn start(session: Session<GgrsConfig<u8>>) {
+/// # let mut app = App::new();
+/// #[derive(Component, Clone, Copy, Hash)]
+/// struct Health(u32);
+///
+/// app.rollback_component_with_clone::<Health>();
+/// app.checksum_component_with_hash::<Health>();
+///
+/// #[cfg(debug_assertions)]
+/// app.add_plugins(DeterminismCheckPlugin);
+/// # }
+/// ```
+pub struct DeterminismCheckPlugin;
+
+impl DeterminismCheckPlugin {
+    /// Advances the current frame twice, loading its snapshot again in between, and compares
+    /// the results. The current frame must have just been saved or loaded.
+    pub(crate) fn check(
+        world: &mut World,
+        load_world_schedule: &mut Schedule,
+        advance_world_schedule: &mut Schedule,
+    ) {
+        Self::instrument(world);
+
+        let frame = world.resource::<RollbackFrameCount>().0;
+
+        // Loading again only respawns entities despawned by the first run, which the rest of the
+        // app shouldn't observe, so the original mapping is restored afterwards
+        let entity_map = world
+            .get_resource::<RollbackEntityMap>()
+            .map(RollbackEntityMap::generate_map);
+
+        let first = Self::record(world, advance_world_schedule, frame);
+
+        world.resource_mut::<RollbackFrameCount>().0 = frame;
+        load_world_schedule.run(world);
+        let second = Self::record(world, advance_world_schedule, frame);
+
+        match entity_map {
+            Some(mut map) => {
+                let respawned = world.resource::<RollbackEntityMap>();
+
+                for mapped in map.values_mut() {
+                    if let Some(entity) = respawned.get(*mapped) {
+                        *mapped = entity;
+                    }
+                }
+
+                world.insert_resource(RollbackEntityMap::new(map));
+            }
+            None => {
+                world.remove_resource::<RollbackEntityMap>();
+            }
+        }
+
+        Self::compare(world, frame + 1, first, second);
+    }
+
+    /// Advances the provided frame, returning the report recorded after each system.
+    fn record(
+        world: &mut World,
+        advance_world_schedule: &mut Schedule,
+        frame: i32,
+    ) -> Vec<(String, FrameChecksumReport)> {
+        world.resource_mut::<RollbackFrameCount>().0 = frame + 1;
+        world.resource_mut::<DeterminismCheck>().probes = Some(Vec::new());
+
+        advance_world_schedule.run(world);
+        Self::probe(world, END_OF_FRAME);
+
+        world
+            .resource_mut::<DeterminismCheck>()
+            .probes
+            .take()
+            .unwrap_or_default()
+    }
+
+    /// Records a [`FrameChecksumReport`] for the current state of the [`World`], if a check is in progress.
+    fn probe(world: &mut World, system: &str) {
+        let recording = world
+            .get_resource::<DeterminismCheck>()
+            .is_some_and(|check| check.probes.is_some());
+
+        if !recording {
+            return;
+        }
+
+        world.run_schedule(ChecksumWorld);
+
+        let frame = world.resource::<RollbackFrameCount>().0;
+        let mut parts = world.query::<(
+            &ChecksumPart,
+            Option<&ChecksumPartName>,
+            Option<&ChecksumPartEntities>,
+        )>();
+
+        let checksum = parts
+            .iter(world)
+            .fold(0, |a: u128, (&ChecksumPart(b), _, _)| a ^ b);
+        let report = DesyncDiagnosticsPlugin::report(frame, checksum, parts.iter(world));
+
+        if let Some(probes) = world.resource_mut::<DeterminismCheck>().probes.as_mut() {
+            probes.push((system.to_owned(), report));
+        }
+    }
+
+    /// Logs and sends a [`NonDeterminismDetected`] for the first probe which differs between runs.
+    fn compare(
+        world: &mut World,
+        frame: i32,
+        first: Vec<(String, FrameChecksumReport)>,
+        second: Vec<(String, FrameChecksumReport)>,
+    ) {
+        let detected = first
+            .into_iter()
+            .zip(second)
+            .find_map(|((system, first), (_, second))| {
+                let mismatches = first.diff(&second);
+
+                (!mismatches.is_empty()).then_some(NonDeterminismDetected {
+                    frame,
+                    system,
+                    mismatches,
+                })
+            });
+
+        let Some(detected) = detected else {
+            trace!("Frame {frame} was advanced deterministically");
+            return;
+        };
+
+        error!(
+            "Non-determinism detected advancing to frame {} in {}",
+            detected.frame, detected.system
+        );
+
+        for mismatch in &detected.mismatches {
+            error!(
+                "  {}: first run {:X?}, second run {:X?}",
+                bevy::utils::get_short_name(&mismatch.name),
+                mismatch.local,
+                mismatch.remote
+            );
+
+            for entity in &mismatch.entities {
+                error!(
+                    "    Rollback entity #{}: first run {:X?}, second run {:X?}",
+                    entity.order, entity.local, entity.remote
+                );
+            }
+        }
+
+        world.send_event(detected);
+    }
+
+    /// Captures the systems in the [`GgrsSchedule`], unless already captured or the schedule
+    /// has already been run.
+    fn capture(world: &mut World) {
+        if world.contains_resource::<ProbedSystems>() {
+            return;
+        }
+
+        let Some(schedule) = world.resource::<Schedules>().get(GgrsSchedule) else {
+            return;
+        };
+
+        let graph = schedule.graph();
+
+        // Once run, the systems have been moved out of the graph
+        let run = graph
+            .hierarchy()
+            .graph()
+            .nodes()
+            .any(|node| node.is_system() && graph.get_system_at(node).is_none());
+
+        if run {
+            return;
+        }
+
+        let systems = Self::ordered_systems(graph);
+
+        world.insert_resource(ProbedSystems {
+            systems,
+            instrumented: false,
+        });
+    }
+
+    /// Adds a probe after every captured system in the [`GgrsSchedule`], the first time a check
+    /// is run.
+    fn instrument(world: &mut World) {
+        Self::capture(world);
+
+        let Some(mut probed) = world.get_resource_mut::<ProbedSystems>() else {
+            warn!(
+                "Unable to instrument the GgrsSchedule, as it was run before the check started. \
+                Finish the App before updating it, or start the check before the first frame."
+            );
+            return;
+        };
+
+        if probed.instrumented {
+            return;
+        }
+
+        probed.instrumented = true;
+        let systems = probed.systems.clone();
+
+        let mut schedules = world.resource_mut::<Schedules>();
+
+        let Some(schedule) = schedules.get_mut(GgrsSchedule) else {
+            return;
+        };
+
+        for (index, (name, set)) in systems.iter().enumerate() {
+            let name = name.clone();
+            let mut probe = (move |world: &mut World| Self::probe(world, &name))
+                .after(*set)
+                .ambiguous_with_all();
+
+            // Prevent the next system from running until the probe is complete
+            if let Some((_, next)) = systems.get(index + 1) {
+                probe = probe.before(*next);
+            }
+
+            schedule.add_systems(probe);
+        }
+    }
+
+    /// Returns the name and type set of every system in the graph which can be individually
+    /// ordered, in an order consistent with the dependencies of all systems.
+    fn ordered_systems(graph: &ScheduleGraph) -> Vec<(String, InternedSystemSet)> {
+        let mut systems = HashMap::<NodeId, (String, Option<InternedSystemSet>)>::default();
+        let mut instances = HashMap::<InternedSystemSet, usize>::default();
+
+        for (id, system, _) in graph.systems() {
+            let sets = system.default_system_sets();
+
+            for &set in &sets {
+                *instances.entry(set).or_default() += 1;
+            }
+
+            let set = (sets.len() == 1).then(|| sets[0]);
+            systems.insert(id, (system.name().to_string(), set));
+        }
+
+        // Flatten dependencies between sets into dependencies between their systems
+        let hierarchy = graph.hierarchy().graph();
+        let members = |node: NodeId| {
+            let mut members = Vec::new();
+            let mut stack = vec![node];
+
+            while let Some(node) = stack.pop() {
+                if node.is_system() {
+                    members.push(node);
+                } else {
+                    stack.extend(
+                        hierarchy
+                            .neighbors_directed(node, bevy::utils::petgraph::Direction::Outgoing),
+                    );
+                }
+            }
+
+            members
+        };
+
+        let mut edges = HashSet::<(NodeId, NodeId)>::default();
+
+        for (before, after, _) in graph.dependency().graph().all_edges() {
+            for before in members(before) {
+                for after in members(after) {
+                    if before != after {
+                        edges.insert((before, after));
+                    }
+                }
+            }
+        }
+
+        let mut dependencies = systems
+            .keys()
+            .map(|&id| (id, 0))
+            .collect::<HashMap<_, usize>>();
+        let mut dependents = HashMap::<NodeId, Vec<NodeId>>::default();
+
+        for &(before, after) in &edges {
+            *dependencies.entry(after).or_default() += 1;
+            dependents.entry(before).or_default().push(after);
+        }
+
+        let mut ready = dependencies
+            .iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(&id, _)| id)
+            .collect::<BTreeSet<_>>();
+        let mut ordered = Vec::with_capacity(systems.len());
+
+        while let Some(id) = ready.pop_first() {
+            ordered.push(id);
+
+            for dependent in dependents.remove(&id).unwrap_or_default() {
+                let count = dependencies.get_mut(&dependent).unwrap();
+                *count -= 1;
+
+                if *count == 0 {
+                    ready.insert(dependent);
+                }
+            }
+        }
+
+        if ordered.len() != dependencies.len() {
+            warn!("Unable to order the systems in the GgrsSchedule, as their dependencies contain a cycle");
+            return Vec::new();
+        }
+
+        ordered
+            .into_iter()
+            .filter_map(|id| {
+                let (name, set) = systems.remove(&id)?;
+                let set = set.filter(|set| instances[set] == 1)?;
+                Some((name, set))
+            })
+            .collect()
+    }
+}
+
+impl Plugin for DeterminismCheckPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<DesyncDiagnosticsPlugin>() {
+            app.add_plugins(DesyncDiagnosticsPlugin::default());
+        }
+
+        app.init_resource::<DeterminismCheck>()
+            .add_event::<NonDeterminismDetected>();
+    }
+
+    fn finish(&self, app: &mut App) {
+        Self::capture(&mut app.world);
+    }
+}
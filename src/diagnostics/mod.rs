@@ -0,0 +1,9 @@
+mod desync;
+mod determinism;
+mod rollback_lint;
+mod synctest;
+
+pub use desync::*;
+pub use determinism::*;
+pub use rollback_lint::*;
+pub use synctest::*;
@@ -0,0 +1,200 @@
+use std::any::TypeId;
+
+use bevy::{
+    ecs::component::{ComponentId, ComponentTicks, Tick},
+    prelude::*,
+    utils::HashSet,
+};
+
+use crate::{AdvanceWorld, AdvanceWorldSet, DeterminismCheck, Rollback, RollbackTypes};
+
+/// A [`Resource`] tracking the state of the [`RollbackLintPlugin`].
+///
+/// Use [`ignore_component`](`Self::ignore_component`) and [`ignore_resource`](`Self::ignore_resource`)
+/// to silence warnings for types which are intentionally mutated in the
+/// [`GgrsSchedule`](`crate::GgrsSchedule`) without being rolled back.
+#[derive(Resource)]
+pub struct RollbackLint {
+    since: Option<Tick>,
+    ignored: HashSet<TypeId>,
+    flagged: Vec<String>,
+    warned: HashSet<ComponentId>,
+}
+
+impl Default for RollbackLint {
+    fn default() -> Self {
+        let mut lint = Self {
+            since: None,
+            ignored: default(),
+            flagged: default(),
+            warned: default(),
+        };
+
+        // Managed by the EntitySnapshotPlugin
+        lint.ignore_component::<Rollback>();
+
+        // Modified by running the GgrsSchedule itself
+        lint.ignore_resource::<Schedules>()
+            .ignore_resource::<DeterminismCheck>();
+
+        lint
+    }
+}
+
+impl RollbackLint {
+    /// Stops warnings for a [`Component`] type.
+    pub fn ignore_component<T: Component>(&mut self) -> &mut Self {
+        self.ignored.insert(TypeId::of::<T>());
+        self
+    }
+
+    /// Stops warnings for a [`Resource`] type.
+    pub fn ignore_resource<T: Resource>(&mut self) -> &mut Self {
+        self.ignored.insert(TypeId::of::<T>());
+        self
+    }
+
+    /// Iterate over the names of all types which have been warned about, in the order they were found.
+    pub fn flagged(&self) -> impl Iterator<Item = &str> + '_ {
+        self.flagged.iter().map(String::as_str)
+    }
+}
+
+/// A debugging [`Plugin`] which warns about state mutated in the [`GgrsSchedule`](`crate::GgrsSchedule`)
+/// that is not rolled back.
+///
+/// After each run of the [`AdvanceWorld`] schedule, change detection is used to find components
+/// on [`Rollback`] entities, and resources, which were changed but have no
+/// [`ComponentSnapshotPlugin`](`crate::ComponentSnapshotPlugin`) or
+/// [`ResourceSnapshotPlugin`](`crate::ResourceSnapshotPlugin`) registered. Such state will not be
+/// restored when rolling back, and is a common cause of desyncs. A warning is logged once per type.
+///
+/// Types can be excluded through the [`RollbackLint`] resource.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, RollbackLint, RollbackLintPlugin};
+/// #
+/// # let mut app = App::new();
+/// # app.add_plugins(GgrsPlugin::<GgrsConfig<u8>>::default());
+/// /// Only used for visuals, so it does not need to be rolled back.
+/// #[derive(Component)]
+/// struct Sparkle(f32);
+///
+/// #[cfg(debug_assertions)]
+/// {
+///     app.add_plugins(RollbackLintPlugin);
+///     app.world
+///         .resource_mut::<RollbackLint>()
+///         .ignore_component::<Sparkle>();
+/// }
+/// ```
+pub struct RollbackLintPlugin;
+
+impl RollbackLintPlugin {
+    /// An exclusive [`System`] which records the change tick before the
+    /// [`GgrsSchedule`](`crate::GgrsSchedule`) is run.
+    pub fn begin(world: &mut World) {
+        let tick = world.change_tick();
+        world.resource_mut::<RollbackLint>().since = Some(tick);
+    }
+
+    /// An exclusive [`System`] which warns about every type changed since [`begin`](`Self::begin`)
+    /// that is not rolled back.
+    pub fn check(world: &mut World) {
+        let Some(since) = world.resource::<RollbackLint>().since else {
+            return;
+        };
+
+        let this_run = world.change_tick();
+        let changed = |ticks: ComponentTicks| ticks.is_changed(since, this_run);
+
+        let types = world.resource::<RollbackTypes>();
+        let lint = world.resource::<RollbackLint>();
+        let components = world.components();
+
+        let unregistered = |id: ComponentId, rolled_back: &dyn Fn(TypeId) -> bool| {
+            if lint.warned.contains(&id) {
+                return false;
+            }
+
+            components
+                .get_info(id)
+                .and_then(|info| info.type_id())
+                .is_some_and(|type_id| !lint.ignored.contains(&type_id) && !rolled_back(type_id))
+        };
+
+        let mut found = Vec::<(ComponentId, &'static str)>::new();
+
+        for (id, data) in world.storages().resources.iter() {
+            if unregistered(id, &|type_id| types.contains_resource(type_id))
+                && data.get_ticks().is_some_and(changed)
+            {
+                found.push((id, "Resource"));
+            }
+        }
+
+        if let Some(rollback) = world.component_id::<Rollback>() {
+            for archetype in world
+                .archetypes()
+                .iter()
+                .filter(|archetype| archetype.contains(rollback))
+            {
+                for id in archetype.components() {
+                    if found.iter().any(|&(found, _)| found == id)
+                        || !unregistered(id, &|type_id| types.contains_component(type_id))
+                    {
+                        continue;
+                    }
+
+                    let is_changed = archetype.entities().iter().any(|entity| {
+                        world
+                            .entity(entity.entity())
+                            .get_change_ticks_by_id(id)
+                            .is_some_and(changed)
+                    });
+
+                    if is_changed {
+                        found.push((id, "Component"));
+                    }
+                }
+            }
+        }
+
+        let found = found
+            .into_iter()
+            .map(|(id, kind)| (id, kind, components.get_info(id).unwrap().name().to_owned()))
+            .collect::<Vec<_>>();
+
+        let mut lint = world.resource_mut::<RollbackLint>();
+
+        for (id, kind, name) in found {
+            warn!(
+                "{kind} {name} was changed in the GgrsSchedule, but is not rolled back. \
+                This will likely cause desyncs. Register it for rollback, or ignore it in RollbackLint."
+            );
+
+            lint.warned.insert(id);
+            lint.flagged.push(name);
+        }
+    }
+}
+
+impl Plugin for RollbackLintPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RollbackLint>()
+            .init_resource::<RollbackTypes>()
+            .add_systems(
+                AdvanceWorld,
+                (
+                    Self::begin
+                        .after(AdvanceWorldSet::First)
+                        .before(AdvanceWorldSet::Main),
+                    Self::check
+                        .after(AdvanceWorldSet::Main)
+                        .before(AdvanceWorldSet::Last),
+                ),
+            );
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    ChecksumHistory, ChecksumMismatch, DesyncDiagnosticsPlugin, FrameChecksumReport,
+    RollbackFrameCount, RollbackFrameOffset, RollbackOrdered, SaveWorld, SaveWorldSet,
+};
+
+/// A [`Resource`] holding the [`FrameChecksumReports`](`FrameChecksumReport`) from the first time
+/// each frame was simulated during a [`SyncTest`](`crate::Session::SyncTest`) session.
+#[derive(Resource, Debug)]
+pub struct SyncTestHistory {
+    original: BTreeMap<i32, FrameChecksumReport>,
+    capacity: usize,
+}
+
+impl SyncTestHistory {
+    /// Create a new [`SyncTestHistory`] holding at most `capacity` frames.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            original: default(),
+            capacity,
+        }
+    }
+
+    /// Get the report from the first simulation of a particular frame, if it is still held.
+    pub fn original(&self, frame: i32) -> Option<&FrameChecksumReport> {
+        self.original.get(&frame)
+    }
+}
+
+/// An [`Event`] describing the first frame which diverged when resimulated during a
+/// [`SyncTest`](`crate::Session::SyncTest`) session, and exactly which types diverged.
+///
+/// In each [`ChecksumMismatch`], `local` refers to the original simulation of the frame, and
+/// `remote` to the resimulation.
+#[derive(Event, Clone, Debug)]
+pub struct SyncTestMismatch {
+    /// The first frame which diverged.
+    pub frame: i32,
+    /// All types which differed on that frame.
+    pub mismatches: Vec<ChecksumMismatch>,
+}
+
+/// A [`Plugin`] which pinpoints the cause of a checksum mismatch in a
+/// [`SyncTest`](`crate::Session::SyncTest`) session.
+///
+/// A [`SyncTestSession`](`ggrs::SyncTestSession`) resimulates every frame, comparing the
+/// [`Checksum`](`crate::Checksum`) against the original simulation. This plugin keeps the
+/// [`ChecksumParts`](`crate::ChecksumPart`) of the original simulation in a [`SyncTestHistory`],
+/// and when the session reports a mismatch, compares them against the resimulation to find the
+/// first frame, type and [`Rollback`](`crate::Rollback`) entity which diverged. The result is
+/// logged and sent as a [`SyncTestMismatch`] event.
+///
+/// This plugin adds the [`DesyncDiagnosticsPlugin`] if it has not been added already.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, SyncTestDiagnosticsPlugin};
+/// #
+/// # fn start(session: Session<GgrsConfig<u8>>) {
+/// # let mut app = App::new();
+/// #[derive(Component, Clone, Copy, Hash)]
+/// struct Health(u32);
+///
+/// app.rollback_component_with_clone::<Health>();
+/// app.checksum_component_with_hash::<Health>();
+///
+/// app.add_plugins(SyncTestDiagnosticsPlugin::default());
+/// # }
+/// ```
+pub struct SyncTestDiagnosticsPlugin {
+    /// The number of frames to keep original [`FrameChecksumReports`](`FrameChecksumReport`) for.
+    /// Must be at least the check distance of the [`SyncTestSession`](`ggrs::SyncTestSession`).
+    pub history: usize,
+}
+
+impl Default for SyncTestDiagnosticsPlugin {
+    fn default() -> Self {
+        Self { history: 128 }
+    }
+}
+
+impl SyncTestDiagnosticsPlugin {
+    /// A [`System`] which keeps the [`FrameChecksumReport`] for a frame the first time it is saved.
+    pub fn record(
+        mut sync_test_history: ResMut<SyncTestHistory>,
+        history: Res<ChecksumHistory>,
+        frame: Res<RollbackFrameCount>,
+    ) {
+        let Some(report) = history.get(frame.0) else {
+            return;
+        };
+
+        let capacity = sync_test_history.capacity;
+        let original = &mut sync_test_history.original;
+
+        original.entry(frame.0).or_insert_with(|| report.clone());
+
+        while original.len() > capacity {
+            original.pop_first();
+        }
+    }
+
+    /// Compares the original and resimulated reports for the provided GGRS frames, logging and
+    /// sending a [`SyncTestMismatch`] for the first frame which diverged.
+    pub(crate) fn diagnose(world: &mut World, mismatched_frames: &[i32]) {
+        let (Some(sync_test_history), Some(history)) = (
+            world.get_resource::<SyncTestHistory>(),
+            world.get_resource::<ChecksumHistory>(),
+        ) else {
+            return;
+        };
+
+        let offset = world
+            .get_resource::<RollbackFrameOffset>()
+            .map(|offset| offset.0)
+            .unwrap_or_default();
+
+        let mut frames = mismatched_frames
+            .iter()
+            .map(|frame| frame + offset)
+            .collect::<Vec<_>>();
+
+        frames.sort_unstable();
+
+        let first = frames.into_iter().find_map(|frame| {
+            let (Some(original), Some(resimulated)) =
+                (sync_test_history.original(frame), history.get(frame))
+            else {
+                warn!("Checksum reports for frame {frame} are no longer in the history");
+                return None;
+            };
+
+            let mismatches = original.diff(resimulated);
+
+            (!mismatches.is_empty()).then_some(SyncTestMismatch { frame, mismatches })
+        });
+
+        let Some(mismatch) = first else {
+            warn!("Unable to find which types diverged. Are they registered for checksums?");
+            return;
+        };
+
+        let ordered = world.get_resource::<RollbackOrdered>();
+
+        for type_mismatch in &mismatch.mismatches {
+            error!(
+                "Frame {} diverged when resimulated in {}: original {:X?}, resimulated {:X?}",
+                mismatch.frame,
+                bevy::utils::get_short_name(&type_mismatch.name),
+                type_mismatch.local,
+                type_mismatch.remote
+            );
+
+            for entity in &type_mismatch.entities {
+                let rollback = ordered.and_then(|ordered| ordered.get(entity.order));
+
+                error!(
+                    "  Rollback entity #{} ({:?}): original {:X?}, resimulated {:X?}",
+                    entity.order, rollback, entity.local, entity.remote
+                );
+            }
+        }
+
+        world.send_event(mismatch);
+    }
+}
+
+impl Plugin for SyncTestDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<DesyncDiagnosticsPlugin>() {
+            app.add_plugins(DesyncDiagnosticsPlugin::default());
+        }
+
+        app.insert_resource(SyncTestHistory::new(self.history))
+            .add_event::<SyncTestMismatch>()
+            .add_systems(
+                SaveWorld,
+                Self::record
+                    .after(DesyncDiagnosticsPlugin::record)
+                    .before(SaveWorldSet::Snapshot),
+            );
+    }
+}
@@ -0,0 +1,181 @@
+use std::marker::PhantomData;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    events::FrameHistory, AdvanceWorld, AdvanceWorldSet, ConfirmedFrameCount, RollbackFrameCount,
+    RollbackLint,
+};
+
+/// When effects queued with a [`RollbackEffectWriter`] are released as [`Events`](`Event`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EffectRelease {
+    /// Effects are released as soon as the frame which queued them is first simulated. If a
+    /// rollback removes them, an [`EffectCancelled`] event is sent. Suitable for effects which
+    /// must be responsive, such as sounds for local actions.
+    #[default]
+    Predicted,
+    /// Effects are released once the frame which queued them has been confirmed, so they are
+    /// never cancelled. Suitable for effects which cannot be undone, such as achievements.
+    Confirmed,
+}
+
+/// An [`Event`] sent when an effect released with [`EffectRelease::Predicted`] was not queued
+/// again after rolling back.
+#[derive(Event, Clone, Debug)]
+pub struct EffectCancelled<E> {
+    /// The frame which queued the effect.
+    pub frame: i32,
+    /// The cancelled effect.
+    pub effect: E,
+}
+
+/// A [`Resource`] holding the effects queued by each unconfirmed frame.
+#[derive(Resource)]
+pub struct RollbackEffectQueue<E> {
+    release: EffectRelease,
+    queued: Vec<E>,
+    history: FrameHistory<E>,
+}
+
+impl<E> RollbackEffectQueue<E> {
+    /// Creates a new queue, releasing effects according to the provided [`EffectRelease`].
+    pub fn new(release: EffectRelease) -> Self {
+        Self {
+            release,
+            queued: Vec::new(),
+            history: default(),
+        }
+    }
+
+    /// When effects in this queue are released.
+    pub fn release(&self) -> EffectRelease {
+        self.release
+    }
+}
+
+/// A [`SystemParam`] for queueing effects of type `E` inside the [`GgrsSchedule`](`crate::GgrsSchedule`),
+/// such as sounds or particles.
+///
+/// Effects are de-duplicated across resimulations of the same frame, and released to systems
+/// outside the rollback schedules as [`Events`](`Event`) according to the [`EffectRelease`] chosen
+/// in [`GgrsApp::add_rollback_effect`](`crate::GgrsApp::add_rollback_effect`).
+#[derive(SystemParam)]
+pub struct RollbackEffectWriter<'w, E: Event + Clone + PartialEq> {
+    queue: ResMut<'w, RollbackEffectQueue<E>>,
+}
+
+impl<'w, E: Event + Clone + PartialEq> RollbackEffectWriter<'w, E> {
+    /// Queues an effect for the current frame.
+    pub fn send(&mut self, effect: E) {
+        self.queue.queued.push(effect);
+    }
+}
+
+/// A [`Plugin`] which releases effects queued with a [`RollbackEffectWriter`] as [`Events`](`Event`).
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, EffectCancelled, EffectRelease};
+/// #
+/// #[derive(Event, Clone, PartialEq)]
+/// struct PlaySound(&'static str);
+///
+/// fn jump(mut sounds: RollbackEffectWriter<PlaySound>) {
+///     sounds.send(PlaySound("jump.ogg"));
+/// }
+///
+/// fn play_sounds(
+///     mut sounds: EventReader<PlaySound>,
+///     mut cancelled: EventReader<EffectCancelled<PlaySound>>,
+/// ) {
+///     for sound in sounds.read() {
+///         info!("Playing {}", sound.0);
+///     }
+///
+///     for cancelled in cancelled.read() {
+///         info!("Stopping {}, if it is still playing", cancelled.effect.0);
+///     }
+/// }
+///
+/// # let mut app = App::new();
+/// # app.add_plugins(GgrsPlugin::<GgrsConfig<u8>>::default());
+/// app.add_rollback_effect::<PlaySound>(EffectRelease::Predicted)
+///     .add_systems(GgrsSchedule, jump)
+///     .add_systems(Update, play_sounds);
+/// ```
+pub struct RollbackEffectsPlugin<E> {
+    release: EffectRelease,
+    _phantom: PhantomData<E>,
+}
+
+impl<E> RollbackEffectsPlugin<E> {
+    /// Creates a new plugin, releasing effects according to the provided [`EffectRelease`].
+    pub fn new(release: EffectRelease) -> Self {
+        Self {
+            release,
+            _phantom: default(),
+        }
+    }
+}
+
+impl<E> Default for RollbackEffectsPlugin<E> {
+    fn default() -> Self {
+        Self::new(default())
+    }
+}
+
+impl<E: Event + Clone + PartialEq> RollbackEffectsPlugin<E> {
+    /// A [`System`] which discards any effects queued outside of a frame.
+    pub fn begin(mut queue: ResMut<RollbackEffectQueue<E>>) {
+        queue.queued.clear();
+    }
+
+    /// A [`System`] which records the effects queued during the current frame, and releases
+    /// or cancels effects as required.
+    pub fn release(
+        mut queue: ResMut<RollbackEffectQueue<E>>,
+        mut effects: EventWriter<E>,
+        mut cancellations: EventWriter<EffectCancelled<E>>,
+        frame: Res<RollbackFrameCount>,
+        confirmed_frame: Res<ConfirmedFrameCount>,
+    ) {
+        let queue = queue.as_mut();
+        let frame = frame.0;
+        let queued = std::mem::take(&mut queue.queued);
+        let (added, cancelled) = queue.history.record(frame, queued);
+        // While resimulating, later frames have not been recorded again yet
+        let confirmed = queue.history.confirm(confirmed_frame.0.min(frame));
+
+        match queue.release {
+            EffectRelease::Predicted => {
+                cancellations.send_batch(
+                    cancelled
+                        .into_iter()
+                        .map(|effect| EffectCancelled { frame, effect }),
+                );
+                effects.send_batch(added);
+            }
+            EffectRelease::Confirmed => {
+                effects.send_batch(confirmed.into_iter().map(|(_, effect)| effect));
+            }
+        }
+    }
+}
+
+impl<E: Event + Clone + PartialEq> Plugin for RollbackEffectsPlugin<E> {
+    fn build(&self, app: &mut App) {
+        // The queue is intentionally not rolled back
+        app.init_resource::<RollbackLint>()
+            .world
+            .resource_mut::<RollbackLint>()
+            .ignore_resource::<RollbackEffectQueue<E>>();
+
+        app.insert_resource(RollbackEffectQueue::<E>::new(self.release))
+            .add_event::<E>()
+            .add_event::<EffectCancelled<E>>()
+            .add_systems(AdvanceWorld, Self::begin.in_set(AdvanceWorldSet::First))
+            .add_systems(AdvanceWorld, Self::release.in_set(AdvanceWorldSet::Last));
+    }
+}
@@ -0,0 +1,309 @@
+use std::{collections::BTreeMap, marker::PhantomData};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    AdvanceWorld, AdvanceWorldSet, CloneStrategy, ConfirmedFrameCount, ResourceSnapshotPlugin,
+    RollbackFrameCount,
+};
+
+/// The items produced by each simulated frame, kept until the frame is confirmed so that
+/// resimulations can be compared against it.
+pub(crate) struct FrameHistory<T> {
+    frames: BTreeMap<i32, Vec<T>>,
+}
+
+impl<T> Default for FrameHistory<T> {
+    fn default() -> Self {
+        Self {
+            frames: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T: PartialEq> FrameHistory<T> {
+    /// Records the items produced by a simulation of `frame`, returning the items which are new
+    /// and the items from a previous simulation of `frame` which were not produced again.
+    pub(crate) fn record(&mut self, frame: i32, items: Vec<T>) -> (Vec<T>, Vec<T>)
+    where
+        T: Clone,
+    {
+        let mut previous = self.frames.remove(&frame).unwrap_or_default();
+        let mut added = Vec::new();
+
+        for item in &items {
+            match previous.iter().position(|old| old == item) {
+                Some(index) => {
+                    previous.swap_remove(index);
+                }
+                None => added.push(item.clone()),
+            }
+        }
+
+        if !items.is_empty() {
+            self.frames.insert(frame, items);
+        }
+
+        (added, previous)
+    }
+
+    /// Removes and returns all items from frames up to and including `confirmed_frame`, oldest first.
+    pub(crate) fn confirm(&mut self, confirmed_frame: i32) -> Vec<(i32, T)> {
+        let mut confirmed = Vec::new();
+
+        while let Some(entry) = self.frames.first_entry() {
+            if *entry.key() > confirmed_frame {
+                break;
+            }
+
+            let (frame, items) = entry.remove_entry();
+            confirmed.extend(items.into_iter().map(|item| (frame, item)));
+        }
+
+        confirmed
+    }
+}
+
+/// A rolled back buffer of events of type `E`, written with a [`RollbackEventWriter`] and read
+/// with a [`RollbackEventReader`] inside the [`GgrsSchedule`](`crate::GgrsSchedule`).
+///
+/// Unlike [`Events`], this buffer is restored when rolling back, so resimulated frames do not
+/// send duplicate events. Events sent during a frame are read during the following frame,
+/// regardless of system order, so each event is read exactly once by every reader.
+///
+/// Added by [`GgrsApp::add_rollback_event`](`crate::GgrsApp::add_rollback_event`).
+#[derive(Resource, Clone, Debug)]
+pub struct RollbackEvents<E> {
+    current: Vec<E>,
+    previous: Vec<E>,
+}
+
+impl<E> Default for RollbackEvents<E> {
+    fn default() -> Self {
+        Self {
+            current: Vec::new(),
+            previous: Vec::new(),
+        }
+    }
+}
+
+impl<E> RollbackEvents<E> {
+    /// Sends an event, to be read during the next frame.
+    pub fn send(&mut self, event: E) {
+        self.current.push(event);
+    }
+
+    /// Iterate over the events sent during the previous frame.
+    pub fn iter_previous(&self) -> impl ExactSizeIterator<Item = &E> + '_ {
+        self.previous.iter()
+    }
+
+    /// Iterate over the events sent so far during the current frame.
+    pub fn iter_current(&self) -> impl ExactSizeIterator<Item = &E> + '_ {
+        self.current.iter()
+    }
+
+    /// Moves the events of the current frame into the previous frame, discarding the oldest.
+    fn rotate(&mut self) {
+        std::mem::swap(&mut self.current, &mut self.previous);
+        self.current.clear();
+    }
+}
+
+/// A [`SystemParam`] for sending [`RollbackEvents`] inside the [`GgrsSchedule`](`crate::GgrsSchedule`).
+///
+/// As with [`EventWriter`], systems writing the same event type must be ordered relative to each
+/// other, as the order of events would otherwise differ between peers.
+#[derive(SystemParam)]
+pub struct RollbackEventWriter<'w, E: Event + Clone> {
+    events: ResMut<'w, RollbackEvents<E>>,
+}
+
+impl<'w, E: Event + Clone> RollbackEventWriter<'w, E> {
+    /// Sends an event, to be read during the next frame.
+    pub fn send(&mut self, event: E) {
+        self.events.send(event);
+    }
+
+    /// Sends a list of events, to be read during the next frame.
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = E>) {
+        for event in events {
+            self.events.send(event);
+        }
+    }
+}
+
+/// A [`SystemParam`] for reading [`RollbackEvents`] inside the [`GgrsSchedule`](`crate::GgrsSchedule`).
+///
+/// Events sent during a frame are read during the following frame. Since nothing is tracked per
+/// reader, the same events are returned every time [`read`](`Self::read`) is called within a frame.
+#[derive(SystemParam)]
+pub struct RollbackEventReader<'w, E: Event + Clone> {
+    events: Res<'w, RollbackEvents<E>>,
+}
+
+impl<'w, E: Event + Clone> RollbackEventReader<'w, E> {
+    /// Iterate over the events sent during the previous frame.
+    pub fn read(&self) -> impl ExactSizeIterator<Item = &E> + '_ {
+        self.events.iter_previous()
+    }
+
+    /// The number of events sent during the previous frame.
+    pub fn len(&self) -> usize {
+        self.events.previous.len()
+    }
+
+    /// Returns `true` if no events were sent during the previous frame.
+    pub fn is_empty(&self) -> bool {
+        self.events.previous.is_empty()
+    }
+}
+
+/// How a [`RollbackEventOutcome`] relates to the event it carries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RollbackEventStatus {
+    /// The event was sent by a frame which has not been confirmed yet, and may still be cancelled.
+    Predicted,
+    /// The event was sent by a confirmed frame, and will not be cancelled.
+    Confirmed,
+    /// A previously [`Predicted`](`RollbackEventStatus::Predicted`) event was not sent again
+    /// after rolling back.
+    Cancelled,
+}
+
+/// An [`Event`] sent outside the rollback schedules, describing what happened to an event sent
+/// through [`RollbackEvents`].
+///
+/// Every event is reported as [`Predicted`](`RollbackEventStatus::Predicted`) once, when it is
+/// first sent, and later as either [`Confirmed`](`RollbackEventStatus::Confirmed`) or
+/// [`Cancelled`](`RollbackEventStatus::Cancelled`). Identical events sent again by a
+/// resimulation of the same frame are not reported again.
+#[derive(Event, Clone, Debug)]
+pub struct RollbackEventOutcome<E> {
+    /// The frame which sent the event.
+    pub frame: i32,
+    /// What happened to the event.
+    pub status: RollbackEventStatus,
+    /// The event itself.
+    pub event: E,
+}
+
+/// A [`Resource`] holding the events sent by each unconfirmed frame, used to produce
+/// [`RollbackEventOutcomes`](`RollbackEventOutcome`).
+#[derive(Resource)]
+pub struct RollbackEventHistory<E> {
+    history: FrameHistory<E>,
+}
+
+impl<E> Default for RollbackEventHistory<E> {
+    fn default() -> Self {
+        Self { history: default() }
+    }
+}
+
+/// A [`Plugin`] which sets up [`RollbackEvents`] for the event type `E`.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, RollbackEventOutcome, RollbackEventStatus};
+/// #
+/// #[derive(Resource, Clone)]
+/// struct BossHealth(u32);
+///
+/// #[derive(Event, Clone, PartialEq)]
+/// struct Hit {
+///     damage: u32,
+/// }
+///
+/// fn attack(mut hits: RollbackEventWriter<Hit>) {
+///     hits.send(Hit { damage: 10 });
+/// }
+///
+/// fn apply_damage(hits: RollbackEventReader<Hit>, mut health: ResMut<BossHealth>) {
+///     for hit in hits.read() {
+///         health.0 = health.0.saturating_sub(hit.damage);
+///     }
+/// }
+///
+/// fn show_damage_numbers(mut outcomes: EventReader<RollbackEventOutcome<Hit>>) {
+///     for outcome in outcomes.read() {
+///         match outcome.status {
+///             RollbackEventStatus::Predicted => { /* Spawn a damage number */ }
+///             RollbackEventStatus::Cancelled => { /* Remove it again */ }
+///             RollbackEventStatus::Confirmed => {}
+///         }
+///     }
+/// }
+///
+/// # let mut app = App::new();
+/// # app.add_plugins(GgrsPlugin::<GgrsConfig<u8>>::default());
+/// app.rollback_resource_with_clone::<BossHealth>()
+///     .add_rollback_event::<Hit>()
+///     .add_systems(GgrsSchedule, (attack, apply_damage).chain())
+///     .add_systems(Update, show_damage_numbers);
+/// ```
+pub struct RollbackEventsPlugin<E> {
+    _phantom: PhantomData<E>,
+}
+
+impl<E> Default for RollbackEventsPlugin<E> {
+    fn default() -> Self {
+        Self {
+            _phantom: default(),
+        }
+    }
+}
+
+impl<E: Event + Clone + PartialEq> RollbackEventsPlugin<E> {
+    /// A [`System`] which makes the events sent during the previous frame readable.
+    pub fn rotate(mut events: ResMut<RollbackEvents<E>>) {
+        events.rotate();
+    }
+
+    /// A [`System`] which sends a [`RollbackEventOutcome`] for each event which was sent,
+    /// cancelled, or confirmed since the last time it ran.
+    pub fn track(
+        events: Res<RollbackEvents<E>>,
+        mut history: ResMut<RollbackEventHistory<E>>,
+        mut outcomes: EventWriter<RollbackEventOutcome<E>>,
+        frame: Res<RollbackFrameCount>,
+        confirmed_frame: Res<ConfirmedFrameCount>,
+    ) {
+        let frame = frame.0;
+        let (predicted, cancelled) = history
+            .history
+            .record(frame, events.iter_current().cloned().collect());
+
+        for (status, events) in [
+            (RollbackEventStatus::Cancelled, cancelled),
+            (RollbackEventStatus::Predicted, predicted),
+        ] {
+            outcomes.send_batch(events.into_iter().map(|event| RollbackEventOutcome {
+                frame,
+                status,
+                event,
+            }));
+        }
+
+        // While resimulating, later frames have not been recorded again yet
+        for (frame, event) in history.history.confirm(confirmed_frame.0.min(frame)) {
+            outcomes.send(RollbackEventOutcome {
+                frame,
+                status: RollbackEventStatus::Confirmed,
+                event,
+            });
+        }
+    }
+}
+
+impl<E: Event + Clone + PartialEq> Plugin for RollbackEventsPlugin<E> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RollbackEvents<E>>()
+            .init_resource::<RollbackEventHistory<E>>()
+            .add_event::<RollbackEventOutcome<E>>()
+            .add_plugins(ResourceSnapshotPlugin::<CloneStrategy<RollbackEvents<E>>>::default())
+            .add_systems(AdvanceWorld, Self::rotate.in_set(AdvanceWorldSet::First))
+            .add_systems(AdvanceWorld, Self::track.in_set(AdvanceWorldSet::Last));
+    }
+}
@@ -6,6 +6,7 @@
 use bevy::{
     ecs::{
         entity::MapEntities,
+        event::ManualEventReader,
         schedule::{ExecutorKind, LogLevel, ScheduleBuildSettings, ScheduleLabel},
     },
     prelude::*,
@@ -14,25 +15,46 @@ use bevy::{
 use ggrs::{Config, InputStatus, P2PSession, PlayerHandle, SpectatorSession, SyncTestSession};
 use std::{fmt::Debug, hash::Hash, marker::PhantomData, net::SocketAddr};
 
+pub use bevy_ggrs_macros::Rollback;
 pub use ggrs;
 
+pub use diagnostics::*;
+pub use effects::*;
+pub use events::*;
+pub use network::*;
+pub use register::*;
+pub use replay::*;
 pub use rollback::*;
+pub use session_events::*;
 pub use snapshot::*;
 pub use time::*;
 
+pub(crate) mod diagnostics;
+pub(crate) mod effects;
+pub(crate) mod events;
+pub(crate) mod network;
+pub(crate) mod register;
+pub(crate) mod replay;
 pub(crate) mod rollback;
 pub(crate) mod schedule_systems;
+pub(crate) mod session_events;
 pub(crate) mod snapshot;
 pub(crate) mod time;
 
 pub mod prelude {
     pub use crate::{
         snapshot::prelude::*, AddRollbackCommandExtension, GgrsApp, GgrsConfig, GgrsPlugin,
-        GgrsSchedule, GgrsTime, PlayerInputs, ReadInputs, Rollback, Session,
+        GgrsSchedule, GgrsTime, PlayerInputs, ReadInputs, RegisterRollback, Rollback,
+        RollbackEffectWriter, RollbackEventReader, RollbackEventWriter, Session,
     };
     pub use ggrs::{GgrsEvent, PlayerType, SessionBuilder};
 }
 
+#[doc(hidden)]
+pub mod __macro_exports {
+    pub use bevy::app::App;
+}
+
 /// A sensible default [GGRS Config](`ggrs::Config`) type suitable for most applications.
 ///
 /// If you require a more specialized configuration, you can create your own type implementing
@@ -55,6 +77,7 @@ where
 }
 
 const DEFAULT_FPS: usize = 60;
+const DEFAULT_MAX_PREDICTION: usize = 8;
 
 #[derive(ScheduleLabel, Debug, Hash, PartialEq, Eq, Clone)]
 pub struct GgrsSchedule;
@@ -66,6 +89,7 @@ pub enum Session<T: Config> {
     SyncTest(SyncTestSession<T>),
     P2P(P2PSession<T>),
     Spectator(SpectatorSession<T>),
+    Replay(ReplaySession<T>),
 }
 
 // TODO: more specific name to avoid conflicts?
@@ -78,6 +102,10 @@ struct FixedTimestepData {
     accumulator: Duration,
     /// boolean to see if we should run slow to let remote clients catch up
     run_slow: bool,
+    /// number of frames remaining to be skipped to let remote clients catch up
+    skip_frames: u32,
+    /// time left over from previous updates, to be consumed at most one step at a time
+    backlog: Duration,
 }
 
 impl Default for FixedTimestepData {
@@ -85,10 +113,16 @@ impl Default for FixedTimestepData {
         Self {
             accumulator: Duration::ZERO,
             run_slow: false,
+            skip_frames: 0,
+            backlog: Duration::ZERO,
         }
     }
 }
 
+/// Tracks which [`WaitRecommendations`](`WaitRecommendation`) have already been honoured.
+#[derive(Resource, Default)]
+struct WaitRecommendationReader(ManualEventReader<WaitRecommendation>);
+
 /// Keeps track of the current frame the rollback simulation is in
 #[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct RollbackFrameCount(i32);
@@ -99,6 +133,18 @@ impl From<RollbackFrameCount> for i32 {
     }
 }
 
+/// The [`RollbackFrameCount`] at which the current [`Session`] started. Sessions always begin
+/// at frame `0`, so this allows a [`Session`] to be started part way through a match, such as
+/// after a [state transfer](`StateTransferHost`) or loading a [`SerializedWorld`].
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RollbackFrameOffset(pub(crate) i32);
+
+impl From<RollbackFrameOffset> for i32 {
+    fn from(value: RollbackFrameOffset) -> i32 {
+        value.0
+    }
+}
+
 /// The most recently confirmed frame. Any information for frames stored before this point can be safely discarded.
 #[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ConfirmedFrameCount(i32);
@@ -110,9 +156,16 @@ impl From<ConfirmedFrameCount> for i32 {
 }
 
 /// The maximum prediction window for this [`Session`], provided as a concrete [`Resource`].
+/// Snapshot storages are sized from this value, see [`SnapshotDepth`].
 #[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct MaxPredictionWindow(usize);
 
+impl From<MaxPredictionWindow> for usize {
+    fn from(value: MaxPredictionWindow) -> usize {
+        value.0
+    }
+}
+
 /// Inputs from local players. You have to fill this resource in the ReadInputs schedule.
 #[derive(Resource)]
 pub struct LocalInputs<C: Config>(pub HashMap<PlayerHandle, C::Input>);
@@ -137,6 +190,12 @@ pub struct SaveWorld;
 #[derive(ScheduleLabel, Debug, Hash, PartialEq, Eq, Clone)]
 pub struct AdvanceWorld;
 
+/// Label for the schedule which updates the [`ChecksumParts`](`ChecksumPart`) of the current world.
+/// This is run during [`SaveWorldSet::Checksum`] in the [`SaveWorld`] schedule, but can also be
+/// run on its own to checksum the world without saving a snapshot.
+#[derive(ScheduleLabel, Debug, Hash, PartialEq, Eq, Clone)]
+pub struct ChecksumWorld;
+
 /// GGRS plugin for bevy.
 ///
 /// # Rollback
@@ -191,17 +250,23 @@ impl<C: Config> Default for GgrsPlugin<C> {
 impl<C: Config> Plugin for GgrsPlugin<C> {
     fn build(&self, app: &mut App) {
         app.init_resource::<RollbackFrameCount>()
+            .init_resource::<RollbackFrameOffset>()
             .init_resource::<ConfirmedFrameCount>()
             .init_resource::<MaxPredictionWindow>()
+            .init_resource::<SnapshotDepth>()
             .init_resource::<RollbackOrdered>()
             .init_resource::<LocalPlayers>()
             .init_resource::<FixedTimestepData>()
+            .init_resource::<WaitRecommendationReader>()
             .init_schedule(ReadInputs)
             .init_schedule(LoadWorld)
             .init_schedule(SaveWorld)
+            .init_schedule(ChecksumWorld)
             .edit_schedule(AdvanceWorld, |schedule| {
                 // AdvanceWorld is mostly a facilitator for GgrsSchedule, so SingleThreaded avoids overhead
                 // This can be overridden if desired.
+                // GgrsSchedule, LoadWorld, SaveWorld and ChecksumWorld keep the default executor, so
+                // game logic and snapshots of independent types still run in parallel.
                 schedule.set_executor_kind(ExecutorKind::SingleThreaded);
             })
             .edit_schedule(GgrsSchedule, |schedule| {
@@ -217,6 +282,7 @@ impl<C: Config> Plugin for GgrsPlugin<C> {
                 EntitySnapshotPlugin,
                 EntityChecksumPlugin,
                 GgrsTimePlugin,
+                SessionEventsPlugin::<C>::default(),
                 ResourceSnapshotPlugin::<CloneStrategy<RollbackOrdered>>::default(),
                 ComponentSnapshotPlugin::<ReflectStrategy<Parent>>::default(),
                 ComponentMapEntitiesPlugin::<Parent>::default(),
@@ -304,6 +370,23 @@ pub trait GgrsApp {
     fn checksum_resource<Type>(&mut self, hasher: for<'a> fn(&'a Type) -> u64) -> &mut Self
     where
         Type: Resource;
+
+    /// Sets up [`RollbackEvents`] for an event type, which are rolled back using [`Clone`].
+    fn add_rollback_event<Type>(&mut self) -> &mut Self
+    where
+        Type: Event + Clone + PartialEq;
+
+    /// Sets up a [`RollbackEffectWriter`] for an effect type, releasing effects as [`Events`]
+    /// according to the provided [`EffectRelease`].
+    fn add_rollback_effect<Type>(&mut self, release: EffectRelease) -> &mut Self
+    where
+        Type: Event + Clone + PartialEq;
+
+    /// Registers one or more types implementing [`RegisterRollback`], usually through
+    /// `#[derive(Rollback)]`. Pass a tuple to register several types at once.
+    fn register_rollback_types<Types>(&mut self) -> &mut Self
+    where
+        Types: RegisterRollback;
 }
 
 impl GgrsApp for App {
@@ -396,4 +479,27 @@ impl GgrsApp for App {
     {
         self.add_plugins(ResourceChecksumPlugin::<Type>(hasher))
     }
+
+    fn add_rollback_event<Type>(&mut self) -> &mut Self
+    where
+        Type: Event + Clone + PartialEq,
+    {
+        self.add_plugins(RollbackEventsPlugin::<Type>::default())
+    }
+
+    fn add_rollback_effect<Type>(&mut self, release: EffectRelease) -> &mut Self
+    where
+        Type: Event + Clone + PartialEq,
+    {
+        self.add_plugins(RollbackEffectsPlugin::<Type>::new(release))
+    }
+
+    fn register_rollback_types<Types>(&mut self) -> &mut Self
+    where
+        Types: RegisterRollback,
+    {
+        Types::register_rollback(self);
+
+        self
+    }
 }
@@ -0,0 +1,205 @@
+use bevy::utils::Duration;
+
+/// Datagrams which would wait longer than this for bandwidth to become available are dropped,
+/// like a full router buffer.
+const MAX_QUEUE_DELAY: Duration = Duration::from_secs(1);
+
+/// Datagrams held back to be reordered are released after this much extra delay if no other
+/// datagram was sent over the same link in the meantime.
+const MAX_REORDER_DELAY: Duration = Duration::from_millis(100);
+
+/// Artificial conditions applied to datagrams sent over a [`LoopbackNetwork`](`crate::LoopbackNetwork`)
+/// or a [`NetworkSimulatorSocket`](`crate::NetworkSimulatorSocket`).
+///
+/// The default conditions deliver every datagram once, in order, without delay.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NetworkConditions {
+    /// The time a datagram spends in flight before it can be received.
+    pub latency: Duration,
+    /// The maximum additional time, chosen uniformly at random, added to the latency of each datagram.
+    pub jitter: Duration,
+    /// The probability, between `0.0` and `1.0`, that a datagram is dropped.
+    pub loss: f64,
+    /// The probability, between `0.0` and `1.0`, that a datagram is delivered twice.
+    pub duplicate: f64,
+    /// The probability, between `0.0` and `1.0`, that a datagram is held back and delivered
+    /// after the next datagram sent over the same link. If no other datagram is sent within
+    /// 100ms of its delivery time, it is delivered late instead.
+    pub reorder: f64,
+    /// The number of bytes per second which can be sent over each link, or [`None`] for no limit.
+    /// Datagrams beyond this limit are queued, and dropped once the queue holds a second's worth.
+    pub bandwidth: Option<u32>,
+}
+
+/// A small, seedable PRNG (SplitMix64), so that conditions can be applied identically across runs.
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in the range `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// A datagram which has been scheduled for delivery.
+pub(crate) struct Scheduled<P> {
+    pub(crate) deliver_at: Duration,
+    pub(crate) sequence: u64,
+    pub(crate) payload: P,
+}
+
+/// The state of a single link between a sender and a receiver.
+pub(crate) struct Link<P> {
+    busy_until: Duration,
+    held: Option<Scheduled<P>>,
+}
+
+impl<P> Default for Link<P> {
+    fn default() -> Self {
+        Self {
+            busy_until: Duration::ZERO,
+            held: None,
+        }
+    }
+}
+
+/// Applies [`NetworkConditions`] to datagrams, deciding when (and whether) they are delivered.
+pub(crate) struct Conditioner {
+    rng: SplitMix64,
+    sequence: u64,
+}
+
+impl Conditioner {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self {
+            rng: SplitMix64::new(seed),
+            sequence: 0,
+        }
+    }
+
+    /// Schedules a datagram of `size` bytes sent over `link` at time `now`, pushing zero or more
+    /// copies of it onto `scheduled`.
+    pub(crate) fn send<P: Clone>(
+        &mut self,
+        conditions: &NetworkConditions,
+        link: &mut Link<P>,
+        now: Duration,
+        size: usize,
+        payload: P,
+        scheduled: &mut Vec<Scheduled<P>>,
+    ) {
+        scheduled.extend(self.release_overdue(link, now));
+
+        if self.rng.next_f64() < conditions.loss {
+            return;
+        }
+
+        let copies = if self.rng.next_f64() < conditions.duplicate {
+            2
+        } else {
+            1
+        };
+
+        for _ in 0..copies {
+            let departure = now.max(link.busy_until);
+
+            if departure - now > MAX_QUEUE_DELAY {
+                return;
+            }
+
+            if let Some(bandwidth) = conditions.bandwidth {
+                link.busy_until =
+                    departure + Duration::from_secs_f64(size as f64 / bandwidth as f64);
+            }
+
+            let jitter = conditions.jitter.mul_f64(self.rng.next_f64());
+            let reorder = self.rng.next_f64() < conditions.reorder;
+
+            self.sequence += 1;
+
+            let datagram = Scheduled {
+                deliver_at: departure.max(link.busy_until) + conditions.latency + jitter,
+                sequence: self.sequence,
+                payload: payload.clone(),
+            };
+
+            if reorder && link.held.is_none() {
+                link.held = Some(datagram);
+                continue;
+            }
+
+            let deliver_at = datagram.deliver_at;
+            scheduled.push(datagram);
+
+            // A held datagram is released directly behind the one which overtook it
+            if let Some(mut held) = link.held.take() {
+                self.sequence += 1;
+                held.deliver_at = held.deliver_at.max(deliver_at);
+                held.sequence = self.sequence;
+                scheduled.push(held);
+            }
+        }
+    }
+
+    /// Releases the datagram held back on `link`, if it has waited [`MAX_REORDER_DELAY`] past its
+    /// delivery time by `now` without being overtaken.
+    pub(crate) fn release_overdue<P>(
+        &mut self,
+        link: &mut Link<P>,
+        now: Duration,
+    ) -> Option<Scheduled<P>> {
+        let overdue = link
+            .held
+            .as_ref()
+            .is_some_and(|held| held.deliver_at + MAX_REORDER_DELAY <= now);
+
+        if !overdue {
+            return None;
+        }
+
+        let mut held = link.held.take()?;
+        self.sequence += 1;
+        held.deliver_at += MAX_REORDER_DELAY;
+        held.sequence = self.sequence;
+
+        Some(held)
+    }
+}
+
+/// Removes every datagram due at or before `now` from `in_flight` which satisfies `filter`,
+/// returning them in delivery order.
+pub(crate) fn take_due<K, P>(
+    in_flight: &mut Vec<(K, Scheduled<P>)>,
+    now: Duration,
+    mut filter: impl FnMut(&P) -> bool,
+) -> Vec<(K, P)> {
+    let mut due = Vec::new();
+    let mut index = 0;
+
+    while index < in_flight.len() {
+        let (_, datagram) = &in_flight[index];
+
+        if datagram.deliver_at <= now && filter(&datagram.payload) {
+            due.push(in_flight.swap_remove(index));
+        } else {
+            index += 1;
+        }
+    }
+
+    due.sort_by_key(|(_, datagram)| (datagram.deliver_at, datagram.sequence));
+    due.into_iter()
+        .map(|(key, datagram)| (key, datagram.payload))
+        .collect()
+}
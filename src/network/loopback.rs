@@ -0,0 +1,298 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::{
+    prelude::*,
+    utils::{Duration, HashMap},
+};
+use ggrs::{Config, GgrsError, Message, NonBlockingSocket, P2PSession, PlayerType, SessionBuilder};
+
+use super::conditions::{take_due, Conditioner, Link, Scheduled};
+use crate::{NetworkConditions, Session, StateTransferSocket};
+
+#[derive(Clone)]
+enum Payload {
+    Ggrs(Vec<u8>),
+    Data(Vec<u8>),
+}
+
+impl Payload {
+    fn len(&self) -> usize {
+        match self {
+            Payload::Ggrs(data) | Payload::Data(data) => data.len(),
+        }
+    }
+}
+
+struct LoopbackNetworkInner {
+    conditions: NetworkConditions,
+    conditioner: Conditioner,
+    now: Duration,
+    links: HashMap<(usize, usize), Link<Payload>>,
+    in_flight: HashMap<usize, Vec<(usize, Scheduled<Payload>)>>,
+}
+
+impl LoopbackNetworkInner {
+    fn send(&mut self, from: usize, to: usize, payload: Payload) {
+        let mut scheduled = Vec::new();
+
+        self.conditioner.send(
+            &self.conditions,
+            self.links.entry((from, to)).or_default(),
+            self.now,
+            payload.len(),
+            payload,
+            &mut scheduled,
+        );
+
+        self.in_flight
+            .entry(to)
+            .or_default()
+            .extend(scheduled.into_iter().map(|datagram| (from, datagram)));
+    }
+
+    fn receive(&mut self, to: usize, ggrs: bool) -> Vec<(usize, Vec<u8>)> {
+        let in_flight = self.in_flight.entry(to).or_default();
+
+        for (&(from, link_to), link) in self.links.iter_mut() {
+            if link_to != to {
+                continue;
+            }
+
+            if let Some(datagram) = self.conditioner.release_overdue(link, self.now) {
+                in_flight.push((from, datagram));
+            }
+        }
+
+        take_due(in_flight, self.now, |payload| {
+            matches!(
+                (payload, ggrs),
+                (Payload::Ggrs(_), true) | (Payload::Data(_), false)
+            )
+        })
+        .into_iter()
+        .map(|(from, payload)| match payload {
+            Payload::Ggrs(data) | Payload::Data(data) => (from, data),
+        })
+        .collect()
+    }
+}
+
+/// An in-memory network connecting any number of [`LoopbackSockets`](`LoopbackSocket`),
+/// addressed by `usize`. Useful for running several [`Apps`](`App`) in the same process
+/// without binding real ports, such as in tests.
+///
+/// Time on a [`LoopbackNetwork`] is virtual, and only moves forward when [`advance`](`Self::advance`)
+/// is called. Together with a fixed seed, this makes the applied [`NetworkConditions`] fully
+/// deterministic.
+///
+/// Clones of a [`LoopbackNetwork`] refer to the same network.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::{prelude::*, utils::Duration};
+/// # use bevy_ggrs::{prelude::*, LoopbackNetwork, NetworkConditions};
+/// #
+/// # type MyConfig = GgrsConfig<u8, usize>;
+/// #
+/// # fn read_local_inputs() {}
+/// #
+/// # fn start() -> Result<(), Box<dyn std::error::Error>> {
+/// let network = LoopbackNetwork::new(NetworkConditions {
+///     latency: Duration::from_millis(50),
+///     jitter: Duration::from_millis(10),
+///     loss: 0.05,
+///     ..default()
+/// });
+///
+/// let mut apps = (0..2)
+///     .map(|_| {
+///         let mut app = App::new();
+///         app.add_plugins(MinimalPlugins)
+///             .add_plugins(GgrsPlugin::<MyConfig>::default())
+///             .add_systems(ReadInputs, read_local_inputs);
+///         app
+///     })
+///     .collect::<Vec<_>>();
+///
+/// network.connect_apps::<MyConfig>(&mut apps, |builder| builder)?;
+///
+/// for _ in 0..100 {
+///     network.update_apps(&mut apps, Duration::from_secs_f64(1.0 / 60.0));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct LoopbackNetwork {
+    inner: Arc<Mutex<LoopbackNetworkInner>>,
+}
+
+impl Default for LoopbackNetwork {
+    fn default() -> Self {
+        Self::new(NetworkConditions::default())
+    }
+}
+
+impl LoopbackNetwork {
+    /// Creates a new network with the provided [`NetworkConditions`].
+    pub fn new(conditions: NetworkConditions) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(LoopbackNetworkInner {
+                conditions,
+                conditioner: Conditioner::new(0),
+                now: Duration::ZERO,
+                links: default(),
+                in_flight: default(),
+            })),
+        }
+    }
+
+    /// Sets the seed used when applying the [`NetworkConditions`].
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.inner.lock().unwrap().conditioner = Conditioner::new(seed);
+        self
+    }
+
+    /// Returns the current [`NetworkConditions`].
+    pub fn conditions(&self) -> NetworkConditions {
+        self.inner.lock().unwrap().conditions
+    }
+
+    /// Replaces the [`NetworkConditions`]. Datagrams already in flight are unaffected.
+    pub fn set_conditions(&self, conditions: NetworkConditions) {
+        self.inner.lock().unwrap().conditions = conditions;
+    }
+
+    /// Returns the virtual time which has passed on this network.
+    pub fn now(&self) -> Duration {
+        self.inner.lock().unwrap().now
+    }
+
+    /// Moves the virtual time of this network forward.
+    pub fn advance(&self, delta: Duration) {
+        self.inner.lock().unwrap().now += delta;
+    }
+
+    /// Creates a socket on this network with the provided address.
+    pub fn socket(&self, addr: usize) -> LoopbackSocket {
+        LoopbackSocket {
+            addr,
+            network: self.clone(),
+        }
+    }
+
+    /// Starts one [`P2PSession`] per player, each with a [`LoopbackSocket`] addressed by its
+    /// player handle. `configure` is called on each [`SessionBuilder`] before the players are added.
+    pub fn start_p2p_sessions<T>(
+        &self,
+        num_players: usize,
+        mut configure: impl FnMut(SessionBuilder<T>) -> SessionBuilder<T>,
+    ) -> Result<Vec<P2PSession<T>>, GgrsError>
+    where
+        T: Config<Address = usize>,
+    {
+        (0..num_players)
+            .map(|local| {
+                let mut builder =
+                    configure(SessionBuilder::<T>::new().with_num_players(num_players));
+
+                for handle in 0..num_players {
+                    let player_type = if handle == local {
+                        PlayerType::Local
+                    } else {
+                        PlayerType::Remote(handle)
+                    };
+
+                    builder = builder.add_player(player_type, handle)?;
+                }
+
+                builder.start_p2p_session(self.socket(local))
+            })
+            .collect()
+    }
+
+    /// Inserts a [P2P](`Session::P2P`) [`Session`] into each of the provided [`Apps`](`App`),
+    /// connecting them into a single match. The app at index `n` controls player `n`.
+    pub fn connect_apps<T>(
+        &self,
+        apps: &mut [App],
+        configure: impl FnMut(SessionBuilder<T>) -> SessionBuilder<T>,
+    ) -> Result<(), GgrsError>
+    where
+        T: Config<Address = usize>,
+    {
+        let sessions = self.start_p2p_sessions(apps.len(), configure)?;
+
+        for (app, session) in apps.iter_mut().zip(sessions) {
+            app.insert_resource(Session::P2P(session));
+        }
+
+        Ok(())
+    }
+
+    /// Updates each of the provided [`Apps`](`App`) once, then advances the virtual time of
+    /// this network by `delta`.
+    pub fn update_apps(&self, apps: &mut [App], delta: Duration) {
+        for app in apps.iter_mut() {
+            app.update();
+        }
+
+        self.advance(delta);
+    }
+}
+
+/// A socket on a [`LoopbackNetwork`]. Implements both [`NonBlockingSocket`], for use with
+/// a GGRS [`Session`], and [`StateTransferSocket`].
+pub struct LoopbackSocket {
+    addr: usize,
+    network: LoopbackNetwork,
+}
+
+impl LoopbackSocket {
+    /// The address of this socket on its [`LoopbackNetwork`].
+    pub fn addr(&self) -> usize {
+        self.addr
+    }
+}
+
+impl NonBlockingSocket<usize> for LoopbackSocket {
+    fn send_to(&mut self, msg: &Message, addr: &usize) {
+        let data = match bincode::serialize(msg) {
+            Ok(data) => data,
+            Err(error) => {
+                warn!("Failed to serialize GGRS message: {error}");
+                return;
+            }
+        };
+
+        let mut inner = self.network.inner.lock().unwrap();
+        inner.send(self.addr, *addr, Payload::Ggrs(data));
+    }
+
+    fn receive_all_messages(&mut self) -> Vec<(usize, Message)> {
+        let mut inner = self.network.inner.lock().unwrap();
+        inner
+            .receive(self.addr, true)
+            .into_iter()
+            .filter_map(|(from, data)| match bincode::deserialize(&data) {
+                Ok(message) => Some((from, message)),
+                Err(error) => {
+                    warn!("Discarding malformed GGRS message: {error}");
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+impl StateTransferSocket<usize> for LoopbackSocket {
+    fn send_to(&mut self, data: &[u8], addr: &usize) {
+        let mut inner = self.network.inner.lock().unwrap();
+        inner.send(self.addr, *addr, Payload::Data(data.to_vec()));
+    }
+
+    fn receive_all(&mut self) -> Vec<(usize, Vec<u8>)> {
+        let mut inner = self.network.inner.lock().unwrap();
+        inner.receive(self.addr, false)
+    }
+}
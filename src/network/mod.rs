@@ -0,0 +1,11 @@
+mod conditions;
+mod loopback;
+mod simulator;
+mod socket;
+mod state_transfer;
+
+pub use conditions::NetworkConditions;
+pub use loopback::*;
+pub use simulator::*;
+pub use socket::*;
+pub use state_transfer::*;
@@ -0,0 +1,230 @@
+use std::{
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use bevy::{
+    prelude::*,
+    utils::{Duration, HashMap, Instant},
+};
+use ggrs::{Message, NonBlockingSocket};
+
+use super::conditions::{take_due, Conditioner, Link, Scheduled};
+use crate::NetworkConditions;
+
+/// Seeds each [`NetworkSimulatorSocket`] differently, so that simulated peers in the same
+/// process do not drop the same messages.
+static NEXT_SEED: AtomicU64 = AtomicU64::new(0);
+
+/// Controls the [`NetworkConditions`] applied by every [`NetworkSimulatorSocket`] created from it.
+/// Changes take effect immediately, so this can be inserted as a [`Resource`] and adjusted at
+/// runtime, for example from a debug menu.
+///
+/// Conditions are applied to outgoing messages only, so `latency` is added to the round trip
+/// time once. Use the default [`NetworkConditions`] to disable the simulation.
+///
+/// Messages are delayed using real time, unless the simulator is created
+/// [with a manual clock](`NetworkSimulator::with_manual_clock`).
+///
+/// # Examples
+/// ```rust,no_run
+/// # use bevy::{prelude::*, utils::Duration};
+/// # use bevy_ggrs::{prelude::*, NetworkConditions, NetworkSimulator};
+/// # use ggrs::UdpNonBlockingSocket;
+/// #
+/// # type MyConfig = GgrsConfig<u8>;
+/// #
+/// # fn start(app: &mut App) -> Result<(), Box<dyn std::error::Error>> {
+/// let simulator = NetworkSimulator::new(NetworkConditions {
+///     latency: Duration::from_millis(150),
+///     loss: 0.05,
+///     ..default()
+/// });
+///
+/// let socket = simulator.wrap(UdpNonBlockingSocket::bind_to_port(7000)?);
+/// let session = SessionBuilder::<MyConfig>::new()
+///     .add_player(PlayerType::Local, 0)?
+///     .start_p2p_session(socket)?;
+///
+/// app.insert_resource(simulator)
+///     .insert_resource(Session::P2P(session));
+/// # Ok(())
+/// # }
+///
+/// fn toggle_lag(input: Res<Input<KeyCode>>, simulator: Res<NetworkSimulator>) {
+///     if input.just_pressed(KeyCode::L) {
+///         let mut conditions = simulator.conditions();
+///         conditions.latency = if conditions.latency.is_zero() {
+///             Duration::from_millis(150)
+///         } else {
+///             Duration::ZERO
+///         };
+///         simulator.set_conditions(conditions);
+///     }
+/// }
+/// ```
+#[derive(Resource, Clone, Default)]
+pub struct NetworkSimulator {
+    conditions: Arc<Mutex<NetworkConditions>>,
+    clock: Option<Arc<Mutex<Duration>>>,
+}
+
+impl NetworkSimulator {
+    /// Creates a new simulator applying the provided [`NetworkConditions`].
+    pub fn new(conditions: NetworkConditions) -> Self {
+        Self {
+            conditions: Arc::new(Mutex::new(conditions)),
+            clock: None,
+        }
+    }
+
+    /// Delays messages using a virtual clock, which only moves forward when
+    /// [`advance`](`NetworkSimulator::advance`) is called. Useful in tests, as together with
+    /// [`NetworkConditions`] without randomness, message delivery becomes fully deterministic.
+    ///
+    /// Only sockets wrapped after calling this will use the virtual clock.
+    pub fn with_manual_clock(mut self) -> Self {
+        self.clock = Some(default());
+        self
+    }
+
+    /// Moves the virtual clock forward by `delta`. Has no effect unless this simulator was
+    /// created [with a manual clock](`NetworkSimulator::with_manual_clock`).
+    pub fn advance(&self, delta: Duration) {
+        if let Some(clock) = &self.clock {
+            *clock.lock().unwrap() += delta;
+        }
+    }
+
+    /// Returns the current [`NetworkConditions`].
+    pub fn conditions(&self) -> NetworkConditions {
+        *self.conditions.lock().unwrap()
+    }
+
+    /// Replaces the [`NetworkConditions`]. Messages already queued are unaffected.
+    pub fn set_conditions(&self, conditions: NetworkConditions) {
+        *self.conditions.lock().unwrap() = conditions;
+    }
+
+    /// Wraps the provided socket, applying the conditions of this simulator to all messages
+    /// sent through it.
+    pub fn wrap<A, S>(&self, socket: S) -> NetworkSimulatorSocket<A, S> {
+        NetworkSimulatorSocket {
+            socket,
+            conditions: self.conditions.clone(),
+            conditioner: Conditioner::new(NEXT_SEED.fetch_add(1, Ordering::Relaxed)),
+            clock: match &self.clock {
+                Some(clock) => Clock::Manual(clock.clone(), *clock.lock().unwrap()),
+                None => Clock::Real(Instant::now()),
+            },
+            links: HashMap::default(),
+            outgoing: Vec::new(),
+        }
+    }
+}
+
+/// The source of time used by a [`NetworkSimulatorSocket`].
+enum Clock {
+    /// Real time since the socket was wrapped.
+    Real(Instant),
+    /// A shared virtual clock, and its value when the socket was wrapped.
+    Manual(Arc<Mutex<Duration>>, Duration),
+}
+
+impl Clock {
+    fn elapsed(&self) -> Duration {
+        match self {
+            Clock::Real(start) => start.elapsed(),
+            Clock::Manual(clock, start) => clock.lock().unwrap().saturating_sub(*start),
+        }
+    }
+}
+
+/// A [`NonBlockingSocket`] which wraps another socket, such as a
+/// [`UdpNonBlockingSocket`](`ggrs::UdpNonBlockingSocket`), and applies artificial latency, jitter,
+/// packet loss, duplication, reordering and bandwidth limits to outgoing messages.
+///
+/// Created with [`NetworkSimulator::wrap`].
+pub struct NetworkSimulatorSocket<A, S> {
+    socket: S,
+    conditions: Arc<Mutex<NetworkConditions>>,
+    conditioner: Conditioner,
+    clock: Clock,
+    links: HashMap<A, Link<Vec<u8>>>,
+    outgoing: Vec<(A, Scheduled<Vec<u8>>)>,
+}
+
+impl<A, S> NetworkSimulatorSocket<A, S> {
+    /// Returns the wrapped socket. Messages which are still queued are discarded.
+    pub fn into_inner(self) -> S {
+        self.socket
+    }
+}
+
+impl<A, S> NetworkSimulatorSocket<A, S>
+where
+    A: Clone + PartialEq + Eq + Hash + Send + Sync,
+    S: NonBlockingSocket<A>,
+{
+    /// Sends all queued messages which are due.
+    fn flush(&mut self) {
+        let now = self.clock.elapsed();
+
+        for (addr, link) in self.links.iter_mut() {
+            if let Some(datagram) = self.conditioner.release_overdue(link, now) {
+                self.outgoing.push((addr.clone(), datagram));
+            }
+        }
+
+        for (addr, data) in take_due(&mut self.outgoing, now, |_| true) {
+            match bincode::deserialize(&data) {
+                Ok(message) => self.socket.send_to(&message, &addr),
+                Err(error) => warn!("Discarding malformed GGRS message: {error}"),
+            }
+        }
+    }
+}
+
+impl<A, S> NonBlockingSocket<A> for NetworkSimulatorSocket<A, S>
+where
+    A: Clone + PartialEq + Eq + Hash + Send + Sync,
+    S: NonBlockingSocket<A>,
+{
+    fn send_to(&mut self, msg: &Message, addr: &A) {
+        let data = match bincode::serialize(msg) {
+            Ok(data) => data,
+            Err(error) => {
+                warn!("Failed to serialize GGRS message: {error}");
+                return;
+            }
+        };
+
+        let conditions = *self.conditions.lock().unwrap();
+        let mut scheduled = Vec::new();
+
+        self.conditioner.send(
+            &conditions,
+            self.links.entry(addr.clone()).or_default(),
+            self.clock.elapsed(),
+            data.len(),
+            data,
+            &mut scheduled,
+        );
+
+        self.outgoing.extend(
+            scheduled
+                .into_iter()
+                .map(|datagram| (addr.clone(), datagram)),
+        );
+
+        self.flush();
+    }
+
+    fn receive_all_messages(&mut self) -> Vec<(A, Message)> {
+        self.flush();
+        self.socket.receive_all_messages()
+    }
+}
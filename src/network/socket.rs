@@ -0,0 +1,191 @@
+use std::{
+    collections::VecDeque,
+    io::ErrorKind,
+    net::{SocketAddr, UdpSocket},
+    sync::{Arc, Mutex},
+};
+
+use bevy::prelude::*;
+use ggrs::{Message, NonBlockingSocket};
+
+/// The largest datagram which will be received by a [`UdpStateTransferSocket`].
+const RECV_BUFFER_SIZE: usize = 4096;
+
+/// A non-blocking socket which sends and receives raw datagrams. Unlike [`NonBlockingSocket`],
+/// which only carries GGRS [`Messages`](`Message`), this is used to carry arbitrary data,
+/// such as a [state transfer](`crate::StateTransferHost`).
+///
+/// Delivery is not expected to be reliable or ordered.
+pub trait StateTransferSocket<A>: Send + Sync {
+    /// Sends a datagram to the provided address.
+    fn send_to(&mut self, data: &[u8], addr: &A);
+
+    /// Receives all datagrams which have arrived since the last call.
+    fn receive_all(&mut self) -> Vec<(A, Vec<u8>)>;
+}
+
+/// A [`StateTransferSocket`] using a non-blocking [`UdpSocket`].
+pub struct UdpStateTransferSocket {
+    socket: UdpSocket,
+    buffer: Vec<u8>,
+}
+
+impl UdpStateTransferSocket {
+    /// Binds a non-blocking [`UdpSocket`] to the provided port on all interfaces.
+    pub fn bind_to_port(port: u16) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(SocketAddr::from(([0, 0, 0, 0], port)))?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            socket,
+            buffer: vec![0; RECV_BUFFER_SIZE],
+        })
+    }
+}
+
+impl StateTransferSocket<SocketAddr> for UdpStateTransferSocket {
+    fn send_to(&mut self, data: &[u8], addr: &SocketAddr) {
+        if let Err(error) = self.socket.send_to(data, addr) {
+            warn!("Failed to send datagram to {addr}: {error}");
+        }
+    }
+
+    fn receive_all(&mut self) -> Vec<(SocketAddr, Vec<u8>)> {
+        let mut received = Vec::new();
+
+        loop {
+            match self.socket.recv_from(&mut self.buffer) {
+                Ok((len, addr)) => received.push((addr, self.buffer[..len].to_vec())),
+                Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                // On some platforms, a previous send to an unreachable peer is reported here
+                Err(error) if error.kind() == ErrorKind::ConnectionReset => continue,
+                Err(error) => {
+                    warn!("Failed to receive datagram: {error}");
+                    break;
+                }
+            }
+        }
+
+        received
+    }
+}
+
+/// Identifies which channel of a [`MultiplexedSocket`] a datagram belongs to.
+const GGRS_CHANNEL: u8 = 0;
+const TRANSFER_CHANNEL: u8 = 1;
+
+struct MultiplexedSocketInner<A, S> {
+    socket: S,
+    ggrs: VecDeque<(A, Message)>,
+    transfer: VecDeque<(A, Vec<u8>)>,
+}
+
+impl<A, S: StateTransferSocket<A>> MultiplexedSocketInner<A, S> {
+    /// Drains the underlying socket, sorting datagrams into their channels.
+    fn poll(&mut self) {
+        for (addr, data) in self.socket.receive_all() {
+            match data.split_first() {
+                Some((&GGRS_CHANNEL, message)) => match bincode::deserialize(message) {
+                    Ok(message) => self.ggrs.push_back((addr, message)),
+                    Err(error) => warn!("Discarding malformed GGRS message: {error}"),
+                },
+                Some((&TRANSFER_CHANNEL, data)) => self.transfer.push_back((addr, data.to_vec())),
+                _ => warn!("Discarding datagram for an unknown channel"),
+            }
+        }
+    }
+}
+
+/// Shares a single [`StateTransferSocket`] between a GGRS [`Session`](`crate::Session`) and a
+/// [state transfer](`crate::StateTransferHost`), allowing both to use the same port.
+///
+/// Clones of a [`MultiplexedSocket`] share the same underlying socket. Provide one clone to
+/// the [`SessionBuilder`](`ggrs::SessionBuilder`), and another to a
+/// [`StateTransferHost`](`crate::StateTransferHost`) or [`StateTransferClient`](`crate::StateTransferClient`).
+///
+/// # Examples
+/// ```rust,no_run
+/// # use bevy_ggrs::{prelude::*, MultiplexedSocket, StateTransferHost, UdpStateTransferSocket};
+/// #
+/// # type MyConfig = GgrsConfig<u8>;
+/// #
+/// # fn start() -> Result<(), Box<dyn std::error::Error>> {
+/// let socket = MultiplexedSocket::new(UdpStateTransferSocket::bind_to_port(7000)?);
+///
+/// let session = SessionBuilder::<MyConfig>::new()
+///     .add_player(PlayerType::Local, 0)?
+///     .start_p2p_session(socket.clone())?;
+///
+/// let host = StateTransferHost::<MyConfig>::new(socket);
+/// # Ok(())
+/// # }
+/// ```
+pub struct MultiplexedSocket<A, S> {
+    inner: Arc<Mutex<MultiplexedSocketInner<A, S>>>,
+}
+
+impl<A, S> Clone for MultiplexedSocket<A, S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<A, S: StateTransferSocket<A>> MultiplexedSocket<A, S> {
+    /// Wraps the provided socket.
+    pub fn new(socket: S) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(MultiplexedSocketInner {
+                socket,
+                ggrs: VecDeque::new(),
+                transfer: VecDeque::new(),
+            })),
+        }
+    }
+}
+
+impl<A, S> NonBlockingSocket<A> for MultiplexedSocket<A, S>
+where
+    A: Clone + PartialEq + Eq + std::hash::Hash + Send + Sync,
+    S: StateTransferSocket<A>,
+{
+    fn send_to(&mut self, msg: &Message, addr: &A) {
+        let mut data = vec![GGRS_CHANNEL];
+
+        if let Err(error) = bincode::serialize_into(&mut data, msg) {
+            warn!("Failed to serialize GGRS message: {error}");
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.socket.send_to(&data, addr);
+    }
+
+    fn receive_all_messages(&mut self) -> Vec<(A, Message)> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.poll();
+        inner.ggrs.drain(..).collect()
+    }
+}
+
+impl<A, S> StateTransferSocket<A> for MultiplexedSocket<A, S>
+where
+    A: Send + Sync,
+    S: StateTransferSocket<A>,
+{
+    fn send_to(&mut self, data: &[u8], addr: &A) {
+        let mut datagram = Vec::with_capacity(data.len() + 1);
+        datagram.push(TRANSFER_CHANNEL);
+        datagram.extend_from_slice(data);
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.socket.send_to(&datagram, addr);
+    }
+
+    fn receive_all(&mut self) -> Vec<(A, Vec<u8>)> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.poll();
+        inner.transfer.drain(..).collect()
+    }
+}
@@ -0,0 +1,554 @@
+use bevy::{
+    prelude::*,
+    utils::{Duration, HashMap, Instant},
+};
+use ggrs::Config;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ConfirmedFrameCount, LoadWorld, RollbackFrameCount, RollbackFrameOffset, SerializedWorld,
+    Session, StateTransferSocket, WorldSerializationError,
+};
+
+/// The maximum number of bytes of world data sent in a single datagram.
+const CHUNK_SIZE: usize = 1024;
+
+/// The maximum number of chunks which may be awaiting acknowledgement at once.
+const SEND_WINDOW: usize = 32;
+
+/// The time to wait for an acknowledgement before resending a chunk.
+const RESEND_INTERVAL: Duration = Duration::from_millis(200);
+
+/// The time to wait for the host to respond before repeating a request.
+const REQUEST_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The default maximum size in bytes of a world state a [`StateTransferClient`] will receive.
+const DEFAULT_MAX_TRANSFER_SIZE: usize = 64 * 1024 * 1024;
+
+/// Datagrams exchanged during a state transfer.
+#[derive(Serialize, Deserialize, Debug)]
+enum TransferMessage {
+    /// Sent by a client to request the current world state.
+    Request,
+    /// A single chunk of a [`SerializedWorld`].
+    Chunk {
+        frame: i32,
+        index: u32,
+        count: u32,
+        data: Vec<u8>,
+    },
+    /// Acknowledges receipt of a [`TransferMessage::Chunk`].
+    Ack { frame: i32, index: u32 },
+}
+
+struct OutgoingTransfer {
+    frame: i32,
+    chunks: Vec<Vec<u8>>,
+    acked: Vec<bool>,
+    sent: Vec<Option<Instant>>,
+}
+
+impl OutgoingTransfer {
+    fn is_complete(&self) -> bool {
+        self.acked.iter().all(|&acked| acked)
+    }
+}
+
+/// [`Resource`] which sends the confirmed world state to peers joining a match in progress.
+///
+/// Peers request a transfer using a [`StateTransferClient`], which is reported by the
+/// [`StateTransferPlugin`] as a [`StateTransferRequested`] event. The transfer can then be started
+/// with [`StateTransferPlugin::begin_transfer`].
+#[derive(Resource)]
+pub struct StateTransferHost<T: Config> {
+    socket: Box<dyn StateTransferSocket<T::Address>>,
+    transfers: HashMap<T::Address, OutgoingTransfer>,
+}
+
+impl<T: Config> StateTransferHost<T> {
+    /// Creates a host which listens for requests on the provided socket.
+    pub fn new(socket: impl StateTransferSocket<T::Address> + 'static) -> Self {
+        Self {
+            socket: Box::new(socket),
+            transfers: HashMap::default(),
+        }
+    }
+
+    /// Starts sending the provided world state to a peer, replacing any existing transfer
+    /// to that peer.
+    pub fn send(
+        &mut self,
+        addr: T::Address,
+        world: &SerializedWorld,
+    ) -> Result<(), WorldSerializationError> {
+        let bytes = world.to_bytes()?;
+        let chunks = bytes
+            .chunks(CHUNK_SIZE)
+            .map(<[u8]>::to_vec)
+            .collect::<Vec<_>>();
+
+        debug!(
+            "Sending frame {} to {addr:?} in {} chunk(s)",
+            world.frame(),
+            chunks.len()
+        );
+
+        self.transfers.insert(
+            addr,
+            OutgoingTransfer {
+                frame: world.frame(),
+                acked: vec![false; chunks.len()],
+                sent: vec![None; chunks.len()],
+                chunks,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Returns `true` once the peer has acknowledged every chunk of its transfer.
+    pub fn is_complete(&self, addr: &T::Address) -> bool {
+        self.transfers
+            .get(addr)
+            .is_some_and(OutgoingTransfer::is_complete)
+    }
+
+    /// Returns the number of chunks acknowledged and the total number of chunks for the
+    /// transfer to a peer, if there is one.
+    pub fn progress(&self, addr: &T::Address) -> Option<(usize, usize)> {
+        let transfer = self.transfers.get(addr)?;
+        let acked = transfer.acked.iter().filter(|&&acked| acked).count();
+        Some((acked, transfer.chunks.len()))
+    }
+
+    /// Processes received datagrams and (re)sends any outstanding chunks. Returns the
+    /// addresses of peers which have newly requested a transfer.
+    pub fn update(&mut self) -> Vec<T::Address> {
+        let mut requests = Vec::new();
+
+        for (addr, data) in self.socket.receive_all() {
+            match bincode::deserialize::<TransferMessage>(&data) {
+                Ok(TransferMessage::Request) => {
+                    let in_progress = self
+                        .transfers
+                        .get(&addr)
+                        .is_some_and(|transfer| !transfer.is_complete());
+
+                    if !in_progress && !requests.contains(&addr) {
+                        requests.push(addr);
+                    }
+                }
+                Ok(TransferMessage::Ack { frame, index }) => {
+                    let Some(transfer) = self.transfers.get_mut(&addr) else {
+                        continue;
+                    };
+
+                    if transfer.frame == frame {
+                        if let Some(acked) = transfer.acked.get_mut(index as usize) {
+                            *acked = true;
+                        }
+                    }
+                }
+                Ok(message) => warn!("Unexpected state transfer message {message:?} from {addr:?}"),
+                Err(error) => warn!("Discarding malformed state transfer message: {error}"),
+            }
+        }
+
+        let now = Instant::now();
+
+        for (addr, transfer) in self.transfers.iter_mut() {
+            let count = transfer.chunks.len() as u32;
+
+            let pending = (0..transfer.chunks.len())
+                .filter(|&index| !transfer.acked[index])
+                .take(SEND_WINDOW);
+
+            for index in pending {
+                let due = transfer.sent[index]
+                    .map_or(true, |sent| now.duration_since(sent) >= RESEND_INTERVAL);
+
+                if !due {
+                    continue;
+                }
+
+                let message = TransferMessage::Chunk {
+                    frame: transfer.frame,
+                    index: index as u32,
+                    count,
+                    data: transfer.chunks[index].clone(),
+                };
+
+                send(self.socket.as_mut(), &message, addr);
+                transfer.sent[index] = Some(now);
+            }
+        }
+
+        requests
+    }
+}
+
+struct IncomingTransfer {
+    frame: i32,
+    chunks: Vec<Option<Vec<u8>>>,
+    received: usize,
+}
+
+/// [`Resource`] which requests and receives the world state from a [`StateTransferHost`].
+///
+/// When used with the [`StateTransferPlugin`], the received world state is applied
+/// automatically, and a [`StateTransferComplete`] event is sent. A new [`Session`] can then
+/// be started, which will continue from the received frame. If the world state cannot be
+/// applied, it is requested again.
+#[derive(Resource)]
+pub struct StateTransferClient<T: Config> {
+    socket: Box<dyn StateTransferSocket<T::Address>>,
+    host: T::Address,
+    max_chunks: usize,
+    last_request: Option<Instant>,
+    incoming: Option<IncomingTransfer>,
+    complete: bool,
+}
+
+impl<T: Config> StateTransferClient<T> {
+    /// Creates a client which will request the world state from the provided host.
+    pub fn new(socket: impl StateTransferSocket<T::Address> + 'static, host: T::Address) -> Self {
+        Self {
+            socket: Box::new(socket),
+            host,
+            max_chunks: DEFAULT_MAX_TRANSFER_SIZE.div_ceil(CHUNK_SIZE),
+            last_request: None,
+            incoming: None,
+            complete: false,
+        }
+    }
+
+    /// Sets the maximum size in bytes of the world state this client will receive. Transfers
+    /// announced as larger than this are discarded before any memory is allocated for them.
+    /// Defaults to 64 MiB.
+    pub fn with_max_transfer_size(mut self, max_transfer_size: usize) -> Self {
+        self.max_chunks = max_transfer_size.div_ceil(CHUNK_SIZE);
+        self
+    }
+
+    /// Returns `true` once the world state has been received and applied.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Marks the transfer as complete, once the world state returned by
+    /// [`update`](`StateTransferClient::update`) has been applied. No further chunks will be
+    /// received.
+    pub fn mark_complete(&mut self) {
+        self.complete = true;
+    }
+
+    /// Discards any received chunks and requests the world state again, such as when the
+    /// world state returned by [`update`](`StateTransferClient::update`) could not be applied.
+    pub fn restart(&mut self) {
+        self.incoming = None;
+        self.last_request = None;
+        self.complete = false;
+    }
+
+    /// Returns the number of chunks received and the total number of chunks, once the
+    /// transfer has started.
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        let incoming = self.incoming.as_ref()?;
+        Some((incoming.received, incoming.chunks.len()))
+    }
+
+    /// Requests the world state if required, and processes received datagrams. Returns the
+    /// world state once it has been completely received, which should then be applied and
+    /// [marked complete](`StateTransferClient::mark_complete`). If the received bytes cannot
+    /// be decoded, the world state is requested again.
+    pub fn update(&mut self) -> Option<Result<SerializedWorld, WorldSerializationError>> {
+        let now = Instant::now();
+        let mut result = None;
+
+        for (addr, data) in self.socket.receive_all() {
+            if addr != self.host {
+                continue;
+            }
+
+            let message = match bincode::deserialize::<TransferMessage>(&data) {
+                Ok(message) => message,
+                Err(error) => {
+                    warn!("Discarding malformed state transfer message: {error}");
+                    continue;
+                }
+            };
+
+            let TransferMessage::Chunk {
+                frame,
+                index,
+                count,
+                data,
+            } = message
+            else {
+                warn!("Unexpected state transfer message {message:?} from {addr:?}");
+                continue;
+            };
+
+            // The chunk count comes from the peer, so it must be checked before allocating
+            if count == 0 || count as usize > self.max_chunks {
+                warn!(
+                    "Discarding state transfer of {count} chunk(s), the maximum is {}",
+                    self.max_chunks
+                );
+                continue;
+            }
+
+            // Always acknowledge, in case a previous acknowledgement was lost
+            send(
+                self.socket.as_mut(),
+                &TransferMessage::Ack { frame, index },
+                &self.host,
+            );
+
+            if self.complete {
+                continue;
+            }
+
+            // The host may have restarted the transfer from a different frame
+            if self
+                .incoming
+                .as_ref()
+                .map_or(true, |incoming| incoming.frame != frame)
+            {
+                self.incoming = Some(IncomingTransfer {
+                    frame,
+                    chunks: vec![None; count as usize],
+                    received: 0,
+                });
+            }
+
+            let incoming = self.incoming.as_mut().unwrap();
+
+            let Some(chunk) = incoming.chunks.get_mut(index as usize) else {
+                continue;
+            };
+
+            // Duplicates of a chunk are only acknowledged
+            if chunk.is_some() {
+                continue;
+            }
+
+            *chunk = Some(data);
+            incoming.received += 1;
+
+            if incoming.received < incoming.chunks.len() {
+                continue;
+            }
+
+            let bytes = incoming
+                .chunks
+                .iter()
+                .flatten()
+                .flatten()
+                .copied()
+                .collect::<Vec<_>>();
+
+            let world = SerializedWorld::from_bytes(&bytes);
+
+            if world.is_err() {
+                self.restart();
+            }
+
+            result = Some(world);
+        }
+
+        let request_due = self
+            .last_request
+            .map_or(true, |sent| now.duration_since(sent) >= REQUEST_INTERVAL);
+
+        if self.incoming.is_none() && !self.complete && request_due {
+            send(self.socket.as_mut(), &TransferMessage::Request, &self.host);
+            self.last_request = Some(now);
+        }
+
+        result
+    }
+}
+
+fn send<A>(socket: &mut dyn StateTransferSocket<A>, message: &TransferMessage, addr: &A) {
+    match bincode::serialize(message) {
+        Ok(data) => socket.send_to(&data, addr),
+        Err(error) => warn!("Failed to serialize state transfer message: {error}"),
+    }
+}
+
+/// Sent by the [`StateTransferPlugin`] when a peer requests the world state from the
+/// [`StateTransferHost`].
+#[derive(Event, Debug)]
+pub struct StateTransferRequested<T: Config> {
+    /// The address of the peer requesting the world state.
+    pub addr: T::Address,
+}
+
+/// Sent by the [`StateTransferPlugin`] once a [`StateTransferClient`] has received and applied
+/// the world state.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct StateTransferComplete {
+    /// The frame which was received. New [`Sessions`](`Session`) will continue from this frame.
+    pub frame: i32,
+}
+
+/// A [`Plugin`] which allows peers to join a match in progress by transferring the confirmed
+/// world state, as a [`SerializedWorld`], from a [`StateTransferHost`] to a [`StateTransferClient`].
+///
+/// Since GGRS cannot add players to a running [`Session`], every peer must start a new
+/// [`Session`] from the transferred frame:
+/// 1. The joining peer inserts a [`StateTransferClient`], which requests the world state.
+/// 2. The host receives a [`StateTransferRequested`] event, and calls
+///    [`begin_transfer`](`StateTransferPlugin::begin_transfer`). This rewinds the host to the
+///    [`ConfirmedFrameCount`], removes its [`Session`], and starts sending the world state.
+/// 3. Every other peer already in the match removes its [`Session`] and also inserts a
+///    [`StateTransferClient`], since each peer may have confirmed a different frame. How
+///    they are told to do so (such as through a lobby or matchmaking server) is up to you,
+///    as the host's [`Session`] simply stops without notifying them.
+/// 4. Each peer receives a [`StateTransferComplete`] event once the world state has
+///    been applied.
+/// 5. All peers start a new [`Session`] including each other.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, StateTransferPlugin, StateTransferRequested};
+/// #
+/// # type MyConfig = GgrsConfig<u8>;
+/// #
+/// fn accept_joins(world: &mut World) {
+///     let requests = world
+///         .resource_mut::<Events<StateTransferRequested<MyConfig>>>()
+///         .drain()
+///         .collect::<Vec<_>>();
+///
+///     for StateTransferRequested { addr } in requests {
+///         match StateTransferPlugin::<MyConfig>::begin_transfer(world, addr) {
+///             Ok(frame) => info!("Sending frame {frame} to {addr}"),
+///             Err(error) => error!("Could not send world state to {addr}: {error}"),
+///         }
+///     }
+/// }
+/// #
+/// # let mut app = App::new();
+/// # app.add_plugins(StateTransferPlugin::<MyConfig>::default());
+/// # app.add_systems(Update, accept_joins);
+/// ```
+pub struct StateTransferPlugin<T: Config> {
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Config> Default for StateTransferPlugin<T> {
+    fn default() -> Self {
+        Self {
+            _phantom: default(),
+        }
+    }
+}
+
+impl<T: Config> StateTransferPlugin<T> {
+    /// Rewinds the [`World`] to the [`ConfirmedFrameCount`], removes the current [`Session`],
+    /// and sets the [`RollbackFrameOffset`] so the next [`Session`] continues from this frame.
+    /// Returns the frame the [`World`] was rewound to.
+    ///
+    /// Remote peers are not notified, and will only see this peer stop sending inputs. The
+    /// whole match must be restarted from the returned frame, with every other peer receiving
+    /// it through a [`StateTransferClient`] (see [`StateTransferPlugin`]). Calling this again
+    /// before a new [`Session`] is started returns the same frame.
+    pub fn rewind_to_confirmed_frame(world: &mut World) -> i32 {
+        let current = world.resource::<RollbackFrameCount>().0;
+        let confirmed = world.resource::<ConfirmedFrameCount>().0;
+        let offset = world.resource::<RollbackFrameOffset>().0;
+
+        if confirmed >= offset && confirmed < current {
+            debug!("Rewinding from frame {current} to confirmed frame {confirmed}");
+            world.resource_mut::<RollbackFrameCount>().0 = confirmed;
+            world.run_schedule(LoadWorld);
+        }
+
+        let frame = world.resource::<RollbackFrameCount>().0;
+
+        world.remove_resource::<Session<T>>();
+        world.insert_resource(RollbackFrameOffset(frame));
+        world.insert_resource(ConfirmedFrameCount(frame - 1));
+
+        frame
+    }
+
+    /// Rewinds the [`World`] to the [`ConfirmedFrameCount`] (see
+    /// [`rewind_to_confirmed_frame`](`StateTransferPlugin::rewind_to_confirmed_frame`)), and
+    /// starts sending it to the provided peer using the [`StateTransferHost`].
+    /// Returns the frame being sent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no [`StateTransferHost`].
+    pub fn begin_transfer(
+        world: &mut World,
+        addr: T::Address,
+    ) -> Result<i32, WorldSerializationError> {
+        let frame = Self::rewind_to_confirmed_frame(world);
+        let serialized = SerializedWorld::from_world(world)?;
+
+        world
+            .get_resource_mut::<StateTransferHost<T>>()
+            .expect("No StateTransferHost found. Did you insert one?")
+            .send(addr, &serialized)?;
+
+        Ok(frame)
+    }
+
+    /// A [`System`] which updates the [`StateTransferHost`], if present.
+    pub fn update_host(
+        host: Option<ResMut<StateTransferHost<T>>>,
+        mut requests: EventWriter<StateTransferRequested<T>>,
+    ) {
+        let Some(mut host) = host else {
+            return;
+        };
+
+        for addr in host.update() {
+            info!("State transfer requested by {addr:?}");
+            requests.send(StateTransferRequested { addr });
+        }
+    }
+
+    /// A [`System`] which updates the [`StateTransferClient`], if present, applying the
+    /// world state once received.
+    pub fn update_client(world: &mut World) {
+        let Some(mut client) = world.get_resource_mut::<StateTransferClient<T>>() else {
+            return;
+        };
+
+        let Some(result) = client.update() else {
+            return;
+        };
+
+        match result.and_then(|serialized| {
+            serialized.apply(world)?;
+            Ok(serialized.frame())
+        }) {
+            Ok(frame) => {
+                info!("Received world state for frame {frame}");
+                world
+                    .resource_mut::<StateTransferClient<T>>()
+                    .mark_complete();
+                world.insert_resource(ConfirmedFrameCount(frame - 1));
+                world.send_event(StateTransferComplete { frame });
+            }
+            Err(error) => {
+                error!("Failed to apply transferred world state, requesting it again: {error}");
+                world.resource_mut::<StateTransferClient<T>>().restart();
+            }
+        }
+    }
+}
+
+impl<T: Config> Plugin for StateTransferPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_event::<StateTransferRequested<T>>()
+            .add_event::<StateTransferComplete>()
+            .add_systems(Update, (Self::update_host, Self::update_client).chain());
+    }
+}
@@ -0,0 +1,42 @@
+use bevy::{prelude::*, utils::all_tuples};
+
+/// A type which knows how to register itself for rollback, snapshotting, checksums and entity
+/// mapping. Usually implemented with `#[derive(Rollback)]`, and registered with
+/// [`GgrsApp::register_rollback_types`](`crate::GgrsApp::register_rollback_types`).
+///
+/// Implemented for tuples of such types, which are registered in order.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::prelude::*;
+/// #
+/// #[derive(Component, Clone, Hash, Rollback)]
+/// #[rollback(clone, checksum)]
+/// struct Health(u32);
+///
+/// #[derive(Resource, Clone, Copy, Default, Rollback)]
+/// #[rollback(resource, copy)]
+/// struct Round(u32);
+///
+/// # let mut app = App::new();
+/// # app.add_plugins(GgrsPlugin::<GgrsConfig<u8>>::default());
+/// app.register_rollback_types::<(Health, Round)>();
+/// ```
+pub trait RegisterRollback {
+    /// Registers this type with the provided [`App`].
+    fn register_rollback(app: &mut App);
+}
+
+macro_rules! impl_register_rollback_tuples {
+    ($($T: ident),*) => {
+        impl<$($T: RegisterRollback),*> RegisterRollback for ($($T,)*) {
+            #[allow(unused_variables)]
+            fn register_rollback(app: &mut App) {
+                $($T::register_rollback(app);)*
+            }
+        }
+    }
+}
+
+all_tuples!(impl_register_rollback_tuples, 0, 15, T);
@@ -0,0 +1,598 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use bevy::prelude::*;
+use ggrs::{Config, GameStateCell, GgrsRequest, InputStatus};
+
+use crate::{
+    AdvanceWorld, AdvanceWorldSet, ConfirmedFrameCount, PlayerInputs, RollbackFrameCount,
+    RollbackFrameOffset,
+};
+
+/// Identifies a serialized [`InputRecording`].
+const RECORDING_MAGIC: [u8; 4] = *b"BGRR";
+
+/// The current version of the serialized [`InputRecording`] format.
+const RECORDING_VERSION: u8 = 1;
+
+/// A list of inputs for every player, for every frame from frame `0` onwards.
+///
+/// Recordings are created by an [`InputRecorder`] during a [`P2P`](`crate::Session::P2P`) or
+/// [`SyncTest`](`crate::Session::SyncTest`) [`Session`](`crate::Session`), and can be replayed
+/// using a [`ReplaySession`].
+///
+/// # Format
+///
+/// Recordings can be written to and read from a compact binary format, consisting of:
+/// - A 4 byte magic number, `BGRR`,
+/// - A 1 byte format version,
+/// - The number of players, the size of a single input in bytes, and the number of frames,
+///   each as a little-endian `u32`,
+/// - For every frame, and then every player, a 1 byte [`InputStatus`] followed by the
+///   input as raw bytes.
+pub struct InputRecording<T: Config> {
+    num_players: usize,
+    frames: Vec<Vec<(T::Input, InputStatus)>>,
+}
+
+impl<T: Config> Clone for InputRecording<T> {
+    fn clone(&self) -> Self {
+        Self {
+            num_players: self.num_players,
+            frames: self.frames.clone(),
+        }
+    }
+}
+
+impl<T: Config> InputRecording<T> {
+    /// Creates an empty recording for the provided number of players.
+    pub fn new(num_players: usize) -> Self {
+        Self {
+            num_players,
+            frames: Vec::new(),
+        }
+    }
+
+    /// The number of players this recording contains inputs for.
+    pub fn num_players(&self) -> usize {
+        self.num_players
+    }
+
+    /// The number of frames stored in this recording.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Returns `true` if no frames have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Get the inputs for a particular frame, if it was recorded.
+    pub fn frame(&self, frame: usize) -> Option<&[(T::Input, InputStatus)]> {
+        self.frames.get(frame).map(Vec::as_slice)
+    }
+
+    /// Iterate over the inputs of all recorded frames in order.
+    pub fn iter(&self) -> impl Iterator<Item = &[(T::Input, InputStatus)]> + '_ {
+        self.frames.iter().map(Vec::as_slice)
+    }
+
+    /// Appends the inputs for the next frame to this recording.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of inputs does not match [`num_players`](`InputRecording::num_players`).
+    pub fn push(&mut self, inputs: Vec<(T::Input, InputStatus)>) -> &mut Self {
+        assert_eq!(
+            inputs.len(),
+            self.num_players,
+            "Recorded frames must contain an input for every player"
+        );
+
+        self.frames.push(inputs);
+        self
+    }
+
+    /// Writes this recording in its binary format.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        let input_size = std::mem::size_of::<T::Input>();
+
+        writer.write_all(&RECORDING_MAGIC)?;
+        writer.write_all(&[RECORDING_VERSION])?;
+        writer.write_all(&to_u32(self.num_players)?.to_le_bytes())?;
+        writer.write_all(&to_u32(input_size)?.to_le_bytes())?;
+        writer.write_all(&to_u32(self.frames.len())?.to_le_bytes())?;
+
+        for frame in &self.frames {
+            for (input, status) in frame {
+                writer.write_all(&[status_to_byte(*status)])?;
+                writer.write_all(bytemuck::bytes_of(input))?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    /// Reads a recording from its binary format.
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != RECORDING_MAGIC {
+            return Err(invalid_data("not an input recording"));
+        }
+
+        let mut version = [0; 1];
+        reader.read_exact(&mut version)?;
+        if version[0] != RECORDING_VERSION {
+            return Err(invalid_data(format!(
+                "unsupported input recording version {}",
+                version[0]
+            )));
+        }
+
+        let num_players = read_u32(&mut reader)? as usize;
+        let input_size = read_u32(&mut reader)? as usize;
+        let num_frames = read_u32(&mut reader)? as usize;
+
+        if input_size != std::mem::size_of::<T::Input>() {
+            return Err(invalid_data(format!(
+                "recorded inputs are {input_size} bytes, expected {}",
+                std::mem::size_of::<T::Input>()
+            )));
+        }
+
+        let mut recording = Self::new(num_players);
+        let mut buffer = vec![0; input_size];
+
+        for _ in 0..num_frames {
+            let mut inputs = Vec::with_capacity(num_players);
+
+            for _ in 0..num_players {
+                let mut status = [0; 1];
+                reader.read_exact(&mut status)?;
+                let status = byte_to_status(status[0])?;
+
+                reader.read_exact(&mut buffer)?;
+                let input = bytemuck::pod_read_unaligned::<T::Input>(&buffer);
+
+                inputs.push((input, status));
+            }
+
+            recording.frames.push(inputs);
+        }
+
+        Ok(recording)
+    }
+
+    /// Writes this recording to a file at the provided path.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Reads a recording from a file at the provided path.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+fn to_u32(value: usize) -> io::Result<u32> {
+    u32::try_from(value).map_err(|_| invalid_data("input recording is too large"))
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+fn status_to_byte(status: InputStatus) -> u8 {
+    match status {
+        InputStatus::Confirmed => 0,
+        InputStatus::Predicted => 1,
+        InputStatus::Disconnected => 2,
+    }
+}
+
+fn byte_to_status(byte: u8) -> io::Result<InputStatus> {
+    match byte {
+        0 => Ok(InputStatus::Confirmed),
+        1 => Ok(InputStatus::Predicted),
+        2 => Ok(InputStatus::Disconnected),
+        _ => Err(invalid_data(format!("invalid input status {byte}"))),
+    }
+}
+
+/// [`Resource`] which records the inputs of every confirmed frame into an [`InputRecording`].
+///
+/// Insert this resource before starting your [`Session`](`crate::Session`) to begin recording.
+/// Requires the [`InputRecordingPlugin`].
+#[derive(Resource)]
+pub struct InputRecorder<T: Config> {
+    /// Inputs for frames which have not been confirmed yet.
+    pending: BTreeMap<i32, Vec<(T::Input, InputStatus)>>,
+    /// Inputs for all confirmed frames.
+    recording: InputRecording<T>,
+}
+
+impl<T: Config> InputRecorder<T> {
+    /// Creates a recorder for the provided number of players.
+    pub fn new(num_players: usize) -> Self {
+        Self {
+            pending: BTreeMap::new(),
+            recording: InputRecording::new(num_players),
+        }
+    }
+
+    /// The inputs of all frames confirmed so far.
+    pub fn recording(&self) -> &InputRecording<T> {
+        &self.recording
+    }
+
+    /// Consumes this recorder, returning the inputs of all frames confirmed so far.
+    pub fn into_recording(self) -> InputRecording<T> {
+        self.recording
+    }
+
+    /// Stores the inputs used to advance `frame`, replacing any previous (predicted) inputs.
+    fn record(&mut self, frame: i32, inputs: &[(T::Input, InputStatus)]) {
+        if frame < self.recording.len() as i32 {
+            // This frame has already been confirmed
+            return;
+        }
+
+        self.pending.insert(frame, inputs.to_vec());
+    }
+
+    /// Moves all frames up to and including `confirmed_frame` into the recording.
+    fn confirm(&mut self, confirmed_frame: i32) {
+        while let Some(entry) = self.pending.first_entry() {
+            let frame = *entry.key();
+
+            if frame > confirmed_frame || frame != self.recording.len() as i32 {
+                break;
+            }
+
+            let inputs = entry
+                .remove()
+                .into_iter()
+                .map(|(input, status)| match status {
+                    // Inputs which were predicted correctly are now confirmed
+                    InputStatus::Predicted => (input, InputStatus::Confirmed),
+                    status => (input, status),
+                })
+                .collect();
+
+            self.recording.push(inputs);
+        }
+    }
+}
+
+/// A [`Plugin`] which records the inputs of every confirmed frame into an [`InputRecorder`],
+/// if one is present.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, InputRecorder, InputRecordingPlugin};
+/// #
+/// # type MyConfig = GgrsConfig<u8>;
+/// #
+/// # fn start(session: Session<MyConfig>) {
+/// # let mut app = App::new();
+/// app.add_plugins(InputRecordingPlugin::<MyConfig>::default());
+///
+/// // Record inputs for a 2 player match
+/// app.insert_resource(InputRecorder::<MyConfig>::new(2));
+/// app.insert_resource(session);
+///
+/// // Once the match is over, save the recording
+/// fn save_replay(recorder: Res<InputRecorder<MyConfig>>) {
+///     recorder.recording().save("match.replay").unwrap();
+/// }
+/// # }
+/// ```
+pub struct InputRecordingPlugin<T: Config> {
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Config> Default for InputRecordingPlugin<T> {
+    fn default() -> Self {
+        Self {
+            _phantom: default(),
+        }
+    }
+}
+
+impl<T: Config> InputRecordingPlugin<T> {
+    /// A [`System`] which records the current [`PlayerInputs`] and confirms frames
+    /// up to the [`ConfirmedFrameCount`].
+    ///
+    /// Frames are recorded relative to the [`RollbackFrameOffset`], so the first frame of the
+    /// [`Session`](`crate::Session`) is always frame `0` of the recording.
+    pub fn record(
+        recorder: Option<ResMut<InputRecorder<T>>>,
+        inputs: Option<Res<PlayerInputs<T>>>,
+        frame: Res<RollbackFrameCount>,
+        confirmed_frame: Res<ConfirmedFrameCount>,
+        offset: Res<RollbackFrameOffset>,
+    ) {
+        let (Some(mut recorder), Some(inputs)) = (recorder, inputs) else {
+            return;
+        };
+
+        // RollbackFrameCount has already been incremented for the frame being advanced
+        let frame = frame.0 - offset.0 - 1;
+        recorder.record(frame, &inputs);
+
+        // While resimulating, later frames still hold predicted inputs until they're re-recorded
+        recorder.confirm((confirmed_frame.0 - offset.0).min(frame));
+    }
+}
+
+impl<T: Config> Plugin for InputRecordingPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(AdvanceWorld, Self::record.in_set(AdvanceWorldSet::First));
+    }
+}
+
+/// A [`Session`](`crate::Session`) which plays back an [`InputRecording`] without any network.
+///
+/// The [`World`] must be in the same state it was when recording began, with
+/// [`RollbackFrameCount`] at `0`.
+///
+/// Playback can be paused, stepped, and seeked through using a [`ReplayController`]. Seeking
+/// backwards requires a [keyframe interval](`ReplaySession::with_keyframe_interval`).
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, InputRecording, ReplaySession};
+/// #
+/// # type MyConfig = GgrsConfig<u8>;
+/// #
+/// # fn start(mut app: App) -> std::io::Result<()> {
+/// let recording = InputRecording::<MyConfig>::load("match.replay")?;
+///
+/// // Keep a snapshot of every second of gameplay to allow seeking
+/// let session = ReplaySession::new(recording).with_keyframe_interval(60);
+///
+/// app.insert_resource(Session::Replay(session));
+/// # Ok(())
+/// # }
+/// ```
+pub struct ReplaySession<T: Config> {
+    recording: InputRecording<T>,
+    current_frame: usize,
+    keyframe_interval: Option<usize>,
+    /// Frames which have been saved as keyframes so far.
+    keyframes: BTreeSet<usize>,
+}
+
+impl<T: Config> ReplaySession<T> {
+    /// Creates a session which will play back the provided recording from the first frame.
+    pub fn new(recording: InputRecording<T>) -> Self {
+        Self {
+            recording,
+            current_frame: 0,
+            keyframe_interval: None,
+            keyframes: BTreeSet::new(),
+        }
+    }
+
+    /// Saves a snapshot of the [`World`] every `interval` frames, which is kept for the
+    /// entire replay as a keyframe. This allows seeking to earlier frames.
+    pub fn with_keyframe_interval(mut self, interval: usize) -> Self {
+        self.keyframe_interval = (interval > 0).then_some(interval);
+        self
+    }
+
+    /// The interval between keyframes, if any.
+    pub fn keyframe_interval(&self) -> Option<usize> {
+        self.keyframe_interval
+    }
+
+    /// The number of players in the recording.
+    pub fn num_players(&self) -> usize {
+        self.recording.num_players()
+    }
+
+    /// The recording being played back.
+    pub fn recording(&self) -> &InputRecording<T> {
+        &self.recording
+    }
+
+    /// The frame which will be advanced next.
+    pub fn current_frame(&self) -> i32 {
+        self.current_frame as i32
+    }
+
+    /// Returns `true` once every recorded frame has been played back.
+    pub fn is_finished(&self) -> bool {
+        self.current_frame >= self.recording.len()
+    }
+
+    /// Returns the requests required to advance by a single frame. Once the recording has
+    /// finished, no further requests are produced.
+    pub fn advance_frame(&mut self) -> Vec<GgrsRequest<T>> {
+        let mut requests = Vec::new();
+        self.push_advance(&mut requests);
+        requests
+    }
+
+    /// Returns the requests required to move playback to the provided frame, clamped to the
+    /// length of the recording.
+    ///
+    /// Seeking forwards will fast-forward from the current frame, or the nearest keyframe if
+    /// it is closer. Seeking backwards loads the nearest keyframe at or before the target
+    /// frame, then fast-forwards. If no such keyframe exists, the seek is ignored.
+    pub fn seek(&mut self, frame: i32) -> Vec<GgrsRequest<T>> {
+        let target = (frame.max(0) as usize).min(self.recording.len());
+        let mut requests = Vec::new();
+
+        let keyframe = self.keyframes.range(..=target).next_back().copied();
+
+        match keyframe {
+            Some(keyframe) if keyframe > self.current_frame || target < self.current_frame => {
+                requests.push(GgrsRequest::LoadGameState {
+                    cell: GameStateCell::default(),
+                    frame: keyframe as i32,
+                });
+                self.current_frame = keyframe;
+            }
+            None if target < self.current_frame => {
+                warn!("Cannot seek to frame {target}: no earlier keyframe is available.");
+                return requests;
+            }
+            _ => {}
+        }
+
+        while self.current_frame < target {
+            self.push_advance(&mut requests);
+        }
+
+        requests
+    }
+
+    /// Pushes the requests for advancing a single frame, saving a keyframe if required.
+    fn push_advance(&mut self, requests: &mut Vec<GgrsRequest<T>>) {
+        let Some(inputs) = self.recording.frame(self.current_frame) else {
+            return;
+        };
+
+        let inputs = inputs.to_vec();
+
+        let is_keyframe = self
+            .keyframe_interval
+            .is_some_and(|interval| self.current_frame % interval == 0);
+
+        if is_keyframe && self.keyframes.insert(self.current_frame) {
+            requests.push(GgrsRequest::SaveGameState {
+                cell: GameStateCell::default(),
+                frame: self.current_frame as i32,
+            });
+        }
+
+        self.current_frame += 1;
+
+        requests.push(GgrsRequest::AdvanceFrame { inputs });
+    }
+}
+
+/// [`Resource`] controlling playback of a [`ReplaySession`]. If this resource is not present,
+/// replays play continuously from start to finish.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::ReplayController;
+/// #
+/// fn replay_controls(keys: Res<Input<KeyCode>>, mut controller: ResMut<ReplayController>) {
+///     if keys.just_pressed(KeyCode::Space) {
+///         controller.toggle_pause();
+///     }
+///
+///     if keys.just_pressed(KeyCode::Right) {
+///         controller.step(1);
+///     }
+///
+///     if keys.just_pressed(KeyCode::Left) {
+///         controller.rewind(60);
+///     }
+/// }
+/// #
+/// # let mut app = App::new();
+/// # app.init_resource::<ReplayController>();
+/// # app.add_systems(Update, replay_controls);
+/// ```
+#[derive(Resource, Default, Debug, Clone)]
+pub struct ReplayController {
+    paused: bool,
+    steps: usize,
+    seek: Option<i32>,
+    current_frame: i32,
+}
+
+impl ReplayController {
+    /// Stops advancing the replay, other than through [`step`](`ReplayController::step`)
+    /// and [`seek`](`ReplayController::seek`).
+    pub fn pause(&mut self) -> &mut Self {
+        self.paused = true;
+        self
+    }
+
+    /// Continues advancing the replay.
+    pub fn resume(&mut self) -> &mut Self {
+        self.paused = false;
+        self.steps = 0;
+        self
+    }
+
+    /// Pauses the replay if it is playing, or resumes it if it is paused.
+    pub fn toggle_pause(&mut self) -> &mut Self {
+        if self.paused {
+            self.resume()
+        } else {
+            self.pause()
+        }
+    }
+
+    /// Returns `true` if the replay is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Advances the replay by the provided number of frames while paused.
+    pub fn step(&mut self, frames: usize) -> &mut Self {
+        self.steps = self.steps.saturating_add(frames);
+        self
+    }
+
+    /// Moves the replay to the provided frame at the next opportunity.
+    pub fn seek(&mut self, frame: i32) -> &mut Self {
+        self.seek = Some(frame);
+        self.steps = 0;
+        self
+    }
+
+    /// Moves the replay backwards by the provided number of frames, stopping at the start.
+    pub fn rewind(&mut self, frames: usize) -> &mut Self {
+        let frame = self.seek.unwrap_or(self.current_frame);
+        let frames = i32::try_from(frames).unwrap_or(i32::MAX);
+        self.seek(frame.saturating_sub(frames))
+    }
+
+    /// The frame the replay will advance next.
+    pub fn current_frame(&self) -> i32 {
+        self.current_frame
+    }
+
+    /// Returns the requests for the provided [`ReplaySession`] according to this controller.
+    pub(crate) fn advance<T: Config>(
+        &mut self,
+        session: &mut ReplaySession<T>,
+    ) -> Vec<GgrsRequest<T>> {
+        let requests = if let Some(frame) = self.seek.take() {
+            session.seek(frame)
+        } else if !self.paused {
+            session.advance_frame()
+        } else if self.steps > 0 {
+            self.steps -= 1;
+            session.advance_frame()
+        } else {
+            Vec::new()
+        };
+
+        self.current_frame = session.current_frame();
+
+        requests
+    }
+}
@@ -3,18 +3,40 @@ use bevy::{
     ecs::system::{EntityCommand, EntityCommands},
     prelude::*,
 };
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::RollbackFrameCount;
 
 /// This component flags an entity as being included in the rollback save/load schedule with GGRS.
 ///
 /// You must use the [`AddRollbackCommand`] when spawning an entity to add this component. Alternatively,
 /// you can use the `add_rollback()` extension method provided by [`AddRollbackCommandExtension`].
-#[derive(Component, Hash, PartialEq, Eq, Clone, Copy, Debug)]
-pub struct Rollback(Entity);
+///
+/// A [`Rollback`] is identified by the [`RollbackFrameCount`] it was added on, and the order it
+/// was added in within that frame. Since the [`RollbackOrdered`] allocating them is rolled back,
+/// an entity spawned again while resimulating a frame receives the same [`Rollback`] it had
+/// originally.
+#[derive(Component, Hash, PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Rollback {
+    frame: i32,
+    index: u32,
+}
 
 impl Rollback {
-    /// Creates a new [`Rollback`] component from an [`Entity`].
-    pub(crate) fn new(entity: Entity) -> Self {
-        Self(entity)
+    /// Creates a new [`Rollback`] component for the `index`th entity added on `frame`.
+    pub(crate) fn new(frame: i32, index: u32) -> Self {
+        Self { frame, index }
+    }
+
+    /// The [`RollbackFrameCount`] this [`Rollback`] was added on.
+    pub fn frame(&self) -> i32 {
+        self.frame
+    }
+
+    /// The order this [`Rollback`] was added in, among those added on the same frame.
+    pub fn index(&self) -> u32 {
+        self.index
     }
 }
 
@@ -23,13 +45,16 @@ pub struct AddRollbackCommand;
 
 impl EntityCommand for AddRollbackCommand {
     fn apply(self, id: Entity, world: &mut World) {
-        let rollback = Rollback::new(id);
+        let frame = world
+            .get_resource::<RollbackFrameCount>()
+            .map(|&frame| frame.into())
+            .unwrap_or_default();
 
-        world.entity_mut(id).insert(rollback);
-
-        world
+        let rollback = world
             .get_resource_or_insert_with::<RollbackOrdered>(default)
-            .push(rollback);
+            .allocate(frame);
+
+        world.entity_mut(id).insert(rollback);
     }
 }
 
@@ -53,36 +78,125 @@ impl<'w, 's, 'a> AddRollbackCommandExtension for EntityCommands<'w, 's, 'a> {
     }
 }
 
-/// A [`Resource`] which provides methods for stable ordering of [`Rollback`] flags.
+/// A [`Resource`] which allocates [`Rollback`] flags, and provides methods for their stable ordering.
+///
+/// This [`Resource`] is rolled back, so [`Rollback`] flags allocated during a mispredicted frame
+/// are discarded along with their entities. Flags of entities which were despawned before the
+/// [`ConfirmedFrameCount`](`crate::ConfirmedFrameCount`) are compacted by the
+/// [`EntitySnapshotPlugin`](`crate::EntitySnapshotPlugin`). Compaction never changes the
+/// [`order`](`Self::order`) of the remaining flags, or the [`total`](`Self::total`).
 #[derive(Resource, Default, Clone)]
 pub struct RollbackOrdered {
     order: HashMap<Rollback, usize>,
+    /// Sorted by order, with gaps left by compaction.
     sorted: Vec<Rollback>,
+    /// The number of [`Rollback`] flags ever registered.
+    total: usize,
+    /// The next unallocated [`Rollback`] index for every frame which may still allocate one.
+    allocated: BTreeMap<i32, u32>,
 }
 
 impl RollbackOrdered {
-    /// Register a new [`Rollback`] for explicit ordering.
-    fn push(&mut self, rollback: Rollback) -> &mut Self {
-        self.sorted.push(rollback);
-        self.order.insert(rollback, self.sorted.len() - 1);
+    /// Allocate and register the next [`Rollback`] for the provided frame.
+    pub(crate) fn allocate(&mut self, frame: i32) -> Rollback {
+        let index = self.allocated.get(&frame).copied().unwrap_or_default();
+
+        let rollback = Rollback::new(frame, index);
+        self.push(rollback);
+
+        rollback
+    }
+
+    /// Register an existing [`Rollback`] for explicit ordering, after all others.
+    pub(crate) fn push(&mut self, rollback: Rollback) -> &mut Self {
+        self.insert(rollback, self.total)
+    }
+
+    /// Register an existing [`Rollback`] with a particular order, such as from a [`SerializedWorld`](`crate::SerializedWorld`).
+    pub(crate) fn insert(&mut self, rollback: Rollback, order: usize) -> &mut Self {
+        let index = self
+            .sorted
+            .partition_point(|other| self.order[other] < order);
+
+        self.sorted.insert(index, rollback);
+        self.order.insert(rollback, order);
+        self.total = self.total.max(order + 1);
+
+        // Rollbacks may be registered out of order, such as from a SerializedWorld
+        let allocated = self.allocated.entry(rollback.frame).or_default();
+        *allocated = (*allocated).max(rollback.index + 1);
 
         self
     }
 
-    /// Iterate over all [`Rollback`] markers ever registered, even if they have since been deleted.
+    /// Ensures the [`total`](`Self::total`) is at least the provided value, such as when
+    /// later [`Rollback`] flags have been compacted.
+    pub(crate) fn reserve_total(&mut self, total: usize) -> &mut Self {
+        self.total = self.total.max(total);
+        self
+    }
+
+    /// Removes every [`Rollback`] allocated on or before `frame` for which `alive` returns `false`,
+    /// returning the number removed.
+    ///
+    /// This must only be called for frames which can no longer be rolled back to, as a removed
+    /// [`Rollback`] can no longer be [ordered](`Self::order`).
+    pub(crate) fn compact(&mut self, frame: i32, alive: impl Fn(&Rollback) -> bool) -> usize {
+        let before = self.sorted.len();
+        let order = &mut self.order;
+
+        // No more Rollbacks can be allocated on these frames
+        self.allocated
+            .retain(|&allocated_on, _| allocated_on > frame);
+
+        self.sorted.retain(|rollback| {
+            let keep = rollback.frame > frame || alive(rollback);
+
+            if !keep {
+                order.remove(rollback);
+            }
+
+            keep
+        });
+
+        before - self.sorted.len()
+    }
+
+    /// Iterate over all registered [`Rollback`] markers in order, including those which have since
+    /// been despawned but not yet compacted.
     pub fn iter_sorted(&self) -> impl Iterator<Item = Rollback> + '_ {
         self.sorted.iter().copied()
     }
 
     /// Returns a unique and order stable index for the provided [`Rollback`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`Rollback`] was not registered, or has since been compacted.
+    /// See [`try_order`](`Self::try_order`).
     pub fn order(&self, rollback: Rollback) -> usize {
-        self.order
-            .get(&rollback)
-            .copied()
+        self.try_order(rollback)
             .expect("Rollback requested was not created using AddRollbackCommand!")
     }
 
-    /// Get the number of registered [`Rollback`] entities.
+    /// Returns a unique and order stable index for the provided [`Rollback`], or `None` if it
+    /// was not registered, or has since been compacted.
+    pub fn try_order(&self, rollback: Rollback) -> Option<usize> {
+        self.order.get(&rollback).copied()
+    }
+
+    /// Get the [`Rollback`] with the provided [`order`](`Self::order`), if it is still registered.
+    pub fn get(&self, order: usize) -> Option<Rollback> {
+        let index = self
+            .sorted
+            .binary_search_by_key(&order, |rollback| self.order[rollback])
+            .ok()?;
+
+        Some(self.sorted[index])
+    }
+
+    /// Get the number of [`Rollback`] entities currently registered. This decreases when despawned
+    /// entities are compacted, so it may differ between peers. See [`total`](`Self::total`).
     pub fn len(&self) -> usize {
         self.order.len()
     }
@@ -91,4 +205,10 @@ impl RollbackOrdered {
     pub fn is_empty(&self) -> bool {
         self.order.is_empty()
     }
+
+    /// Get the number of [`Rollback`] entities ever registered, including those which have been
+    /// compacted. This is identical between peers on the same frame.
+    pub fn total(&self) -> usize {
+        self.total
+    }
 }
@@ -1,15 +1,20 @@
 use crate::{
-    AdvanceWorld, Checksum, ConfirmedFrameCount, FixedTimestepData, LoadWorld, LocalInputs,
-    LocalPlayers, MaxPredictionWindow, PlayerInputs, ReadInputs, RollbackFrameCount,
-    RollbackFrameRate, SaveWorld, Session,
+    AdvanceWorld, CatchUpLimitReached, CatchUpOverflow, Checksum, ConfirmedFrameCount,
+    DeterminismCheck, DeterminismCheckPlugin, FixedTimestepData, KeyframeInterval, LoadWorld,
+    LocalInputs, LocalPlayers, MaxPredictionWindow, PlayerInputs, ReadInputs, ReplayController,
+    ReplaySession, RollbackFrameCount, RollbackFrameOffset, RollbackFrameRate, SaveWorld, Session,
+    SessionEventsPlugin, SnapshotDepth, SnapshotDepthMargin, SyncTestDiagnosticsPlugin,
+    TimeSyncPolicy, WaitRecommendation, WaitRecommendationReader, DEFAULT_MAX_PREDICTION,
 };
 use bevy::{prelude::*, utils::Duration};
 use ggrs::{
     Config, GgrsError, GgrsRequest, P2PSession, SessionState, SpectatorSession, SyncTestSession,
 };
+use std::num::NonZeroUsize;
 
 pub(crate) fn run_ggrs_schedules<T: Config>(world: &mut World) {
     let framerate: usize = **world.get_resource_or_insert_with::<RollbackFrameRate>(default);
+    let policy = *world.get_resource_or_insert_with::<TimeSyncPolicy>(default);
 
     let mut time_data = world
         .remove_resource::<FixedTimestepData>()
@@ -22,10 +27,17 @@ pub(crate) fn run_ggrs_schedules<T: Config>(world: &mut World) {
 
     let mut fps_delta = 1. / framerate as f64;
     if time_data.run_slow {
-        fps_delta *= 1.1;
+        fps_delta *= policy.slow_down_factor;
     }
     time_data.accumulator = time_data.accumulator.saturating_add(delta);
 
+    // release at most one step worth of time from the backlog
+    if !time_data.backlog.is_zero() {
+        let step = time_data.backlog.min(Duration::from_secs_f64(fps_delta));
+        time_data.backlog -= step;
+        time_data.accumulator = time_data.accumulator.saturating_add(step);
+    }
+
     // no matter what, poll remotes and send responses
     if let Some(mut session) = world.get_resource_mut::<Session<T>>() {
         match &mut *session {
@@ -39,13 +51,47 @@ pub(crate) fn run_ggrs_schedules<T: Config>(world: &mut World) {
         }
     }
 
+    // forward any session events to Bevy
+    let events = SessionEventsPlugin::<T>::drain(world);
+
+    SessionEventsPlugin::<T>::send(world, events);
+
+    // recommendations are read back as Bevy events, so they can also be sent by the user
+    world.resource_scope(|world, mut reader: Mut<WaitRecommendationReader>| {
+        let Some(events) = world.get_resource::<Events<WaitRecommendation>>() else {
+            return;
+        };
+
+        for event in reader.0.read(events) {
+            if policy.honour_wait_recommendations {
+                time_data.skip_frames = time_data.skip_frames.saturating_add(event.skip_frames);
+            }
+        }
+    });
+
+    let max_steps = policy
+        .max_catch_up_steps
+        .map_or(usize::MAX, NonZeroUsize::get);
+    let mut steps = 0;
+
     // if we accumulated enough time, do steps
-    while time_data.accumulator.as_secs_f64() > fps_delta {
+    while time_data.accumulator.as_secs_f64() > fps_delta && steps < max_steps {
+        steps += 1;
+
         // decrease accumulator
         time_data.accumulator = time_data
             .accumulator
             .saturating_sub(Duration::from_secs_f64(fps_delta));
 
+        // skip this frame entirely if we were recommended to wait for remote clients
+        if time_data.skip_frames > 0
+            && matches!(world.get_resource::<Session<T>>(), Some(Session::P2P(_)))
+        {
+            time_data.skip_frames -= 1;
+            trace!("Skipping a frame: WaitRecommendation.");
+            continue;
+        }
+
         // depending on the session type, doing a single update looks a bit different
         let session = world.remove_resource::<Session<T>>();
         match session {
@@ -57,18 +103,42 @@ pub(crate) fn run_ggrs_schedules<T: Config>(world: &mut World) {
                 run_p2p(world, session);
             }
             Some(Session::Spectator(s)) => run_spectator(world, s),
+            Some(Session::Replay(s)) => run_replay(world, s),
             _ => {
                 // No session has been started yet, reset time data and snapshots
                 time_data.accumulator = Duration::ZERO;
                 time_data.run_slow = false;
+                time_data.skip_frames = 0;
+                time_data.backlog = Duration::ZERO;
                 world.insert_resource(LocalPlayers::default());
-                world.insert_resource(RollbackFrameCount(0));
-                world.insert_resource(ConfirmedFrameCount(-1));
-                world.insert_resource(MaxPredictionWindow(8));
+                let offset = world
+                    .get_resource::<RollbackFrameOffset>()
+                    .map(|offset| offset.0)
+                    .unwrap_or_default();
+                world.insert_resource(RollbackFrameCount(offset));
+                world.insert_resource(ConfirmedFrameCount(offset - 1));
+                insert_max_prediction(world, DEFAULT_MAX_PREDICTION);
             }
         }
     }
 
+    // if we hit the step limit, deal with the leftover time
+    if steps == max_steps && time_data.accumulator.as_secs_f64() > fps_delta {
+        let leftover = time_data.accumulator;
+
+        match policy.catch_up_overflow {
+            CatchUpOverflow::Drop => time_data.accumulator = Duration::ZERO,
+            CatchUpOverflow::Carry => {}
+            CatchUpOverflow::Spread => {
+                time_data.backlog = time_data.backlog.saturating_add(leftover);
+                time_data.accumulator = Duration::ZERO;
+            }
+        }
+
+        debug!("Reached the limit of {steps} steps per update, {leftover:?} left over.");
+        world.send_event(CatchUpLimitReached { steps, leftover });
+    }
+
     world.insert_resource(time_data);
 }
 
@@ -91,6 +161,13 @@ pub(crate) fn run_synctest<C: Config>(world: &mut World, mut sess: SyncTestSessi
 
     match requests {
         Ok(requests) => handle_requests(requests, world),
+        Err(GgrsError::MismatchedChecksum {
+            current_frame,
+            mismatched_frames,
+        }) => {
+            warn!("Detected checksum mismatch during rollback on frame {current_frame}, mismatched frames: {mismatched_frames:?}");
+            SyncTestDiagnosticsPlugin::diagnose(world, &mismatched_frames);
+        }
         Err(e) => warn!("{e}"),
     }
 }
@@ -112,6 +189,28 @@ pub(crate) fn run_spectator<T: Config>(world: &mut World, mut sess: SpectatorSes
     };
 }
 
+pub(crate) fn run_replay<T: Config>(world: &mut World, mut sess: ReplaySession<T>) {
+    // there are no local players when watching a replay
+    world.insert_resource(LocalPlayers::default());
+
+    // keep keyframes around to allow seeking
+    match sess.keyframe_interval() {
+        Some(interval) => world.insert_resource(KeyframeInterval(interval)),
+        None => {
+            world.remove_resource::<KeyframeInterval>();
+        }
+    }
+
+    let requests = match world.get_resource_mut::<ReplayController>() {
+        Some(mut controller) => controller.advance(&mut sess),
+        None => sess.advance_frame(),
+    };
+
+    world.insert_resource(Session::Replay(sess));
+
+    handle_requests(requests, world);
+}
+
 pub(crate) fn run_p2p<C: Config>(world: &mut World, mut sess: P2PSession<C>) {
     world.insert_resource(LocalPlayers(sess.local_player_handles()));
 
@@ -163,6 +262,9 @@ pub(crate) fn handle_requests<T: Config>(requests: Vec<GgrsRequest<T>>, world: &
         panic!("Could not extract AdvanceWorld Schedule!");
     };
 
+    // The frame which was most recently saved or loaded, which can be loaded again if required
+    let mut snapshot_frame = None;
+
     // Run Schedules as Required
     for request in requests {
         let current_frame = world
@@ -170,27 +272,34 @@ pub(crate) fn handle_requests<T: Config>(requests: Vec<GgrsRequest<T>>, world: &
             .map(|frame| frame.0)
             .unwrap_or_default();
 
+        let offset = world
+            .get_resource::<RollbackFrameOffset>()
+            .map(|offset| offset.0)
+            .unwrap_or_default();
+
         let session = world.get_resource::<Session<T>>();
 
         let max_prediction = match session {
             Some(Session::P2P(s)) => Some(s.max_prediction()),
             Some(Session::SyncTest(s)) => Some(s.max_prediction()),
             Some(Session::Spectator(_)) => Some(0),
+            Some(Session::Replay(_)) => Some(0),
             None => None,
         };
 
         let confirmed_frame = match session {
-            Some(Session::P2P(s)) => Some(s.confirmed_frame()),
+            Some(Session::P2P(s)) => Some(s.confirmed_frame() + offset),
             Some(Session::SyncTest(s)) => {
                 let current_frame = current_frame - (s.check_distance() as i32);
-                (current_frame < 0).then_some(current_frame)
+                (current_frame >= offset).then_some(current_frame)
             }
             Some(Session::Spectator(_)) => Some(current_frame),
+            Some(Session::Replay(_)) => Some(current_frame),
             None => None,
         };
 
         if let Some(max_prediction) = max_prediction {
-            world.insert_resource(MaxPredictionWindow(max_prediction));
+            insert_max_prediction(world, max_prediction);
         }
 
         if let Some(confirmed_frame) = confirmed_frame {
@@ -204,6 +313,7 @@ pub(crate) fn handle_requests<T: Config>(requests: Vec<GgrsRequest<T>>, world: &
                 debug!("saving snapshot for frame {frame}");
 
                 save_world_schedule.run(world);
+                snapshot_frame = Some(current_frame);
 
                 // look into resources and find the checksum
                 let checksum = world
@@ -222,26 +332,39 @@ pub(crate) fn handle_requests<T: Config>(requests: Vec<GgrsRequest<T>>, world: &
                 world
                     .get_resource_mut::<RollbackFrameCount>()
                     .expect("Unable to find GGRS RollbackFrameCount. Did you remove it?")
-                    .0 = frame;
+                    .0 = frame + offset;
 
                 load_world_schedule.run(world);
+                snapshot_frame = Some(frame + offset);
             }
             GgrsRequest::AdvanceFrame { inputs } => {
                 let _span =
                     bevy::utils::tracing::info_span!("schedule", name = "AdvanceWorld").entered();
-                let mut frame_count = world
-                    .get_resource_mut::<RollbackFrameCount>()
-                    .expect("Unable to find GGRS RollbackFrameCount. Did you remove it?");
-
-                frame_count.0 += 1;
-                let frame = frame_count.0;
+                let frame = world
+                    .get_resource::<RollbackFrameCount>()
+                    .expect("Unable to find GGRS RollbackFrameCount. Did you remove it?")
+                    .0
+                    + 1;
 
                 debug!("advancing to frame: {}", frame);
                 world.insert_resource(PlayerInputs::<T>(inputs));
 
-                advance_world_schedule.run(world);
+                // frames can only be checked if they can be loaded again
+                if world.contains_resource::<DeterminismCheck>()
+                    && snapshot_frame == Some(frame - 1)
+                {
+                    DeterminismCheckPlugin::check(
+                        world,
+                        &mut load_world_schedule,
+                        &mut advance_world_schedule,
+                    );
+                } else {
+                    world.resource_mut::<RollbackFrameCount>().0 = frame;
+                    advance_world_schedule.run(world);
+                }
 
                 world.remove_resource::<PlayerInputs<T>>();
+                snapshot_frame = None;
                 debug!("frame {frame} completed");
             }
         }
@@ -265,3 +388,23 @@ pub(crate) fn handle_requests<T: Config>(requests: Vec<GgrsRequest<T>>, world: &
         panic!("GgrsSchedule Schedule was Duplicated!");
     }
 }
+
+/// Updates the [`MaxPredictionWindow`] and the [`SnapshotDepth`] derived from it.
+fn insert_max_prediction(world: &mut World, max_prediction: usize) {
+    let margin = world
+        .get_resource::<SnapshotDepthMargin>()
+        .map(|margin| margin.0)
+        .unwrap_or_default();
+
+    let window = MaxPredictionWindow(max_prediction);
+    let depth = SnapshotDepth::new(max_prediction, margin);
+
+    // Only replaced when changed, so systems watching for changes don't run every frame
+    if world.get_resource::<MaxPredictionWindow>() != Some(&window) {
+        world.insert_resource(window);
+    }
+
+    if world.get_resource::<SnapshotDepth>() != Some(&depth) {
+        world.insert_resource(depth);
+    }
+}
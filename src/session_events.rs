@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+use ggrs::{Config, Frame, GgrsEvent};
+
+use crate::Session;
+
+/// Sent while the [`Session`] is synchronizing with a remote peer.
+#[derive(Event, Debug)]
+pub struct Synchronizing<T: Config> {
+    /// The address of the remote peer.
+    pub addr: T::Address,
+    /// The total number of synchronization roundtrips required.
+    pub total: u32,
+    /// The number of synchronization roundtrips completed so far.
+    pub count: u32,
+}
+
+/// Sent once the [`Session`] has finished synchronizing with a remote peer.
+#[derive(Event, Debug)]
+pub struct Synchronized<T: Config> {
+    /// The address of the remote peer.
+    pub addr: T::Address,
+}
+
+/// Sent when a remote peer has been disconnected.
+#[derive(Event, Debug)]
+pub struct Disconnected<T: Config> {
+    /// The address of the remote peer.
+    pub addr: T::Address,
+}
+
+/// Sent when no packets have been received from a remote peer for some time.
+#[derive(Event, Debug)]
+pub struct NetworkInterrupted<T: Config> {
+    /// The address of the remote peer.
+    pub addr: T::Address,
+    /// The time in milliseconds until the remote peer will be disconnected.
+    pub disconnect_timeout: u128,
+}
+
+/// Sent when packets are received again from a previously interrupted remote peer.
+#[derive(Event, Debug)]
+pub struct NetworkResumed<T: Config> {
+    /// The address of the remote peer.
+    pub addr: T::Address,
+}
+
+/// Sent when the local [`Session`] is too far ahead of remote peers and should skip frames
+/// to let them catch up.
+///
+/// Sending this event yourself will also skip frames, as long as
+/// [`honour_wait_recommendations`](`crate::TimeSyncPolicy::honour_wait_recommendations`) is enabled.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct WaitRecommendation {
+    /// The number of frames which should be skipped.
+    pub skip_frames: u32,
+}
+
+/// Sent when the checksum of a confirmed frame differs between the local and a remote peer.
+#[derive(Event, Debug)]
+pub struct DesyncDetected<T: Config> {
+    /// The frame which desynced.
+    pub frame: Frame,
+    /// The local [`Checksum`](`crate::Checksum`) for the frame.
+    pub local_checksum: u128,
+    /// The remote [`Checksum`](`crate::Checksum`) for the frame.
+    pub remote_checksum: u128,
+    /// The address of the remote peer.
+    pub addr: T::Address,
+}
+
+/// A [`Plugin`] which drains the [events](`GgrsEvent`) of a [`P2P`](`Session::P2P`) or
+/// [`Spectator`](`Session::Spectator`) [`Session`] every update, and re-sends them as
+/// typed Bevy [`Events`](`Event`). This plugin is added automatically by [`GgrsPlugin`](`crate::GgrsPlugin`).
+///
+/// Since the [`Session`] events are drained, you should use an [`EventReader`] for the
+/// relevant event type instead of calling `events()` on the [`Session`] directly.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, Disconnected, Synchronized};
+/// #
+/// # type MyConfig = GgrsConfig<u8>;
+/// #
+/// fn print_connections(
+///     mut synchronized: EventReader<Synchronized<MyConfig>>,
+///     mut disconnected: EventReader<Disconnected<MyConfig>>,
+/// ) {
+///     for event in synchronized.read() {
+///         info!("Synchronized with {:?}", event.addr);
+///     }
+///
+///     for event in disconnected.read() {
+///         warn!("Disconnected from {:?}", event.addr);
+///     }
+/// }
+/// #
+/// # let mut app = App::new();
+/// # app.add_plugins(GgrsPlugin::<MyConfig>::default());
+/// # app.add_systems(Update, print_connections);
+/// ```
+pub struct SessionEventsPlugin<T: Config> {
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: Config> Default for SessionEventsPlugin<T> {
+    fn default() -> Self {
+        Self {
+            _phantom: default(),
+        }
+    }
+}
+
+impl<T: Config> SessionEventsPlugin<T> {
+    /// Drains all pending [`GgrsEvents`](`GgrsEvent`) from the [`Session`], if there is one.
+    pub(crate) fn drain(world: &mut World) -> Vec<GgrsEvent<T>> {
+        let Some(mut session) = world.get_resource_mut::<Session<T>>() else {
+            return Vec::new();
+        };
+
+        match &mut *session {
+            Session::P2P(session) => session.events().collect(),
+            Session::Spectator(session) => session.events().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Re-sends the provided [`GgrsEvents`](`GgrsEvent`) as typed Bevy [`Events`](`Event`).
+    pub(crate) fn send(world: &mut World, events: Vec<GgrsEvent<T>>) {
+        for event in events {
+            match event {
+                GgrsEvent::Synchronizing { addr, total, count } => {
+                    debug!("GGRS event: Synchronizing with {addr:?} ({count}/{total})");
+                    world.send_event(Synchronizing::<T> { addr, total, count });
+                }
+                GgrsEvent::Synchronized { addr } => {
+                    info!("GGRS event: Synchronized with {addr:?}");
+                    world.send_event(Synchronized::<T> { addr });
+                }
+                GgrsEvent::Disconnected { addr } => {
+                    warn!("GGRS event: Disconnected from {addr:?}");
+                    world.send_event(Disconnected::<T> { addr });
+                }
+                GgrsEvent::NetworkInterrupted {
+                    addr,
+                    disconnect_timeout,
+                } => {
+                    warn!("GGRS event: Network interrupted with {addr:?}");
+                    world.send_event(NetworkInterrupted::<T> {
+                        addr,
+                        disconnect_timeout,
+                    });
+                }
+                GgrsEvent::NetworkResumed { addr } => {
+                    info!("GGRS event: Network resumed with {addr:?}");
+                    world.send_event(NetworkResumed::<T> { addr });
+                }
+                GgrsEvent::WaitRecommendation { skip_frames } => {
+                    debug!("GGRS event: Recommended to skip {skip_frames} frame(s)");
+                    world.send_event(WaitRecommendation { skip_frames });
+                }
+                GgrsEvent::DesyncDetected {
+                    frame,
+                    local_checksum,
+                    remote_checksum,
+                    addr,
+                } => {
+                    error!("GGRS event: Desync on frame {frame} with {addr:?}. Local checksum: {local_checksum:X}, remote checksum: {remote_checksum:X}");
+                    world.send_event(DesyncDetected::<T> {
+                        frame,
+                        local_checksum,
+                        remote_checksum,
+                        addr,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl<T: Config> Plugin for SessionEventsPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Synchronizing<T>>()
+            .add_event::<Synchronized<T>>()
+            .add_event::<Disconnected<T>>()
+            .add_event::<NetworkInterrupted<T>>()
+            .add_event::<NetworkResumed<T>>()
+            .add_event::<WaitRecommendation>()
+            .add_event::<DesyncDetected<T>>();
+    }
+}
@@ -0,0 +1,189 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use bevy::{
+    ecs::{component::Tick, system::SystemChangeTick},
+    prelude::*,
+};
+
+use crate::{
+    GgrsComponentSnapshot, GgrsComponentSnapshots, LoadWorld, LoadWorldSet, Rollback,
+    RollbackFrameCount, RollbackTypes, SaveWorld, SaveWorldSet, Strategy,
+};
+
+/// A [`Resource`] recording when the [`Component`] `C` was last known to match the newest snapshot
+/// stored by a [`ChangedComponentSnapshotPlugin`].
+#[derive(Resource)]
+pub struct ChangedSnapshotSync<C> {
+    tick: Tick,
+    _phantom: PhantomData<C>,
+}
+
+impl<C> Default for ChangedSnapshotSync<C> {
+    fn default() -> Self {
+        Self {
+            tick: Tick::new(0),
+            _phantom: default(),
+        }
+    }
+}
+
+/// A [`Plugin`] which manages snapshots for a [`Component`] using a provided [`Strategy`], like a
+/// [`ComponentSnapshotPlugin`](`crate::ComponentSnapshotPlugin`), but only calls [`Strategy::store`]
+/// for components which changed since the previous snapshot.
+///
+/// Stored values are shared between snapshots using an [`Arc`], so unchanged components cost a
+/// reference count increment rather than a full copy. This is best suited to large components
+/// which rarely change, such as inventories.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, ChangedComponentSnapshotPlugin, CloneStrategy};
+/// #
+/// #[derive(Component, Clone)]
+/// struct Inventory {
+///     items: Vec<u32>,
+/// }
+///
+/// # let mut app = App::new();
+/// # app.add_plugins(GgrsPlugin::<GgrsConfig<u8>>::default());
+/// // Inventories are only cloned on frames where they are modified
+/// app.add_plugins(ChangedComponentSnapshotPlugin::<CloneStrategy<Inventory>>::default());
+/// ```
+pub struct ChangedComponentSnapshotPlugin<S>
+where
+    S: Strategy,
+    S::Target: Component,
+    S::Stored: Send + Sync + 'static,
+{
+    _phantom: PhantomData<S>,
+}
+
+impl<S> Default for ChangedComponentSnapshotPlugin<S>
+where
+    S: Strategy,
+    S::Target: Component,
+    S::Stored: Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self {
+            _phantom: default(),
+        }
+    }
+}
+
+impl<S> ChangedComponentSnapshotPlugin<S>
+where
+    S: Strategy,
+    S::Target: Component,
+    S::Stored: Send + Sync + 'static,
+{
+    pub fn save(
+        mut snapshots: ResMut<GgrsComponentSnapshots<S::Target, Arc<S::Stored>>>,
+        mut sync: ResMut<ChangedSnapshotSync<S::Target>>,
+        frame: Res<RollbackFrameCount>,
+        query: Query<(&Rollback, Ref<S::Target>)>,
+        ticks: SystemChangeTick,
+    ) {
+        let mut reused = 0;
+
+        let snapshot = {
+            let previous = snapshots.newest();
+
+            let components = query.iter().map(|(&rollback, component)| {
+                let unchanged = !component
+                    .last_changed()
+                    .is_newer_than(sync.tick, ticks.this_run());
+
+                let stored = previous
+                    .filter(|_| unchanged)
+                    .and_then(|previous| previous.get(&rollback))
+                    .map(Arc::clone);
+
+                let stored = match stored {
+                    Some(stored) => {
+                        reused += 1;
+                        stored
+                    }
+                    None => Arc::new(S::store(component.as_ref())),
+                };
+
+                (rollback, stored)
+            });
+
+            GgrsComponentSnapshot::new(components)
+        };
+
+        trace!(
+            "Snapshot {} {} component(s), reusing {}",
+            snapshot.iter().count(),
+            bevy::utils::get_short_name(std::any::type_name::<S::Target>()),
+            reused
+        );
+
+        snapshots.push(frame.0, snapshot);
+        sync.tick = ticks.this_run();
+    }
+
+    pub fn load(
+        mut commands: Commands,
+        mut snapshots: ResMut<GgrsComponentSnapshots<S::Target, Arc<S::Stored>>>,
+        mut sync: ResMut<ChangedSnapshotSync<S::Target>>,
+        frame: Res<RollbackFrameCount>,
+        mut query: Query<(Entity, &Rollback, Option<&mut S::Target>)>,
+        ticks: SystemChangeTick,
+    ) {
+        let snapshot = snapshots.rollback(frame.0).get();
+
+        for (entity, rollback, component) in query.iter_mut() {
+            let snapshot = snapshot.get(rollback);
+
+            match (component, snapshot) {
+                (Some(mut component), Some(snapshot)) => S::update(component.as_mut(), snapshot),
+                (Some(_), None) => {
+                    commands.entity(entity).remove::<S::Target>();
+                }
+                (None, Some(snapshot)) => {
+                    commands.entity(entity).insert(S::load(snapshot));
+                }
+                (None, None) => {}
+            }
+        }
+
+        // Components updated above now match the selected snapshot
+        sync.tick = ticks.this_run();
+
+        trace!(
+            "Rolled back {} {} component(s)",
+            snapshot.iter().count(),
+            bevy::utils::get_short_name(std::any::type_name::<S::Target>())
+        );
+    }
+}
+
+impl<S> Plugin for ChangedComponentSnapshotPlugin<S>
+where
+    S: Send + Sync + 'static + Strategy,
+    S::Target: Component,
+    S::Stored: Send + Sync + 'static,
+{
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RollbackTypes>()
+            .world
+            .resource_mut::<RollbackTypes>()
+            .register_component::<S::Target>();
+
+        app.init_resource::<GgrsComponentSnapshots<S::Target, Arc<S::Stored>>>()
+            .init_resource::<ChangedSnapshotSync<S::Target>>()
+            .add_systems(
+                SaveWorld,
+                (
+                    GgrsComponentSnapshots::<S::Target, Arc<S::Stored>>::discard_old_snapshots,
+                    Self::save,
+                )
+                    .chain()
+                    .in_set(SaveWorldSet::Snapshot),
+            )
+            .add_systems(LoadWorld, Self::load.in_set(LoadWorldSet::Data));
+    }
+}
@@ -36,6 +36,26 @@ impl ChecksumPart {
     }
 }
 
+/// Names the type a [`ChecksumPart`] was generated for. Used to identify which type
+/// diverged when diagnosing a desync.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChecksumPartName(pub &'static str);
+
+impl ChecksumPartName {
+    /// Creates a [`ChecksumPartName`] from the [type name](`std::any::type_name`) of `T`.
+    pub fn of<T>() -> Self {
+        Self(std::any::type_name::<T>())
+    }
+}
+
+/// Per-[`Rollback`](`crate::Rollback`) contributions to a [`ChecksumPart`], stored as
+/// `(order, hash)` pairs sorted by [`order`](`crate::RollbackOrdered::order`).
+///
+/// This is only populated when per-entity tracking has been enabled through
+/// [`ChecksumHistory`](`crate::ChecksumHistory`), as it is relatively expensive to maintain.
+#[derive(Component, Clone, Default, Debug)]
+pub struct ChecksumPartEntities(pub Vec<(usize, u64)>);
+
 /// Represents a total checksum for a given frame.
 #[derive(Resource, Default, Clone, Copy)]
 pub struct Checksum(pub u128);
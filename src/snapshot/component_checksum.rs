@@ -2,7 +2,10 @@ use std::hash::{BuildHasher, Hash, Hasher};
 
 use bevy::prelude::*;
 
-use crate::{ChecksumFlag, ChecksumPart, Rollback, RollbackOrdered, SaveWorld, SaveWorldSet};
+use crate::{
+    ChecksumFlag, ChecksumHistory, ChecksumPart, ChecksumPartEntities, ChecksumPartName,
+    ChecksumWorld, Rollback, RollbackOrdered,
+};
 
 /// A [`Plugin`] which will track the [`Component`] `C` on [`Rollback Entities`](`Rollback`) and ensure a
 /// [`ChecksumPart`] is available and updated. This can be used to generate a [`Checksum`](`crate::Checksum`).
@@ -54,49 +57,71 @@ where
     fn build(&self, app: &mut App) {
         let custom_hasher = self.0;
 
-        let update = move |mut commands: Commands,
-                           rollback_ordered: Res<RollbackOrdered>,
-                           components: Query<
-            (&Rollback, &C),
-            (With<Rollback>, Without<ChecksumFlag<C>>),
-        >,
-                           mut checksum: Query<
-            &mut ChecksumPart,
-            (Without<Rollback>, With<ChecksumFlag<C>>),
-        >| {
-            let mut hasher = bevy::utils::FixedState.build_hasher();
-
-            let mut result = 0;
-
-            for (&rollback, component) in components.iter() {
-                let mut hasher = hasher.clone();
-
-                // Hashing the rollback index ensures this hash is unique and stable
-                rollback_ordered.order(rollback).hash(&mut hasher);
-                custom_hasher(component).hash(&mut hasher);
-
-                // XOR chosen over addition or multiplication as it is closed on u64 and commutative
-                result ^= hasher.finish();
-            }
-
-            // Hash the XOR'ed result to break commutativity with other types
-            result.hash(&mut hasher);
-
-            let result = ChecksumPart(hasher.finish() as u128);
-
-            trace!(
-                "Component {} has checksum {:X}",
-                bevy::utils::get_short_name(std::any::type_name::<C>()),
-                result.0
-            );
-
-            if let Ok(mut checksum) = checksum.get_single_mut() {
-                *checksum = result;
-            } else {
-                commands.spawn((result, ChecksumFlag::<C>::default()));
-            }
-        };
-
-        app.add_systems(SaveWorld, update.in_set(SaveWorldSet::Checksum));
+        let update =
+            move |mut commands: Commands,
+                  rollback_ordered: Res<RollbackOrdered>,
+                  history: Option<Res<ChecksumHistory>>,
+                  components: Query<
+                (&Rollback, &C),
+                (With<Rollback>, Without<ChecksumFlag<C>>),
+            >,
+                  checksum: Query<Entity, (Without<Rollback>, With<ChecksumFlag<C>>)>| {
+                let track_entities = history.is_some_and(|history| history.tracks_entities());
+
+                let mut hasher = bevy::utils::FixedState.build_hasher();
+
+                let mut result = 0;
+                let mut entities = Vec::new();
+
+                for (&rollback, component) in components.iter() {
+                    let mut hasher = hasher.clone();
+
+                    // Only registered Rollbacks have a stable order to hash
+                    let Some(order) = rollback_ordered.try_order(rollback) else {
+                        continue;
+                    };
+                    let component_hash = custom_hasher(component);
+
+                    // Hashing the rollback index ensures this hash is unique and stable
+                    order.hash(&mut hasher);
+                    component_hash.hash(&mut hasher);
+
+                    // XOR chosen over addition or multiplication as it is closed on u64 and commutative
+                    result ^= hasher.finish();
+
+                    if track_entities {
+                        entities.push((order, component_hash));
+                    }
+                }
+
+                // Hash the XOR'ed result to break commutativity with other types
+                result.hash(&mut hasher);
+
+                let result = ChecksumPart(hasher.finish() as u128);
+
+                trace!(
+                    "Component {} has checksum {:X}",
+                    bevy::utils::get_short_name(std::any::type_name::<C>()),
+                    result.0
+                );
+
+                entities.sort_unstable();
+                let entities = ChecksumPartEntities(entities);
+
+                // Written through Commands so checksums for different types can be computed in parallel.
+                // ChecksumWorld applies these before the total Checksum is produced.
+                if let Ok(checksum) = checksum.get_single() {
+                    commands.entity(checksum).insert((result, entities));
+                } else {
+                    commands.spawn((
+                        result,
+                        entities,
+                        ChecksumFlag::<C>::default(),
+                        ChecksumPartName::of::<C>(),
+                    ));
+                }
+            };
+
+        app.add_systems(ChecksumWorld, update);
     }
 }
@@ -3,6 +3,7 @@ use std::marker::PhantomData;
 use bevy::{
     ecs::entity::{EntityMapper, MapEntities},
     prelude::*,
+    utils::HashMap,
 };
 
 use crate::{LoadWorld, LoadWorldSet, RollbackEntityMap};
@@ -63,23 +64,29 @@ where
     /// Exclusive system which will apply a [`RollbackEntityMap`] to the [`Component`] `C`, provided it implements [`MapEntities`].
     pub fn update(world: &mut World) {
         world.resource_scope(|world: &mut World, map: Mut<RollbackEntityMap>| {
-            apply_rollback_map_to_component_inner::<C>(world, map);
+            apply_rollback_map_to_component(
+                world,
+                map,
+                std::any::type_name::<C>(),
+                |world, entity_map| EntityMapper::world_scope(entity_map, world, apply_map::<C>),
+            );
         });
     }
 }
 
-fn apply_rollback_map_to_component_inner<C>(world: &mut World, map: Mut<RollbackEntityMap>)
-where
-    C: Component + MapEntities,
-{
+/// Applies a [`RollbackEntityMap`] to a [`Component`] using the provided `apply` function, which
+/// should map all entities in the provided [`HashMap`].
+pub(crate) fn apply_rollback_map_to_component(
+    world: &mut World,
+    map: Mut<RollbackEntityMap>,
+    type_name: &str,
+    apply: impl Fn(&mut World, &mut HashMap<Entity, Entity>),
+) {
     let mut applied_entity_map = map.generate_map();
 
-    EntityMapper::world_scope(&mut applied_entity_map, world, apply_map::<C>);
+    apply(world, &mut applied_entity_map);
 
-    trace!(
-        "Mapped {}",
-        bevy::utils::get_short_name(std::any::type_name::<C>())
-    );
+    trace!("Mapped {}", bevy::utils::get_short_name(type_name));
 
     // If the entity map is now larger than the set of rollback entities, then dead entities were created.
     // TODO: This workaround is required because the current behavior of `map_all_entities` is to change all entities,
@@ -100,12 +107,9 @@ where
         }
 
         // Map entities a second time, fixing dead entities
-        EntityMapper::world_scope(&mut applied_entity_map, world, apply_map::<C>);
+        apply(world, &mut applied_entity_map);
 
-        trace!(
-            "Re-Mapped {}",
-            bevy::utils::get_short_name(std::any::type_name::<C>())
-        );
+        trace!("Re-Mapped {}", bevy::utils::get_short_name(type_name));
     }
 }
 
@@ -1,6 +1,6 @@
 use crate::{
     GgrsComponentSnapshot, GgrsComponentSnapshots, LoadWorld, LoadWorldSet, Rollback,
-    RollbackFrameCount, SaveWorld, SaveWorldSet, Strategy,
+    RollbackFrameCount, RollbackTypes, SaveWorld, SaveWorldSet, Strategy,
 };
 use bevy::prelude::*;
 use std::marker::PhantomData;
@@ -110,6 +110,11 @@ where
     S::Stored: Send + Sync + 'static,
 {
     fn build(&self, app: &mut App) {
+        app.init_resource::<RollbackTypes>()
+            .world
+            .resource_mut::<RollbackTypes>()
+            .register_component::<S::Target>();
+
         app.init_resource::<GgrsComponentSnapshots<S::Target, S::Stored>>()
             .add_systems(
                 SaveWorld,
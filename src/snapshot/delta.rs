@@ -0,0 +1,515 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    marker::PhantomData,
+    sync::Arc,
+};
+
+use bevy::{
+    ecs::{component::Tick, system::SystemChangeTick},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    ConfirmedFrameCount, KeyframeInterval, LoadWorld, LoadWorldSet, Rollback, RollbackFrameCount,
+    RollbackTypes, SaveWorld, SaveWorldSet, SnapshotDepth, Strategy,
+};
+
+/// The changes made to a [`Component`] between two saved frames.
+pub struct GgrsDelta<As> {
+    /// Components which were added or changed, stored as `As`.
+    changed: HashMap<Rollback, As>,
+    /// [`Rollback`] entities which no longer have the component.
+    removed: HashSet<Rollback>,
+}
+
+impl<As> Default for GgrsDelta<As> {
+    fn default() -> Self {
+        Self {
+            changed: default(),
+            removed: default(),
+        }
+    }
+}
+
+impl<As> GgrsDelta<As> {
+    /// Applies a newer delta on top of this one.
+    fn merge(&mut self, newer: Self) {
+        for rollback in newer.removed {
+            self.changed.remove(&rollback);
+            self.removed.insert(rollback);
+        }
+
+        for (rollback, stored) in newer.changed {
+            self.removed.remove(&rollback);
+            self.changed.insert(rollback, stored);
+        }
+    }
+
+    /// Iterate over every [`Rollback`] this delta affects.
+    fn affected(&self) -> impl Iterator<Item = Rollback> + '_ {
+        self.changed.keys().chain(self.removed.iter()).copied()
+    }
+}
+
+/// Alternative to [`GgrsComponentSnapshots`](`crate::GgrsComponentSnapshots`) which stores only the
+/// [`Components`](`Component`) which changed since the previous saved frame, based on Bevy's change
+/// ticks. Older changes are folded into a single base state once they are no longer required.
+///
+/// This uses considerably less memory when most components do not change every frame, at the cost
+/// of reconstructing older frames when rolling back. Keyframes (see [`KeyframeInterval`]) are
+/// retained by copying the base state once the keyframe's delta has been folded into it. Stored
+/// values are shared between the base state and keyframes, so only the maps themselves are copied.
+#[derive(Resource)]
+pub struct GgrsDeltaSnapshots<For, As = For> {
+    /// The state of every component as of the oldest retained delta.
+    base: HashMap<Rollback, Arc<As>>,
+    /// The frame `base` was stored for, if any deltas have been folded into it.
+    base_frame: Option<i32>,
+    /// Queue of deltas, newest at the front, oldest at the back.
+    deltas: VecDeque<(i32, GgrsDelta<As>)>,
+    /// [`Rollback`] entities with the component on the newest frame.
+    present: HashSet<Rollback>,
+    /// Base states retained after being folded, see [`KeyframeInterval`].
+    keyframes: BTreeMap<i32, HashMap<Rollback, Arc<As>>>,
+    /// Interval between frames to retain as keyframes, if any.
+    keyframe_interval: Option<usize>,
+    /// [`Rollback`] entities affected by the deltas discarded by the last `rollback(frame)`.
+    dirty: HashSet<Rollback>,
+    /// Set when the last `rollback(frame)` selected a keyframe, so any component may differ.
+    all_dirty: bool,
+    /// The [`Tick`] of the last save, used to find components changed since.
+    last_save: Tick,
+    /// Maximum amount of deltas to store at any one time
+    depth: usize,
+    _phantom: PhantomData<For>,
+}
+
+impl<For, As> Default for GgrsDeltaSnapshots<For, As> {
+    fn default() -> Self {
+        // Replaced by the SnapshotDepth of the Session once it starts
+        let depth = SnapshotDepth::default().0;
+
+        Self {
+            base: default(),
+            base_frame: None,
+            deltas: VecDeque::with_capacity(depth),
+            present: default(),
+            keyframes: default(),
+            keyframe_interval: None,
+            dirty: default(),
+            all_dirty: false,
+            last_save: Tick::new(0),
+            depth,
+            _phantom: default(),
+        }
+    }
+}
+
+impl<For, As> GgrsDeltaSnapshots<For, As> {
+    /// Updates the capacity of this storage to the provided depth.
+    pub fn set_depth(&mut self, depth: usize) -> &mut Self {
+        self.depth = depth;
+
+        while self.deltas.len() > self.depth {
+            self.fold_oldest();
+        }
+
+        // Greedy allocation to avoid allocating at a more sensitive time.
+        if self.deltas.capacity() < self.depth {
+            let additional = self.depth - self.deltas.capacity();
+            self.deltas.reserve(additional);
+        }
+
+        self
+    }
+
+    /// Get the current capacity of this snapshot storage.
+    pub const fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Sets the interval between frames which are retained as keyframes once folded.
+    /// If `None`, no keyframes are retained.
+    pub fn set_keyframe_interval(&mut self, interval: Option<usize>) -> &mut Self {
+        self.keyframe_interval = interval.filter(|&interval| interval > 0);
+        self
+    }
+
+    /// Get the interval between frames which are retained as keyframes, if any.
+    pub const fn keyframe_interval(&self) -> Option<usize> {
+        self.keyframe_interval
+    }
+
+    /// Iterate over the frames of all retained keyframes, oldest first.
+    pub fn keyframes(&self) -> impl Iterator<Item = i32> + '_ {
+        self.keyframes.keys().copied()
+    }
+
+    /// Discards all retained keyframes.
+    pub fn clear_keyframes(&mut self) -> &mut Self {
+        self.keyframes.clear();
+        self
+    }
+
+    /// The frame of the newest stored delta, if any.
+    pub fn newest_frame(&self) -> Option<i32> {
+        self.deltas
+            .front()
+            .map(|&(frame, _)| frame)
+            .or(self.base_frame)
+    }
+
+    /// Folds the oldest delta into the base state, retaining the result as a keyframe if required.
+    fn fold_oldest(&mut self) {
+        let Some((frame, delta)) = self.deltas.pop_back() else {
+            return;
+        };
+
+        for rollback in delta.removed {
+            self.base.remove(&rollback);
+        }
+
+        self.base.extend(
+            delta
+                .changed
+                .into_iter()
+                .map(|(rollback, stored)| (rollback, Arc::new(stored))),
+        );
+        self.base_frame = Some(frame);
+
+        let Some(interval) = self.keyframe_interval else {
+            return;
+        };
+
+        if frame.rem_euclid(interval as i32) == 0 {
+            self.keyframes.insert(frame, self.base.clone());
+        }
+    }
+
+    /// Push the changes made since the last pushed frame. `present` lists every [`Rollback`]
+    /// entity which currently has the component, and is used to detect removals.
+    ///
+    /// Pushing the same frame again merges the changes into the stored delta.
+    pub fn push(
+        &mut self,
+        frame: i32,
+        changed: impl IntoIterator<Item = (Rollback, As)>,
+        present: HashSet<Rollback>,
+    ) -> &mut Self {
+        let delta = GgrsDelta {
+            changed: changed.into_iter().collect(),
+            removed: self.present.difference(&present).copied().collect(),
+        };
+
+        self.present = present;
+        self.dirty.clear();
+        self.all_dirty = false;
+
+        match self.deltas.front_mut() {
+            Some((newest, existing)) if *newest == frame => existing.merge(delta),
+            _ => {
+                debug_assert!(
+                    self.newest_frame().map_or(true, |newest| newest < frame),
+                    "Deltas must be pushed in order, rolling back first if required"
+                );
+
+                self.deltas.push_front((frame, delta));
+            }
+        }
+
+        while self.deltas.len() > self.depth {
+            self.fold_oldest();
+        }
+
+        self
+    }
+
+    /// Confirms a frame as being stable across clients. Deltas from before this point are
+    /// folded into the base state as they are no longer required.
+    pub fn confirm(&mut self, confirmed_frame: i32) -> &mut Self {
+        while self
+            .deltas
+            .back()
+            .is_some_and(|&(frame, _)| frame < confirmed_frame)
+        {
+            self.fold_oldest();
+        }
+
+        self
+    }
+
+    /// Rolls back to the provided frame, discarding deltas saved after the rollback point.
+    ///
+    /// If the frame was retained as a keyframe, all stored deltas are folded (retaining any
+    /// keyframes among them) and the keyframe replaces the base state instead.
+    pub fn rollback(&mut self, frame: i32) -> &mut Self {
+        self.dirty.clear();
+        self.all_dirty = false;
+
+        let stored = self
+            .deltas
+            .iter()
+            .any(|&(saved_frame, _)| saved_frame == frame);
+
+        if !stored && self.keyframes.contains_key(&frame) {
+            while !self.deltas.is_empty() {
+                self.fold_oldest();
+            }
+
+            self.base = self.keyframes[&frame].clone();
+            self.base_frame = Some(frame);
+            self.present = self.base.keys().copied().collect();
+            self.all_dirty = true;
+
+            return self;
+        }
+
+        while let Some(&(newest, _)) = self.deltas.front() {
+            if newest == frame {
+                break;
+            }
+
+            let (_, delta) = self.deltas.pop_front().unwrap();
+            self.dirty.extend(delta.affected());
+        }
+
+        if self.newest_frame() != Some(frame) {
+            // TODO: A panic may not be appropriate here, but suitable for now.
+            panic!("Could not rollback to {frame}: no snapshot at that moment could be found.");
+        }
+
+        // Presence only differs for entities affected by the discarded deltas
+        let dirty = std::mem::take(&mut self.dirty);
+
+        for &rollback in &dirty {
+            if self.get(&rollback).is_some() {
+                self.present.insert(rollback);
+            } else {
+                self.present.remove(&rollback);
+            }
+        }
+
+        self.dirty = dirty;
+
+        self
+    }
+
+    /// Get the stored component for the provided [`Rollback`] on the current frame, if any.
+    /// Use `rollback(frame)` to first select a frame to rollback to.
+    pub fn get(&self, rollback: &Rollback) -> Option<&As> {
+        for (_, delta) in &self.deltas {
+            if let Some(stored) = delta.changed.get(rollback) {
+                return Some(stored);
+            }
+
+            if delta.removed.contains(rollback) {
+                return None;
+            }
+        }
+
+        self.base.get(rollback).map(Arc::as_ref)
+    }
+
+    /// Returns `true` if the provided [`Rollback`] has the component on the current frame.
+    pub fn contains(&self, rollback: &Rollback) -> bool {
+        self.present.contains(rollback)
+    }
+
+    /// Returns `true` if the provided [`Rollback`] was affected by the deltas discarded by the
+    /// last `rollback(frame)`, and so may differ from the world.
+    pub fn is_dirty(&self, rollback: &Rollback) -> bool {
+        self.all_dirty || self.dirty.contains(rollback)
+    }
+
+    /// The number of components stored across the base state and all deltas.
+    pub fn stored_len(&self) -> usize {
+        self.base.len()
+            + self
+                .deltas
+                .iter()
+                .map(|(_, delta)| delta.changed.len())
+                .sum::<usize>()
+    }
+
+    /// A system which automatically confirms the [`ConfirmedFrameCount`], folding older deltas.
+    /// Also applies the [`KeyframeInterval`], if present.
+    pub fn discard_old_snapshots(
+        mut snapshots: ResMut<Self>,
+        confirmed_frame: Option<Res<ConfirmedFrameCount>>,
+        keyframe_interval: Option<Res<KeyframeInterval>>,
+        depth: Option<Res<SnapshotDepth>>,
+    ) where
+        For: Send + Sync + 'static,
+        As: Send + Sync + 'static,
+    {
+        if let Some(depth) = depth {
+            if snapshots.depth() != depth.0 {
+                snapshots.set_depth(depth.0);
+            }
+        }
+
+        let interval = keyframe_interval.map(|interval| interval.0);
+        if snapshots.keyframe_interval() != interval {
+            snapshots.set_keyframe_interval(interval);
+        }
+
+        let Some(confirmed_frame) = confirmed_frame else {
+            return;
+        };
+
+        snapshots.confirm(confirmed_frame.0);
+    }
+}
+
+/// A [`Plugin`] which manages snapshots for a [`Component`] using a provided [`Strategy`], storing
+/// only the components which changed each frame in a [`GgrsDeltaSnapshots`].
+///
+/// This can be used instead of a [`ComponentSnapshotPlugin`](`crate::ComponentSnapshotPlugin`)
+/// for types which are numerous but rarely change.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, ComponentDeltaSnapshotPlugin, CloneStrategy};
+/// #
+/// #[derive(Component, Clone)]
+/// struct Building {
+///     health: u32,
+/// }
+///
+/// # let mut app = App::new();
+/// # app.add_plugins(GgrsPlugin::<GgrsConfig<u8>>::default());
+/// // Most buildings are idle on any given frame
+/// app.add_plugins(ComponentDeltaSnapshotPlugin::<CloneStrategy<Building>>::default());
+/// ```
+pub struct ComponentDeltaSnapshotPlugin<S>
+where
+    S: Strategy,
+    S::Target: Component,
+    S::Stored: Send + Sync + 'static,
+{
+    _phantom: PhantomData<S>,
+}
+
+impl<S> Default for ComponentDeltaSnapshotPlugin<S>
+where
+    S: Strategy,
+    S::Target: Component,
+    S::Stored: Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self {
+            _phantom: default(),
+        }
+    }
+}
+
+impl<S> ComponentDeltaSnapshotPlugin<S>
+where
+    S: Strategy,
+    S::Target: Component,
+    S::Stored: Send + Sync + 'static,
+{
+    pub fn save(
+        mut snapshots: ResMut<GgrsDeltaSnapshots<S::Target, S::Stored>>,
+        frame: Res<RollbackFrameCount>,
+        query: Query<(&Rollback, Ref<S::Target>)>,
+        ticks: SystemChangeTick,
+    ) {
+        let last_save = snapshots.last_save;
+
+        let present = query.iter().map(|(&rollback, _)| rollback).collect();
+
+        let changed = query
+            .iter()
+            .filter(|(_, component)| {
+                component
+                    .last_changed()
+                    .is_newer_than(last_save, ticks.this_run())
+            })
+            .map(|(&rollback, component)| (rollback, S::store(component.as_ref())))
+            .collect::<Vec<_>>();
+
+        trace!(
+            "Snapshot {} changed {} component(s)",
+            changed.len(),
+            bevy::utils::get_short_name(std::any::type_name::<S::Target>())
+        );
+
+        snapshots.push(frame.0, changed, present);
+        snapshots.last_save = ticks.this_run();
+    }
+
+    pub fn load(
+        mut commands: Commands,
+        mut snapshots: ResMut<GgrsDeltaSnapshots<S::Target, S::Stored>>,
+        frame: Res<RollbackFrameCount>,
+        mut query: Query<(Entity, &Rollback, Option<&mut S::Target>)>,
+        ticks: SystemChangeTick,
+    ) {
+        let snapshots = snapshots.rollback(frame.0);
+        let last_save = snapshots.last_save;
+        let mut restored = 0;
+
+        for (entity, rollback, component) in query.iter_mut() {
+            // Only components which may differ from the snapshot need to be restored
+            let outdated = snapshots.is_dirty(rollback)
+                || match &component {
+                    Some(component) => component
+                        .last_changed()
+                        .is_newer_than(last_save, ticks.this_run()),
+                    None => snapshots.contains(rollback),
+                };
+
+            if !outdated {
+                continue;
+            }
+
+            restored += 1;
+
+            match (component, snapshots.get(rollback)) {
+                (Some(mut component), Some(snapshot)) => S::update(component.as_mut(), snapshot),
+                (Some(_), None) => {
+                    commands.entity(entity).remove::<S::Target>();
+                }
+                (None, Some(snapshot)) => {
+                    commands.entity(entity).insert(S::load(snapshot));
+                }
+                (None, None) => {}
+            }
+        }
+
+        trace!(
+            "Rolled back {} {} component(s)",
+            restored,
+            bevy::utils::get_short_name(std::any::type_name::<S::Target>())
+        );
+    }
+}
+
+impl<S> Plugin for ComponentDeltaSnapshotPlugin<S>
+where
+    S: Send + Sync + 'static + Strategy,
+    S::Target: Component,
+    S::Stored: Send + Sync + 'static,
+{
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RollbackTypes>()
+            .world
+            .resource_mut::<RollbackTypes>()
+            .register_component::<S::Target>();
+
+        app.init_resource::<GgrsDeltaSnapshots<S::Target, S::Stored>>()
+            .add_systems(
+                SaveWorld,
+                (
+                    GgrsDeltaSnapshots::<S::Target, S::Stored>::discard_old_snapshots,
+                    Self::save,
+                )
+                    .chain()
+                    .in_set(SaveWorldSet::Snapshot),
+            )
+            .add_systems(LoadWorld, Self::load.in_set(LoadWorldSet::Data));
+    }
+}
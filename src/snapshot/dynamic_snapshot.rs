@@ -0,0 +1,316 @@
+use std::any::TypeId;
+
+use bevy::{
+    ecs::reflect::{AppTypeRegistry, ReflectMapEntities},
+    prelude::*,
+    reflect::TypeRegistry,
+    utils::HashSet,
+};
+
+use crate::{
+    apply_rollback_map_to_component, GgrsComponentSnapshot, GgrsComponentSnapshots, LoadWorld,
+    LoadWorldSet, Rollback, RollbackEntityMap, RollbackFrameCount, RollbackTypes, SaveWorld,
+    SaveWorldSet,
+};
+
+/// [`Resource`] used to store snapshots for the [`DynamicSnapshotPlugin`]. Each [`Rollback`]
+/// entity stores a reflected copy of every [`Component`] included by the [`DynamicSnapshotFilter`].
+pub type DynamicSnapshots =
+    GgrsComponentSnapshots<DynamicSnapshotPlugin, Vec<(TypeId, Box<dyn Reflect>)>>;
+
+/// A [`Resource`] selecting which reflected [`Components`](`Component`) are managed by the
+/// [`DynamicSnapshotPlugin`].
+///
+/// If any types are allowed, only those types are included. Denied types are never included.
+/// Types registered for rollback elsewhere (see [`RollbackTypes`]) are always left to their
+/// own plugins.
+///
+/// The filter is resolved against the [`AppTypeRegistry`] once, before the first snapshot is
+/// saved, so it must be configured before the session starts.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct DynamicSnapshotFilter {
+    allowed: HashSet<TypeId>,
+    denied: HashSet<TypeId>,
+}
+
+impl DynamicSnapshotFilter {
+    /// Includes the [`Component`] `C`. Once any type is allowed, all other types are excluded.
+    pub fn allow<C: Component>(&mut self) -> &mut Self {
+        self.allowed.insert(TypeId::of::<C>());
+        self
+    }
+
+    /// Excludes the [`Component`] `C`, such as for purely cosmetic components.
+    pub fn deny<C: Component>(&mut self) -> &mut Self {
+        self.denied.insert(TypeId::of::<C>());
+        self
+    }
+
+    /// Returns `true` if the provided [`Component`] type passes this filter.
+    pub fn includes(&self, type_id: TypeId) -> bool {
+        (self.allowed.is_empty() || self.allowed.contains(&type_id))
+            && !self.denied.contains(&type_id)
+    }
+}
+
+/// A [`Plugin`] which snapshots every reflected [`Component`] on [`Rollback`] entities, without
+/// requiring a [`ComponentSnapshotPlugin`](`crate::ComponentSnapshotPlugin`) per type.
+///
+/// Any [`Component`] registered with the [`AppTypeRegistry`] with [`ReflectComponent`] type
+/// data is saved, and restored on load, including being added or removed as required. Types
+/// with [`ReflectMapEntities`] type data are also updated after rollback. Which types are
+/// included can be controlled with a [`DynamicSnapshotFilter`]. Every included type is
+/// registered in [`RollbackTypes`], so it is treated as rolled back elsewhere, such as by a
+/// [`SerializedWorld`](`crate::SerializedWorld`).
+///
+/// This is slower than registering each type individually, but is convenient while prototyping.
+/// Every type is saved and loaded from a single exclusive system, so unlike individually
+/// registered types, they are not saved or loaded in parallel.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, DynamicSnapshotPlugin};
+/// #
+/// #[derive(Component, Reflect, Default)]
+/// #[reflect(Component)]
+/// struct Health(u32);
+///
+/// #[derive(Component, Reflect, Default)]
+/// #[reflect(Component)]
+/// struct Sparkles;
+///
+/// # let mut app = App::new();
+/// # app.add_plugins(GgrsPlugin::<GgrsConfig<u8>>::default());
+/// app.register_type::<Health>()
+///     .register_type::<Sparkles>()
+///     // Sparkles are purely cosmetic, so there is no need to roll them back
+///     .add_plugins(DynamicSnapshotPlugin::default().deny::<Sparkles>());
+/// ```
+#[derive(Default)]
+pub struct DynamicSnapshotPlugin {
+    filter: DynamicSnapshotFilter,
+}
+
+/// The reflected [`Component`] types managed by the [`DynamicSnapshotPlugin`], once the
+/// [`DynamicSnapshotFilter`] has been resolved.
+#[derive(Resource, Clone, Default)]
+struct DynamicSnapshotTypes(HashSet<TypeId>);
+
+impl DynamicSnapshotPlugin {
+    /// Includes the [`Component`] `C`. Once any type is allowed, all other types are excluded.
+    pub fn allow<C: Component>(mut self) -> Self {
+        self.filter.allow::<C>();
+        self
+    }
+
+    /// Excludes the [`Component`] `C`.
+    pub fn deny<C: Component>(mut self) -> Self {
+        self.filter.deny::<C>();
+        self
+    }
+
+    /// Resolves the [`DynamicSnapshotFilter`] against the [`AppTypeRegistry`], registering every
+    /// included type in [`RollbackTypes`]. Only the first call has any effect.
+    fn resolve(world: &mut World) -> DynamicSnapshotTypes {
+        if let Some(managed) = world.get_resource::<DynamicSnapshotTypes>() {
+            return managed.clone();
+        }
+
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+        let filter = world.resource::<DynamicSnapshotFilter>();
+        let types = world.resource::<RollbackTypes>();
+
+        let mut registrations = registry
+            .iter()
+            .filter(|registration| registration.data::<ReflectComponent>().is_some())
+            .filter(|registration| {
+                let type_id = registration.type_id();
+                filter.includes(type_id) && !types.contains_component(type_id)
+            })
+            .collect::<Vec<_>>();
+
+        // Sorted so that every peer registers types in the same order
+        registrations.sort_by_key(|registration| registration.type_info().type_path());
+
+        let mut types = world.resource_mut::<RollbackTypes>();
+
+        for registration in &registrations {
+            types.register_component_id(registration.type_id());
+        }
+
+        let managed = DynamicSnapshotTypes(
+            registrations
+                .iter()
+                .map(|registration| registration.type_id())
+                .collect(),
+        );
+
+        world.insert_resource(managed.clone());
+
+        managed
+    }
+
+    /// Exclusive system which saves all included [`Components`](`Component`) on [`Rollback`] entities.
+    pub fn save(world: &mut World) {
+        let managed = Self::resolve(world);
+        let frame = world.resource::<RollbackFrameCount>().0;
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+
+        let mut query = world.query::<(Entity, &Rollback)>();
+
+        let snapshot = {
+            let world = &*world;
+
+            let components = query.iter(world).map(|(entity, &rollback)| {
+                let entity_ref = world.entity(entity);
+
+                let components = included(world, entity, &registry, &managed)
+                    .filter_map(|(type_id, reflect_component)| {
+                        let component = reflect_component.reflect(entity_ref)?;
+                        Some((type_id, component.clone_value()))
+                    })
+                    .collect::<Vec<_>>();
+
+                (rollback, components)
+            });
+
+            GgrsComponentSnapshot::new(components)
+        };
+
+        trace!(
+            "Snapshot {} dynamic component(s)",
+            snapshot
+                .iter()
+                .map(|(_, components)| components.len())
+                .sum::<usize>()
+        );
+
+        world
+            .resource_mut::<DynamicSnapshots>()
+            .push(frame, snapshot);
+    }
+
+    /// Exclusive system which restores all included [`Components`](`Component`) on [`Rollback`] entities,
+    /// adding and removing them as required.
+    pub fn load(world: &mut World) {
+        let managed = Self::resolve(world);
+        let frame = world.resource::<RollbackFrameCount>().0;
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+
+        let entities = world
+            .query::<(Entity, &Rollback)>()
+            .iter(world)
+            .map(|(entity, &rollback)| (entity, rollback))
+            .collect::<Vec<_>>();
+
+        world.resource_scope(|world: &mut World, mut snapshots: Mut<DynamicSnapshots>| {
+            let snapshot = snapshots.rollback(frame).get();
+
+            for (entity, rollback) in entities {
+                let stored = snapshot
+                    .get(&rollback)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+
+                let removed = included(world, entity, &registry, &managed)
+                    .filter(|(type_id, _)| stored.iter().all(|(stored, _)| stored != type_id))
+                    .map(|(_, reflect_component)| reflect_component.clone())
+                    .collect::<Vec<_>>();
+
+                let mut entity = world.entity_mut(entity);
+
+                for reflect_component in removed {
+                    reflect_component.remove(&mut entity);
+                }
+
+                for (type_id, component) in stored {
+                    let Some(reflect_component) =
+                        registry.get_type_data::<ReflectComponent>(*type_id)
+                    else {
+                        continue;
+                    };
+
+                    reflect_component.apply_or_insert(&mut entity, component.as_ref());
+                }
+            }
+
+            trace!(
+                "Rolled back {} dynamic component(s)",
+                snapshot
+                    .iter()
+                    .map(|(_, components)| components.len())
+                    .sum::<usize>()
+            );
+        });
+    }
+
+    /// Exclusive system which applies the [`RollbackEntityMap`] to all included [`Components`](`Component`)
+    /// with [`ReflectMapEntities`] type data.
+    pub fn update(world: &mut World) {
+        let managed = Self::resolve(world);
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+
+        let mappable = registry
+            .iter()
+            .filter(|registration| managed.0.contains(&registration.type_id()))
+            .filter_map(|registration| {
+                let map_entities = registration.data::<ReflectMapEntities>()?;
+                Some((registration.type_info().type_path(), map_entities))
+            });
+
+        for (type_name, map_entities) in mappable {
+            world.resource_scope(|world: &mut World, map: Mut<RollbackEntityMap>| {
+                apply_rollback_map_to_component(world, map, type_name, |world, entity_map| {
+                    map_entities.map_all_entities(world, entity_map)
+                });
+            });
+        }
+    }
+}
+
+/// Iterates over the [`Components`](`Component`) on an entity which are managed by the
+/// [`DynamicSnapshotPlugin`].
+fn included<'a>(
+    world: &'a World,
+    entity: Entity,
+    registry: &'a TypeRegistry,
+    managed: &'a DynamicSnapshotTypes,
+) -> impl Iterator<Item = (TypeId, &'a ReflectComponent)> + 'a {
+    let archetype_id = world.entity(entity).location().archetype_id;
+
+    world.archetypes()[archetype_id]
+        .components()
+        .filter_map(move |component_id| world.components().get_info(component_id)?.type_id())
+        .filter(move |type_id| managed.0.contains(type_id))
+        .filter_map(move |type_id| {
+            let reflect_component = registry.get_type_data::<ReflectComponent>(type_id)?;
+            Some((type_id, reflect_component))
+        })
+}
+
+impl Plugin for DynamicSnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.filter.clone())
+            .init_resource::<RollbackTypes>()
+            .init_resource::<DynamicSnapshots>()
+            .add_systems(
+                SaveWorld,
+                (DynamicSnapshots::discard_old_snapshots, Self::save)
+                    .chain()
+                    .in_set(SaveWorldSet::Snapshot),
+            )
+            .add_systems(LoadWorld, Self::load.in_set(LoadWorldSet::Data))
+            .add_systems(LoadWorld, Self::update.in_set(LoadWorldSet::Mapping));
+    }
+
+    fn finish(&self, app: &mut App) {
+        // Resolve as soon as every type has been registered, rather than waiting for the first
+        // snapshot, so the types are already known to be rolled back
+        Self::resolve(&mut app.world);
+    }
+}
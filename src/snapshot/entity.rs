@@ -1,6 +1,6 @@
 use crate::{
-    GgrsComponentSnapshot, GgrsComponentSnapshots, LoadWorld, LoadWorldSet, Rollback,
-    RollbackEntityMap, RollbackFrameCount, SaveWorld, SaveWorldSet,
+    ConfirmedFrameCount, GgrsComponentSnapshot, GgrsComponentSnapshots, LoadWorld, LoadWorldSet,
+    Rollback, RollbackEntityMap, RollbackFrameCount, RollbackOrdered, SaveWorld, SaveWorldSet,
 };
 use bevy::{prelude::*, utils::HashMap};
 
@@ -8,6 +8,9 @@ use bevy::{prelude::*, utils::HashMap};
 /// all [`Entities`](`Entity`) match the state of the desired frame, or can be mapped using a
 /// [`RollbackEntityMap`], which this [`Plugin`] will also manage.
 ///
+/// [`RollbackOrdered`] entries for entities which were despawned before the
+/// [`ConfirmedFrameCount`] are also compacted, as they can never be rolled back to.
+///
 /// # Examples
 /// ```rust
 /// # use bevy::prelude::*;
@@ -84,12 +87,41 @@ impl EntitySnapshotPlugin {
 
         *map = RollbackEntityMap::new(entity_map);
     }
+
+    /// A [`System`] which removes [`RollbackOrdered`] entries for entities which were not alive
+    /// on the [`ConfirmedFrameCount`].
+    pub fn compact(
+        mut ordered: ResMut<RollbackOrdered>,
+        snapshots: Res<GgrsComponentSnapshots<Entity>>,
+        confirmed_frame: Option<Res<ConfirmedFrameCount>>,
+    ) {
+        let Some(confirmed_frame) = confirmed_frame else {
+            return;
+        };
+
+        let confirmed_frame = confirmed_frame.0;
+
+        // Entities alive on the confirmed frame, which is the earliest frame that can be loaded
+        let Some(snapshot) = snapshots.peek(confirmed_frame) else {
+            return;
+        };
+
+        let removed = ordered
+            .bypass_change_detection()
+            .compact(confirmed_frame, |rollback| snapshot.get(rollback).is_some());
+
+        if removed > 0 {
+            ordered.set_changed();
+            trace!("Compacted {removed} rollback(s) despawned before frame {confirmed_frame}");
+        }
+    }
 }
 
 impl Plugin for EntitySnapshotPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<GgrsComponentSnapshots<Entity>>()
             .init_resource::<RollbackEntityMap>()
+            .init_resource::<RollbackOrdered>()
             .add_systems(
                 SaveWorld,
                 (
@@ -99,6 +131,13 @@ impl Plugin for EntitySnapshotPlugin {
                     .chain()
                     .in_set(SaveWorldSet::Snapshot),
             )
+            .add_systems(
+                SaveWorld,
+                // RollbackOrdered is itself snapshotted, so compact it before any snapshots are taken
+                Self::compact
+                    .after(SaveWorldSet::Checksum)
+                    .before(SaveWorldSet::Snapshot),
+            )
             .add_systems(LoadWorld, Self::load.in_set(LoadWorldSet::Entity));
     }
 }
@@ -2,7 +2,9 @@ use std::hash::{BuildHasher, Hash, Hasher};
 
 use bevy::prelude::*;
 
-use crate::{ChecksumFlag, ChecksumPart, Rollback, RollbackOrdered, SaveWorld, SaveWorldSet};
+use crate::{
+    ChecksumFlag, ChecksumPart, ChecksumPartName, ChecksumWorld, Rollback, RollbackOrdered,
+};
 
 pub struct EntityChecksumPlugin;
 
@@ -12,7 +14,7 @@ impl EntityChecksumPlugin {
         mut commands: Commands,
         rollback_ordered: Res<RollbackOrdered>,
         active_entities: Query<&Rollback, (With<Rollback>, Without<ChecksumFlag<Entity>>)>,
-        mut checksum: Query<&mut ChecksumPart, (Without<Rollback>, With<ChecksumFlag<Entity>>)>,
+        checksum: Query<Entity, (Without<Rollback>, With<ChecksumFlag<Entity>>)>,
     ) {
         let mut hasher = bevy::utils::FixedState.build_hasher();
 
@@ -20,22 +22,26 @@ impl EntityChecksumPlugin {
         active_entities.iter().len().hash(&mut hasher);
 
         // The quantity of total spawned rollback entities must be synced.
-        rollback_ordered.len().hash(&mut hasher);
+        rollback_ordered.total().hash(&mut hasher);
 
         let result = ChecksumPart(hasher.finish() as u128);
 
         trace!("Rollback Entities have checksum {:X}", result.0);
 
-        if let Ok(mut checksum) = checksum.get_single_mut() {
-            *checksum = result;
+        if let Ok(checksum) = checksum.get_single() {
+            commands.entity(checksum).insert(result);
         } else {
-            commands.spawn((result, ChecksumFlag::<Entity>::default()));
+            commands.spawn((
+                result,
+                ChecksumFlag::<Entity>::default(),
+                ChecksumPartName::of::<Entity>(),
+            ));
         }
     }
 }
 
 impl Plugin for EntityChecksumPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(SaveWorld, Self::update.in_set(SaveWorldSet::Checksum));
+        app.add_systems(ChecksumWorld, Self::update);
     }
 }
@@ -1,30 +1,45 @@
-use crate::{ConfirmedFrameCount, Rollback, DEFAULT_FPS};
+use crate::{ConfirmedFrameCount, Rollback, DEFAULT_MAX_PREDICTION};
 use bevy::{prelude::*, utils::HashMap};
-use std::{collections::VecDeque, marker::PhantomData};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    marker::PhantomData,
+};
 
+mod changed_component_snapshot;
 mod checksum;
 mod component_checksum;
 mod component_map;
 mod component_snapshot;
+mod delta;
+mod dynamic_snapshot;
 mod entity;
 mod entity_checksum;
+mod reflect_rollback;
 mod resource_checksum;
 mod resource_map;
 mod resource_snapshot;
 mod rollback_entity_map;
+mod rollback_types;
+mod serialized_world;
 mod set;
 mod strategy;
 
+pub use changed_component_snapshot::*;
 pub use checksum::*;
 pub use component_checksum::*;
 pub use component_map::*;
 pub use component_snapshot::*;
+pub use delta::*;
+pub use dynamic_snapshot::*;
 pub use entity::*;
 pub use entity_checksum::*;
+pub use reflect_rollback::*;
 pub use resource_checksum::*;
 pub use resource_map::*;
 pub use resource_snapshot::*;
 pub use rollback_entity_map::*;
+pub use rollback_types::*;
+pub use serialized_world::*;
 pub use set::*;
 pub use strategy::*;
 
@@ -51,25 +66,42 @@ pub struct GgrsSnapshots<For, As = For> {
     frames: VecDeque<i32>,
     /// Maximum amount of snapshots to store at any one time
     depth: usize,
+    /// Snapshots retained after being discarded, see [`KeyframeInterval`].
+    keyframes: BTreeMap<i32, As>,
+    /// Interval between frames to retain as keyframes, if any.
+    keyframe_interval: Option<usize>,
+    /// Keyframe selected by the last `rollback(frame)`, if it was not in the queue.
+    selected_keyframe: Option<i32>,
     _phantom: PhantomData<For>,
 }
 
 impl<For, As> Default for GgrsSnapshots<For, As> {
     fn default() -> Self {
+        // Replaced by the SnapshotDepth of the Session once it starts
+        let depth = SnapshotDepth::default().0;
+
         Self {
-            snapshots: VecDeque::with_capacity(DEFAULT_FPS),
-            frames: VecDeque::with_capacity(DEFAULT_FPS),
-            depth: DEFAULT_FPS, // TODO: Make sensible choice here
+            snapshots: VecDeque::with_capacity(depth),
+            frames: VecDeque::with_capacity(depth),
+            depth,
+            keyframes: BTreeMap::new(),
+            keyframe_interval: None,
+            selected_keyframe: None,
             _phantom: default(),
         }
     }
 }
 
 impl<For, As> GgrsSnapshots<For, As> {
-    /// Updates the capacity of this storage to the provided depth.
+    /// Updates the capacity of this storage to the provided depth, discarding the oldest
+    /// snapshots if more than `depth` are currently stored.
     pub fn set_depth(&mut self, depth: usize) -> &mut Self {
         self.depth = depth;
 
+        while self.snapshots.len() > self.depth {
+            self.pop_oldest();
+        }
+
         // Greedy allocation to avoid allocating at a more sensitive time.
         if self.snapshots.capacity() < self.depth {
             let additional = self.depth - self.snapshots.capacity();
@@ -89,6 +121,44 @@ impl<For, As> GgrsSnapshots<For, As> {
         self.depth
     }
 
+    /// Sets the interval between frames which are retained as keyframes once discarded.
+    /// If `None`, no keyframes are retained.
+    pub fn set_keyframe_interval(&mut self, interval: Option<usize>) -> &mut Self {
+        self.keyframe_interval = interval.filter(|&interval| interval > 0);
+        self
+    }
+
+    /// Get the interval between frames which are retained as keyframes, if any.
+    pub const fn keyframe_interval(&self) -> Option<usize> {
+        self.keyframe_interval
+    }
+
+    /// Iterate over the frames of all retained keyframes, oldest first.
+    pub fn keyframes(&self) -> impl Iterator<Item = i32> + '_ {
+        self.keyframes.keys().copied()
+    }
+
+    /// Discards all retained keyframes.
+    pub fn clear_keyframes(&mut self) -> &mut Self {
+        self.keyframes.clear();
+        self.selected_keyframe = None;
+        self
+    }
+
+    /// Removes the oldest snapshot, retaining it as a keyframe if required.
+    fn pop_oldest(&mut self) {
+        let snapshot = self.snapshots.pop_back().unwrap();
+        let frame = self.frames.pop_back().unwrap();
+
+        let Some(interval) = self.keyframe_interval else {
+            return;
+        };
+
+        if frame.rem_euclid(interval as i32) == 0 {
+            self.keyframes.insert(frame, snapshot);
+        }
+    }
+
     /// Push a new snapshot for the provided frame. If the frame is earlier than any
     /// currently stored snapshots, those snapshots will be discarded.
     pub fn push(&mut self, frame: i32, snapshot: As) -> &mut Self {
@@ -118,10 +188,10 @@ impl<For, As> GgrsSnapshots<For, As> {
 
         self.snapshots.push_front(snapshot);
         self.frames.push_front(frame);
+        self.selected_keyframe = None;
 
         while self.snapshots.len() > self.depth {
-            self.snapshots.pop_back().unwrap();
-            self.frames.pop_back().unwrap();
+            self.pop_oldest();
         }
 
         self
@@ -138,8 +208,7 @@ impl<For, As> GgrsSnapshots<For, As> {
 
         while let Some(&frame) = self.frames.back() {
             if frame < confirmed_frame {
-                self.snapshots.pop_back().unwrap();
-                self.frames.pop_back().unwrap();
+                self.pop_oldest();
             } else {
                 break;
             }
@@ -149,7 +218,21 @@ impl<For, As> GgrsSnapshots<For, As> {
     }
 
     /// Rolls back to the provided frame, discarding snapshots taken after the rollback point.
+    ///
+    /// If the frame is no longer stored, but was retained as a keyframe, all stored snapshots
+    /// are discarded (retaining any keyframes) and the keyframe is selected instead.
     pub fn rollback(&mut self, frame: i32) -> &mut Self {
+        self.selected_keyframe = None;
+
+        if !self.frames.contains(&frame) && self.keyframes.contains_key(&frame) {
+            while !self.frames.is_empty() {
+                self.pop_oldest();
+            }
+
+            self.selected_keyframe = Some(frame);
+            return self;
+        }
+
         loop {
             let Some(&current) = self.frames.front() else {
                 // TODO: A panic may not be appropriate here, but suitable for now.
@@ -169,27 +252,56 @@ impl<For, As> GgrsSnapshots<For, As> {
 
     /// Get the current snapshot. Use `rollback(frame)` to first select a frame to rollback to.
     pub fn get(&self) -> &As {
-        self.snapshots.front().unwrap()
+        match self.selected_keyframe {
+            Some(frame) => &self.keyframes[&frame],
+            None => self.snapshots.front().unwrap(),
+        }
+    }
+
+    /// Get the most recently pushed snapshot, or the snapshot selected by the last
+    /// `rollback(frame)`, if any.
+    pub fn newest(&self) -> Option<&As> {
+        match self.selected_keyframe {
+            Some(frame) => self.keyframes.get(&frame),
+            None => self.snapshots.front(),
+        }
     }
 
     /// Get a particular snapshot if it exists.
     pub fn peek(&self, frame: i32) -> Option<&As> {
-        let (index, _) = self
+        let Some((index, _)) = self
             .frames
             .iter()
             .enumerate()
-            .find(|(_, &saved_frame)| saved_frame == frame)?;
+            .find(|(_, &saved_frame)| saved_frame == frame)
+        else {
+            return self.keyframes.get(&frame);
+        };
         self.snapshots.get(index)
     }
 
     /// A system which automatically confirms the [`ConfirmedFrameCount`], discarding older snapshots.
+    /// Also applies the [`KeyframeInterval`], if present.
     pub fn discard_old_snapshots(
         mut snapshots: ResMut<Self>,
         confirmed_frame: Option<Res<ConfirmedFrameCount>>,
+        keyframe_interval: Option<Res<KeyframeInterval>>,
+        depth: Option<Res<SnapshotDepth>>,
     ) where
         For: Send + Sync + 'static,
         As: Send + Sync + 'static,
     {
+        if let Some(depth) = depth {
+            if snapshots.depth() != depth.0 {
+                snapshots.set_depth(depth.0);
+            }
+        }
+
+        let interval = keyframe_interval.map(|interval| interval.0);
+        if snapshots.keyframe_interval() != interval {
+            snapshots.set_keyframe_interval(interval);
+        }
+
         let Some(confirmed_frame) = confirmed_frame else {
             return;
         };
@@ -198,6 +310,56 @@ impl<For, As> GgrsSnapshots<For, As> {
     }
 }
 
+/// [`Resource`] which, when present, causes every [`GgrsSnapshots`] and [`GgrsDeltaSnapshots`] to
+/// retain snapshots of every `N`th frame as keyframes after they would otherwise be discarded.
+/// Keyframes can still be [rolled back](`GgrsSnapshots::rollback`) to, allowing seeking within a
+/// [`ReplaySession`](`crate::ReplaySession`).
+///
+/// Note that keyframes are never discarded automatically, so this should only be used when
+/// the length of a [`Session`](`crate::Session`) is bounded.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyframeInterval(pub usize);
+
+/// [`Resource`] which, when present, increases the [`SnapshotDepth`] beyond what the
+/// [`MaxPredictionWindow`](`crate::MaxPredictionWindow`) requires by the provided number of
+/// frames. This leaves headroom for tooling which saves additional snapshots, such as while debugging.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SnapshotDepthMargin(pub usize);
+
+/// The number of snapshots every snapshot storage, such as [`GgrsSnapshots`], retains and
+/// preallocates space for. Updated whenever the [`MaxPredictionWindow`](`crate::MaxPredictionWindow`)
+/// of the current [`Session`](`crate::Session`) is.
+///
+/// This covers the confirmed frame, every frame which may be predicted beyond it, and the frame
+/// currently being saved, plus any [`SnapshotDepthMargin`].
+///
+/// Spectator and replay sessions never predict, so only retain 2 snapshots. Seeking within a
+/// [`ReplaySession`](`crate::ReplaySession`) relies on keyframes instead, which are retained
+/// outside of this depth (see [`KeyframeInterval`]). Tooling which loads any other older frame
+/// must add a [`SnapshotDepthMargin`].
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SnapshotDepth(usize);
+
+impl SnapshotDepth {
+    /// Calculates the [`SnapshotDepth`] required for the provided maximum prediction window and
+    /// [`SnapshotDepthMargin`].
+    pub const fn new(max_prediction: usize, margin: usize) -> Self {
+        Self(max_prediction + 2 + margin)
+    }
+}
+
+impl Default for SnapshotDepth {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_PREDICTION, 0)
+    }
+}
+
+impl From<SnapshotDepth> for usize {
+    fn from(value: SnapshotDepth) -> usize {
+        value.0
+    }
+}
+
 /// A storage type suitable for per-[`Entity`] snapshots, such as [`Component`] types.
 pub struct GgrsComponentSnapshot<For, As = For> {
     snapshot: HashMap<Rollback, As>,
@@ -0,0 +1,162 @@
+use std::{any::TypeId, hash::Hash};
+
+use bevy::{
+    ecs::reflect::ReflectMapEntities,
+    prelude::*,
+    reflect::{FromType, TypeRegistration},
+};
+
+use crate::{
+    apply_rollback_map_to_component, ComponentChecksumPlugin, ComponentSnapshotPlugin,
+    FromReflectStrategy, LoadWorld, LoadWorldSet, RollbackEntityMap, RollbackTypes,
+};
+
+/// Type data which marks a [`Component`] as rolled back, for use with a [`ReflectRollbackPlugin`].
+///
+/// Added to a type's [`TypeRegistration`] with `#[reflect(Rollback)]`.
+#[derive(Clone)]
+pub struct ReflectRollback {
+    register: fn(&mut App),
+}
+
+impl ReflectRollback {
+    /// Registers the reflected [`Component`] for rollback using a [`FromReflectStrategy`]. Types
+    /// which have already been registered for rollback are skipped.
+    pub fn register(&self, app: &mut App) {
+        (self.register)(app);
+    }
+}
+
+impl<C: Component + FromReflect> FromType<C> for ReflectRollback {
+    fn from_type() -> Self {
+        Self {
+            register: |app| {
+                let registered = app
+                    .world
+                    .get_resource::<RollbackTypes>()
+                    .is_some_and(|types| types.contains_component(TypeId::of::<C>()));
+
+                if registered {
+                    return;
+                }
+
+                app.add_plugins(ComponentSnapshotPlugin::<FromReflectStrategy<C>>::default());
+            },
+        }
+    }
+}
+
+/// Type data which includes a [`Component`] in the [`Checksum`](`crate::Checksum`) using its
+/// [`Hash`] implementation, for use with a [`ReflectRollbackPlugin`].
+///
+/// Added to a type's [`TypeRegistration`] with `#[reflect(Checksum)]`.
+#[derive(Clone)]
+pub struct ReflectChecksum {
+    register: fn(&mut App),
+}
+
+impl ReflectChecksum {
+    /// Registers the reflected [`Component`] for checksums using a [`ComponentChecksumPlugin`].
+    /// Types which have already been registered for checksums are skipped.
+    pub fn register(&self, app: &mut App) {
+        (self.register)(app);
+    }
+}
+
+impl<C: Component + Hash> FromType<C> for ReflectChecksum {
+    fn from_type() -> Self {
+        Self {
+            register: |app| {
+                if !app.is_plugin_added::<ComponentChecksumPlugin<C>>() {
+                    app.add_plugins(ComponentChecksumPlugin::<C>::default());
+                }
+            },
+        }
+    }
+}
+
+/// A [`Plugin`] which walks the [`AppTypeRegistry`] and registers every type with
+/// [`ReflectRollback`] type data for rollback. Types which also have [`ReflectChecksum`] type data
+/// are included in the [`Checksum`](`crate::Checksum`), and types which also have
+/// [`ReflectMapEntities`] type data are updated after rollback using it.
+///
+/// This allows rollback registration to be driven by the same reflection data used for scenes.
+/// Types must be registered with the [`AppTypeRegistry`] before this [`Plugin`] is added.
+///
+/// NOTE: Types should not also be registered for [`MapEntities`](`bevy::ecs::entity::MapEntities`)
+/// through [`GgrsApp`](`crate::GgrsApp`), as they would be mapped twice.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::{prelude::*, ecs::{entity::{EntityMapper, MapEntities}, reflect::ReflectMapEntities}};
+/// # use bevy_ggrs::{prelude::*, ReflectChecksum, ReflectRollback, ReflectRollbackPlugin};
+/// #
+/// #[derive(Component, Reflect, Default, Hash)]
+/// #[reflect(Component, Rollback, Checksum)]
+/// struct Health(u32);
+///
+/// // Rollback only requires FromReflect, so types without a sensible default can be registered
+/// #[derive(Component, Reflect)]
+/// #[reflect(Rollback, MapEntities)]
+/// struct BestFriend(Entity);
+///
+/// impl MapEntities for BestFriend {
+///     fn map_entities(&mut self, entity_mapper: &mut EntityMapper) {
+///         self.0 = entity_mapper.get_or_reserve(self.0);
+///     }
+/// }
+///
+/// # let mut app = App::new();
+/// # app.add_plugins(GgrsPlugin::<GgrsConfig<u8>>::default());
+/// app.register_type::<Health>()
+///     .register_type::<BestFriend>()
+///     .add_plugins(ReflectRollbackPlugin);
+/// ```
+pub struct ReflectRollbackPlugin;
+
+impl ReflectRollbackPlugin {
+    fn register(app: &mut App, registration: &TypeRegistration) {
+        let Some(rollback) = registration.data::<ReflectRollback>() else {
+            return;
+        };
+
+        rollback.register(app);
+
+        if let Some(checksum) = registration.data::<ReflectChecksum>() {
+            checksum.register(app);
+        }
+
+        let Some(map_entities) = registration.data::<ReflectMapEntities>().cloned() else {
+            return;
+        };
+
+        let type_name = registration.type_info().type_path();
+
+        app.add_systems(
+            LoadWorld,
+            (move |world: &mut World| {
+                world.resource_scope(|world: &mut World, map: Mut<RollbackEntityMap>| {
+                    apply_rollback_map_to_component(world, map, type_name, |world, entity_map| {
+                        map_entities.map_all_entities(world, entity_map)
+                    });
+                });
+            })
+            .in_set(LoadWorldSet::Mapping),
+        );
+    }
+}
+
+impl Plugin for ReflectRollbackPlugin {
+    fn build(&self, app: &mut App) {
+        let registry = app.world.resource::<AppTypeRegistry>().clone();
+        let registry = registry.read();
+
+        // Sorted so that every peer registers types in the same order
+        let mut registrations = registry.iter().collect::<Vec<_>>();
+        registrations.sort_by_key(|registration| registration.type_info().type_path());
+
+        for registration in registrations {
+            Self::register(app, registration);
+        }
+    }
+}
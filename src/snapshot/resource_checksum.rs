@@ -2,7 +2,7 @@ use std::hash::{BuildHasher, Hash, Hasher};
 
 use bevy::prelude::*;
 
-use crate::{ChecksumFlag, ChecksumPart, Rollback, SaveWorld, SaveWorldSet};
+use crate::{ChecksumFlag, ChecksumPart, ChecksumPartName, ChecksumWorld, Rollback};
 
 /// Plugin which will track the [`Resource`] `R` and ensure a [`ChecksumPart`] is
 /// available and updated. This can be used to generate a [`Checksum`](`crate::Checksum`).
@@ -54,26 +54,28 @@ where
     fn build(&self, app: &mut App) {
         let custom_hasher = self.0;
 
-        let update = move |mut commands: Commands,
-                           resource: Res<R>,
-                           mut checksum: Query<
-            &mut ChecksumPart,
-            (Without<Rollback>, With<ChecksumFlag<R>>),
-        >| {
-            let result = ChecksumPart(custom_hasher(resource.as_ref()) as u128);
+        let update =
+            move |mut commands: Commands,
+                  resource: Res<R>,
+                  checksum: Query<Entity, (Without<Rollback>, With<ChecksumFlag<R>>)>| {
+                let result = ChecksumPart(custom_hasher(resource.as_ref()) as u128);
 
-            trace!(
-                "Resource {} has checksum {:X}",
-                bevy::utils::get_short_name(std::any::type_name::<R>()),
-                result.0
-            );
+                trace!(
+                    "Resource {} has checksum {:X}",
+                    bevy::utils::get_short_name(std::any::type_name::<R>()),
+                    result.0
+                );
 
-            if let Ok(mut checksum) = checksum.get_single_mut() {
-                *checksum = result;
-            } else {
-                commands.spawn((result, ChecksumFlag::<R>::default()));
-            }
-        };
-        app.add_systems(SaveWorld, update.in_set(SaveWorldSet::Checksum));
+                if let Ok(checksum) = checksum.get_single() {
+                    commands.entity(checksum).insert(result);
+                } else {
+                    commands.spawn((
+                        result,
+                        ChecksumFlag::<R>::default(),
+                        ChecksumPartName::of::<R>(),
+                    ));
+                }
+            };
+        app.add_systems(ChecksumWorld, update);
     }
 }
@@ -3,6 +3,8 @@ use std::marker::PhantomData;
 use bevy::{
     ecs::entity::{EntityMapper, MapEntities},
     prelude::*,
+    reflect::FromType,
+    utils::HashMap,
 };
 
 use crate::{LoadWorld, LoadWorldSet, RollbackEntityMap};
@@ -115,6 +117,30 @@ fn apply_map<R: Resource + MapEntities>(world: &mut World, entity_mapper: &mut E
     }
 }
 
+/// Type data for a [`Resource`] implementing [`MapEntities`], allowing its entities to be mapped
+/// through reflection, such as when applying a [`SerializedWorld`](`crate::SerializedWorld`).
+///
+/// Register it with `#[reflect(Resource, MapEntitiesResource)]`.
+#[derive(Clone)]
+pub struct ReflectMapEntitiesResource {
+    map_entities: fn(&mut World, &mut EntityMapper),
+}
+
+impl ReflectMapEntitiesResource {
+    /// Maps the entities within the [`Resource`] using `entity_map`, if it is present.
+    pub fn map_entities(&self, world: &mut World, entity_map: &mut HashMap<Entity, Entity>) {
+        EntityMapper::world_scope(entity_map, world, self.map_entities);
+    }
+}
+
+impl<R: Resource + MapEntities> FromType<R> for ReflectMapEntitiesResource {
+    fn from_type() -> Self {
+        Self {
+            map_entities: apply_map::<R>,
+        }
+    }
+}
+
 impl<R> Plugin for ResourceMapEntitiesPlugin<R>
 where
     R: Resource + MapEntities,
@@ -1,6 +1,6 @@
 use crate::{
-    GgrsResourceSnapshots, LoadWorld, LoadWorldSet, RollbackFrameCount, SaveWorld, SaveWorldSet,
-    Strategy,
+    GgrsResourceSnapshots, LoadWorld, LoadWorldSet, RollbackFrameCount, RollbackTypes, SaveWorld,
+    SaveWorldSet, Strategy,
 };
 use bevy::prelude::*;
 use std::marker::PhantomData;
@@ -97,6 +97,11 @@ where
     S::Stored: Send + Sync + 'static,
 {
     fn build(&self, app: &mut App) {
+        app.init_resource::<RollbackTypes>()
+            .world
+            .resource_mut::<RollbackTypes>()
+            .register_resource::<S::Target>();
+
         app.init_resource::<GgrsResourceSnapshots<S::Target, S::Stored>>()
             .add_systems(
                 SaveWorld,
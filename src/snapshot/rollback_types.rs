@@ -0,0 +1,63 @@
+use std::any::TypeId;
+
+use bevy::prelude::*;
+
+/// A [`Resource`] listing every [`Component`] and [`Resource`] type which has been registered
+/// for rollback through a [`ComponentSnapshotPlugin`](`crate::ComponentSnapshotPlugin`),
+/// [`ResourceSnapshotPlugin`](`crate::ResourceSnapshotPlugin`) or
+/// [`DynamicSnapshotPlugin`](`crate::DynamicSnapshotPlugin`).
+///
+/// Types are listed in the order they were registered.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct RollbackTypes {
+    components: Vec<TypeId>,
+    resources: Vec<TypeId>,
+}
+
+impl RollbackTypes {
+    /// Registers a [`Component`] type as being rolled back.
+    pub fn register_component<T: Component>(&mut self) -> &mut Self {
+        self.register_component_id(TypeId::of::<T>())
+    }
+
+    /// Registers a [`Component`] type as being rolled back by its [`TypeId`], for types which are
+    /// only known through reflection.
+    pub(crate) fn register_component_id(&mut self, type_id: TypeId) -> &mut Self {
+        if !self.components.contains(&type_id) {
+            self.components.push(type_id);
+        }
+
+        self
+    }
+
+    /// Registers a [`Resource`] type as being rolled back.
+    pub fn register_resource<T: Resource>(&mut self) -> &mut Self {
+        let type_id = TypeId::of::<T>();
+
+        if !self.resources.contains(&type_id) {
+            self.resources.push(type_id);
+        }
+
+        self
+    }
+
+    /// Iterate over all [`Component`] types registered for rollback.
+    pub fn components(&self) -> impl Iterator<Item = TypeId> + '_ {
+        self.components.iter().copied()
+    }
+
+    /// Iterate over all [`Resource`] types registered for rollback.
+    pub fn resources(&self) -> impl Iterator<Item = TypeId> + '_ {
+        self.resources.iter().copied()
+    }
+
+    /// Returns `true` if the provided [`Component`] type is rolled back.
+    pub fn contains_component(&self, type_id: TypeId) -> bool {
+        self.components.contains(&type_id)
+    }
+
+    /// Returns `true` if the provided [`Resource`] type is rolled back.
+    pub fn contains_resource(&self, type_id: TypeId) -> bool {
+        self.resources.contains(&type_id)
+    }
+}
@@ -0,0 +1,442 @@
+use std::{any::TypeId, fmt::Display};
+
+use bevy::{
+    ecs::reflect::{AppTypeRegistry, ReflectMapEntities},
+    prelude::*,
+    reflect::{
+        serde::{TypedReflectDeserializer, TypedReflectSerializer},
+        TypeData, TypeRegistration, TypeRegistry,
+    },
+    utils::{Duration, HashMap},
+};
+use bincode::Options;
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+
+use crate::{
+    GgrsTime, ReflectMapEntitiesResource, Rollback, RollbackFrameCount, RollbackFrameOffset,
+    RollbackOrdered, RollbackTypes,
+};
+
+/// The current version of the [`SerializedWorld`] format.
+const SERIALIZED_WORLD_VERSION: u8 = 3;
+
+/// Errors which can occur when creating or applying a [`SerializedWorld`].
+#[derive(Debug)]
+pub enum WorldSerializationError {
+    /// A [`Resource`] required for serialization was not present in the [`World`].
+    MissingResource(&'static str),
+    /// A rolled back type is present in the [`World`], but was not registered with the
+    /// [`AppTypeRegistry`].
+    UnregisteredType(String),
+    /// A type was registered with the [`AppTypeRegistry`], but is missing the type data
+    /// required for serialization, such as [`ReflectComponent`] or [`ReflectResource`].
+    MissingTypeData {
+        /// The [type path](`bevy::reflect::TypePath`) of the type.
+        type_path: String,
+        /// The name of the missing type data.
+        type_data: &'static str,
+    },
+    /// A serialized type could not be found in the [`AppTypeRegistry`].
+    UnknownType(String),
+    /// The serialized data was created by an incompatible version of this format.
+    UnsupportedVersion(u8),
+    /// The data could not be encoded or decoded.
+    Encoding(bincode::Error),
+}
+
+impl Display for WorldSerializationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingResource(name) => write!(f, "missing resource {name}"),
+            Self::UnregisteredType(name) => write!(
+                f,
+                "{name} is rolled back but not registered with the AppTypeRegistry"
+            ),
+            Self::MissingTypeData {
+                type_path,
+                type_data,
+            } => write!(f, "{type_path} is not registered with {type_data}"),
+            Self::UnknownType(type_path) => write!(f, "unknown type {type_path}"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported serialized world version {version}")
+            }
+            Self::Encoding(error) => write!(f, "encoding error: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for WorldSerializationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Encoding(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<bincode::Error> for WorldSerializationError {
+    fn from(error: bincode::Error) -> Self {
+        Self::Encoding(error)
+    }
+}
+
+/// A single reflected value, stored alongside its [type path](`bevy::reflect::TypePath`).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct SerializedValue {
+    type_path: String,
+    data: Vec<u8>,
+}
+
+/// A [`Rollback`] entity, in the order provided by [`RollbackOrdered`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+enum SerializedEntity {
+    /// The entity existed at the time of serialization.
+    Alive {
+        rollback: Rollback,
+        order: usize,
+        /// The [bits](`Entity::to_bits`) of the original [`Entity`], used for mapping.
+        entity: u64,
+        components: Vec<SerializedValue>,
+    },
+    /// The entity was registered for rollback, but has since been despawned.
+    Dead { rollback: Rollback, order: usize },
+}
+
+/// The complete rollback state of a [`World`] for a single frame, which can be encoded as
+/// bytes and applied to another [`World`].
+///
+/// This includes all [`Rollback`] entities, every [`Component`] and [`Resource`] registered for
+/// rollback (see [`RollbackTypes`]), [`RollbackOrdered`], [`RollbackFrameCount`], and
+/// [`Time<GgrsTime>`]. Every rolled back type present in the [`World`] must be registered with
+/// the [`AppTypeRegistry`], including [`ReflectComponent`] or [`ReflectResource`] type data.
+/// Components with [`ReflectMapEntities`] type data, and resources with
+/// [`ReflectMapEntitiesResource`] type data, will be mapped to the new entities when applied.
+///
+/// # Examples
+/// ```rust
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, SerializedWorld, WorldSerializationError};
+/// #
+/// fn save_game(world: &World) -> Result<Vec<u8>, WorldSerializationError> {
+///     SerializedWorld::from_world(world)?.to_bytes()
+/// }
+///
+/// fn load_game(world: &mut World, bytes: &[u8]) -> Result<(), WorldSerializationError> {
+///     SerializedWorld::from_bytes(bytes)?.apply(world)
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SerializedWorld {
+    version: u8,
+    frame: i32,
+    elapsed_nanos: u64,
+    /// The [total](`RollbackOrdered::total`) number of [`Rollback`] entities ever registered.
+    total: usize,
+    entities: Vec<SerializedEntity>,
+    resources: Vec<SerializedValue>,
+}
+
+impl SerializedWorld {
+    /// Captures the current rollback state of the provided [`World`].
+    pub fn from_world(world: &World) -> Result<Self, WorldSerializationError> {
+        let registry = get_resource::<AppTypeRegistry>(world)?.read();
+        let types = get_resource::<RollbackTypes>(world)?;
+        let ordered = get_resource::<RollbackOrdered>(world)?;
+
+        let frame = world
+            .get_resource::<RollbackFrameCount>()
+            .map(|&frame| frame.into())
+            .unwrap_or_default();
+
+        let elapsed_nanos = world
+            .get_resource::<Time<GgrsTime>>()
+            .map(|time| time.elapsed().as_nanos() as u64)
+            .unwrap_or_default();
+
+        let alive = world
+            .iter_entities()
+            .filter_map(|entity| Some((*entity.get::<Rollback>()?, entity)))
+            .collect::<HashMap<_, _>>();
+
+        let mut entities = Vec::with_capacity(ordered.len());
+
+        for rollback in ordered.iter_sorted() {
+            let order = ordered.order(rollback);
+
+            let Some(&entity) = alive.get(&rollback) else {
+                entities.push(SerializedEntity::Dead { rollback, order });
+                continue;
+            };
+
+            let mut components = Vec::new();
+
+            for type_id in types.components() {
+                if !entity.contains_type_id(type_id) {
+                    continue;
+                }
+
+                let registration = get_registration(&registry, type_id, || {
+                    world
+                        .components()
+                        .get_id(type_id)
+                        .and_then(|id| world.components().get_info(id))
+                        .map(|info| info.name().to_owned())
+                })?;
+
+                let reflect_component = get_type_data::<ReflectComponent>(registration)?;
+
+                let Some(component) = reflect_component.reflect(entity) else {
+                    continue;
+                };
+
+                components.push(serialize_value(registration, component, &registry)?);
+            }
+
+            entities.push(SerializedEntity::Alive {
+                rollback,
+                order,
+                entity: entity.id().to_bits(),
+                components,
+            });
+        }
+
+        let mut resources = Vec::new();
+
+        for type_id in types.resources() {
+            // These are handled explicitly
+            if type_id == TypeId::of::<RollbackOrdered>()
+                || type_id == TypeId::of::<Time<GgrsTime>>()
+            {
+                continue;
+            }
+
+            let Some(component_id) = world.components().get_resource_id(type_id) else {
+                continue;
+            };
+
+            if world.get_resource_by_id(component_id).is_none() {
+                continue;
+            }
+
+            let registration = get_registration(&registry, type_id, || {
+                world
+                    .components()
+                    .get_info(component_id)
+                    .map(|info| info.name().to_owned())
+            })?;
+
+            let reflect_resource = get_type_data::<ReflectResource>(registration)?;
+
+            let Some(resource) = reflect_resource.reflect(world) else {
+                continue;
+            };
+
+            resources.push(serialize_value(registration, resource, &registry)?);
+        }
+
+        Ok(Self {
+            version: SERIALIZED_WORLD_VERSION,
+            frame,
+            elapsed_nanos,
+            total: ordered.total(),
+            entities,
+            resources,
+        })
+    }
+
+    /// The [`RollbackFrameCount`] at the time of serialization.
+    pub fn frame(&self) -> i32 {
+        self.frame
+    }
+
+    /// Encodes this [`SerializedWorld`] as bytes.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, WorldSerializationError> {
+        Ok(bincode::DefaultOptions::new().serialize(self)?)
+    }
+
+    /// Decodes a [`SerializedWorld`] from bytes created by [`to_bytes`](`SerializedWorld::to_bytes`).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WorldSerializationError> {
+        let world: Self = bincode::DefaultOptions::new().deserialize(bytes)?;
+
+        if world.version != SERIALIZED_WORLD_VERSION {
+            return Err(WorldSerializationError::UnsupportedVersion(world.version));
+        }
+
+        Ok(world)
+    }
+
+    /// Applies this rollback state to the provided [`World`].
+    ///
+    /// Any existing [`Rollback`] entities are despawned, and replaced with newly spawned
+    /// entities. [`RollbackOrdered`], [`RollbackFrameCount`], and [`Time<GgrsTime>`] are
+    /// overwritten, along with every serialized [`Resource`]. The [`RollbackFrameOffset`] is
+    /// set to the serialized frame, so the next [`Session`](`crate::Session`) will continue
+    /// from this frame.
+    pub fn apply(&self, world: &mut World) -> Result<(), WorldSerializationError> {
+        let registry = get_resource::<AppTypeRegistry>(world)?.clone();
+        let registry = registry.read();
+
+        // Decode everything before modifying the world, so errors leave it untouched
+        let mut entities = Vec::with_capacity(self.entities.len());
+
+        for entity in &self.entities {
+            let entity = match entity {
+                SerializedEntity::Alive {
+                    rollback,
+                    order,
+                    entity,
+                    components,
+                } => {
+                    let components = components
+                        .iter()
+                        .map(|value| deserialize_value(value, &registry))
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    (
+                        *rollback,
+                        *order,
+                        Some((Entity::from_bits(*entity), components)),
+                    )
+                }
+                SerializedEntity::Dead { rollback, order } => (*rollback, *order, None),
+            };
+
+            entities.push(entity);
+        }
+
+        let resources = self
+            .resources
+            .iter()
+            .map(|value| deserialize_value(value, &registry))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Remove the existing rollback entities
+        let existing = world
+            .iter_entities()
+            .filter(|entity| entity.contains::<Rollback>())
+            .map(|entity| entity.id())
+            .collect::<Vec<_>>();
+
+        for entity in existing {
+            world.despawn(entity);
+        }
+
+        let mut ordered = RollbackOrdered::default();
+        ordered.reserve_total(self.total);
+        let mut entity_map = HashMap::<Entity, Entity>::default();
+        let mut spawned = Vec::new();
+        let mut mappers = Vec::<ReflectMapEntities>::new();
+        let mut mapped_types = Vec::<TypeId>::new();
+
+        for (rollback, order, entity) in entities {
+            ordered.insert(rollback, order);
+
+            let Some((old_entity, components)) = entity else {
+                continue;
+            };
+
+            let mut entity = world.spawn_empty();
+
+            for (registration, component) in components {
+                let reflect_component = get_type_data::<ReflectComponent>(registration)?;
+                reflect_component.insert(&mut entity, component.as_ref());
+
+                if let Some(mapper) = registration.data::<ReflectMapEntities>() {
+                    if !mapped_types.contains(&registration.type_id()) {
+                        mapped_types.push(registration.type_id());
+                        mappers.push(mapper.clone());
+                    }
+                }
+            }
+
+            entity.insert(rollback);
+            entity_map.insert(old_entity, entity.id());
+            spawned.push(entity.id());
+        }
+
+        let mut resource_mappers = Vec::<ReflectMapEntitiesResource>::new();
+
+        for (registration, resource) in resources {
+            let reflect_resource = get_type_data::<ReflectResource>(registration)?;
+            reflect_resource.insert(world, resource.as_ref());
+
+            if let Some(mapper) = registration.data::<ReflectMapEntitiesResource>() {
+                resource_mappers.push(mapper.clone());
+            }
+        }
+
+        for mapper in mappers {
+            mapper.map_entities(world, &mut entity_map, &spawned);
+        }
+
+        for mapper in resource_mappers {
+            mapper.map_entities(world, &mut entity_map);
+        }
+
+        let mut time = Time::new_with(GgrsTime);
+        time.advance_to(Duration::from_nanos(self.elapsed_nanos));
+
+        world.insert_resource(ordered);
+        world.insert_resource(RollbackFrameCount(self.frame));
+        world.insert_resource(RollbackFrameOffset(self.frame));
+        world.insert_resource(time);
+
+        Ok(())
+    }
+}
+
+fn get_resource<R: Resource>(world: &World) -> Result<&R, WorldSerializationError> {
+    world
+        .get_resource::<R>()
+        .ok_or(WorldSerializationError::MissingResource(
+            std::any::type_name::<R>(),
+        ))
+}
+
+fn get_registration<'a>(
+    registry: &'a TypeRegistry,
+    type_id: TypeId,
+    name: impl FnOnce() -> Option<String>,
+) -> Result<&'a TypeRegistration, WorldSerializationError> {
+    registry.get(type_id).ok_or_else(|| {
+        WorldSerializationError::UnregisteredType(name().unwrap_or_else(|| format!("{type_id:?}")))
+    })
+}
+
+fn get_type_data<T: TypeData>(
+    registration: &TypeRegistration,
+) -> Result<&T, WorldSerializationError> {
+    registration
+        .data::<T>()
+        .ok_or_else(|| WorldSerializationError::MissingTypeData {
+            type_path: registration.type_info().type_path().to_owned(),
+            type_data: std::any::type_name::<T>(),
+        })
+}
+
+fn serialize_value(
+    registration: &TypeRegistration,
+    value: &dyn Reflect,
+    registry: &TypeRegistry,
+) -> Result<SerializedValue, WorldSerializationError> {
+    let serializer = TypedReflectSerializer::new(value, registry);
+
+    Ok(SerializedValue {
+        type_path: registration.type_info().type_path().to_owned(),
+        data: bincode::DefaultOptions::new().serialize(&serializer)?,
+    })
+}
+
+fn deserialize_value<'a>(
+    value: &SerializedValue,
+    registry: &'a TypeRegistry,
+) -> Result<(&'a TypeRegistration, Box<dyn Reflect>), WorldSerializationError> {
+    let registration = registry
+        .get_with_type_path(&value.type_path)
+        .ok_or_else(|| WorldSerializationError::UnknownType(value.type_path.clone()))?;
+
+    let deserializer = TypedReflectDeserializer::new(registration, registry);
+    let reflect = bincode::DefaultOptions::new().deserialize_seed(deserializer, &value.data)?;
+
+    Ok((registration, reflect))
+}
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::{AdvanceWorld, GgrsSchedule, LoadWorld, SaveWorld};
+use crate::{AdvanceWorld, ChecksumWorld, GgrsSchedule, LoadWorld, SaveWorld};
 
 /// Set for ordering systems during the [`LoadWorld`] schedule.
 /// The most common option is [`LoadWorldSet::Data`], which is where [`Component`]
@@ -19,6 +19,13 @@ pub enum LoadWorldSet {
     /// When this set is complete, all [`Components`](`Component`) and [`Resources`](`Resource`)
     /// will be rolled back to their exact state during the snapshot.
     ///
+    /// Each type is loaded by its own system, which only accesses that type and its snapshots, so
+    /// types are loaded in parallel on a multi-threaded executor. Structural changes (inserting or
+    /// removing data) are deferred through [`Commands`] until [`LoadWorldSet::DataFlush`].
+    ///
+    /// NOTE: The [`DynamicSnapshotPlugin`](`crate::DynamicSnapshotPlugin`) loads every type it
+    /// manages from a single exclusive system, which runs on its own.
+    ///
     /// NOTE: At this point, [`Entity`] relationships may be broken, see [`LoadWorldSet::Mapping`]
     /// for when those relationships are fixed.
     Data,
@@ -28,12 +35,16 @@ pub enum LoadWorldSet {
     /// state of the rollback when compared to the original snapshot. For example, [`Entities`](`Entity`)
     /// which had to be recreated could not use the same ID, so any data referring to that ID is now invalid.
     /// Once this set completes, all data should now be coherent with the [`World`].
+    ///
+    /// NOTE: [`EntityMapper`](`bevy::ecs::entity::EntityMapper`) requires access to the whole
+    /// [`World`], so systems in this set are exclusive and run one at a time. As each maps a
+    /// different type, they are not ordered relative to each other.
     Mapping,
 }
 
 #[derive(SystemSet, Hash, Debug, PartialEq, Eq, Clone)]
 pub enum SaveWorldSet {
-    /// Generate checksums for any tracked data.
+    /// Generate checksums for any tracked data. The [`ChecksumWorld`] schedule is run in this set.
     ///
     /// Within this set, it is expected that all data which will participate in the
     /// total checksum recorded for this frame will have updated/created a single [`Entity`]
@@ -43,6 +54,11 @@ pub enum SaveWorldSet {
     /// the [`Snapshot`](`SaveWorldSet::Snapshot`) set.
     Checksum,
     /// Saves a snapshot of the [`World`] in this state for future possible rollback.
+    ///
+    /// Each type is stored in its own snapshot [`Resource`], so systems in this set for different
+    /// types run in parallel on a multi-threaded executor. The
+    /// [`DynamicSnapshotPlugin`](`crate::DynamicSnapshotPlugin`) is the exception, saving every
+    /// type it manages from a single exclusive system.
     Snapshot,
 }
 
@@ -70,6 +86,10 @@ impl Plugin for SnapshotSetPlugin {
             )
                 .chain(),
         )
+        .configure_sets(
+            LoadWorld,
+            LoadWorldSet::Mapping.ambiguous_with(LoadWorldSet::Mapping),
+        )
         .configure_sets(
             SaveWorld,
             (SaveWorldSet::Checksum, SaveWorldSet::Snapshot).chain(),
@@ -100,6 +120,10 @@ impl Plugin for SnapshotSetPlugin {
         .add_systems(
             AdvanceWorld,
             (|world: &mut World| world.run_schedule(GgrsSchedule)).in_set(AdvanceWorldSet::Main),
+        )
+        .add_systems(
+            SaveWorld,
+            (|world: &mut World| world.run_schedule(ChecksumWorld)).in_set(SaveWorldSet::Checksum),
         );
     }
 }
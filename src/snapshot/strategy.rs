@@ -2,7 +2,7 @@ use std::marker::PhantomData;
 
 use bevy::{
     prelude::{FromWorld, World},
-    reflect::Reflect,
+    reflect::{FromReflect, Reflect},
 };
 
 /// Describes how to efficiently transform a [`Target`](`Strategy::Target`) into a
@@ -92,3 +92,32 @@ impl<T: Reflect + FromWorld> Strategy for ReflectStrategy<T> {
         target
     }
 }
+
+/// A [`Strategy`] based on [`Reflect`] and [`FromReflect`].
+///
+/// Unlike [`ReflectStrategy`], this does not require a [`FromWorld`] implementation, which
+/// types without a sensible default (such as those referring to an [`Entity`](`bevy::prelude::Entity`))
+/// may not have.
+pub struct FromReflectStrategy<T: FromReflect>(PhantomData<T>);
+
+impl<T: FromReflect> Strategy for FromReflectStrategy<T> {
+    type Target = T;
+
+    type Stored = Box<dyn Reflect>;
+
+    #[inline(always)]
+    fn store(target: &Self::Target) -> Self::Stored {
+        target.as_reflect().clone_value()
+    }
+
+    #[inline(always)]
+    fn update(target: &mut Self::Target, stored: &Self::Stored) {
+        target.apply(stored.as_ref());
+    }
+
+    #[inline(always)]
+    fn load(stored: &Self::Stored) -> Self::Target {
+        Self::Target::from_reflect(stored.as_ref())
+            .expect("stored values are always created from a Target")
+    }
+}
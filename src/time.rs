@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::{num::NonZeroUsize, time::Duration};
 
 use bevy::prelude::*;
 
@@ -17,6 +17,85 @@ impl Default for RollbackFrameRate {
     }
 }
 
+/// [`Resource`] describing how the local simulation is kept in time with remote peers.
+///
+/// When the local [`Session`](`crate::Session`) is ahead of its peers, the rollback
+/// simulation is slowed down by [`slow_down_factor`](`TimeSyncPolicy::slow_down_factor`).
+/// If GGRS additionally recommends skipping frames (through a
+/// [`WaitRecommendation`](`crate::WaitRecommendation`)), those frames will be skipped
+/// entirely when [`honour_wait_recommendations`](`TimeSyncPolicy::honour_wait_recommendations`)
+/// is enabled.
+///
+/// # Examples
+/// ```rust
+/// # use std::num::NonZeroUsize;
+/// # use bevy::prelude::*;
+/// # use bevy_ggrs::{prelude::*, TimeSyncPolicy};
+/// #
+/// # let mut app = App::new();
+/// # app.add_plugins(GgrsPlugin::<GgrsConfig<u8>>::default());
+/// // Slow down more aggressively, and never run more than 4 frames per update
+/// app.insert_resource(TimeSyncPolicy {
+///     slow_down_factor: 1.25,
+///     max_catch_up_steps: NonZeroUsize::new(4),
+///     ..default()
+/// });
+/// ```
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct TimeSyncPolicy {
+    /// Multiplier applied to the duration of a frame while the local [`Session`](`crate::Session`)
+    /// is ahead of its remote peers. A value of `1.0` disables slowing down.
+    pub slow_down_factor: f64,
+    /// If `true`, frames recommended to be skipped by GGRS will not be simulated, instead
+    /// consuming the accumulated time without advancing the [`Session`](`crate::Session`).
+    pub honour_wait_recommendations: bool,
+    /// The maximum number of frames to advance within a single update. If `None`, all
+    /// accumulated time will be consumed every update.
+    ///
+    /// This can't be zero, as the simulation would then never advance.
+    pub max_catch_up_steps: Option<NonZeroUsize>,
+    /// What to do with the time left over once [`max_catch_up_steps`](`TimeSyncPolicy::max_catch_up_steps`)
+    /// frames have been advanced within a single update.
+    pub catch_up_overflow: CatchUpOverflow,
+}
+
+impl Default for TimeSyncPolicy {
+    fn default() -> Self {
+        Self {
+            slow_down_factor: 1.1,
+            honour_wait_recommendations: true,
+            max_catch_up_steps: None,
+            catch_up_overflow: CatchUpOverflow::default(),
+        }
+    }
+}
+
+/// Describes how time left over after reaching [`TimeSyncPolicy::max_catch_up_steps`] is handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CatchUpOverflow {
+    /// Discard the left over time. The simulation will permanently fall behind real time,
+    /// but will never run more than the maximum number of steps in an update.
+    Drop,
+    /// Keep the left over time, running the maximum number of steps every update until it
+    /// has been consumed.
+    #[default]
+    Carry,
+    /// Keep the left over time in a backlog, running at most one extra step per update until
+    /// it has been consumed. This catches up more slowly than [`Carry`](`CatchUpOverflow::Carry`),
+    /// but avoids repeated bursts of steps.
+    Spread,
+}
+
+/// Sent when [`TimeSyncPolicy::max_catch_up_steps`] was reached within a single update.
+/// Useful for showing a "catching up" indicator.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CatchUpLimitReached {
+    /// The number of steps advanced in this update.
+    pub steps: usize,
+    /// The time which could not be consumed in this update, before applying [`CatchUpOverflow`].
+    pub leftover: Duration,
+}
+
 /// A [`Time`] type for use with GGRS. This time is guaranteed to be in-sync with
 /// all peers, and reflect that exactly [`RollbackFrameCount`] frames have passed at
 /// the [`RollbackFrameRate`] rate. Note that in the [`GgrsSchedule`](`crate::GgrsSchedule`),
@@ -87,6 +166,8 @@ impl GgrsTimePlugin {
 impl Plugin for GgrsTimePlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(Time::new_with(GgrsTime::default()))
+            .init_resource::<TimeSyncPolicy>()
+            .add_event::<CatchUpLimitReached>()
             .add_plugins(ResourceSnapshotPlugin::<CloneStrategy<Time<GgrsTime>>>::default())
             .add_systems(
                 AdvanceWorld,
@@ -0,0 +1,106 @@
+mod common;
+
+use std::{
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use bevy::prelude::*;
+use bevy_ggrs::*;
+
+const ENTITIES: usize = 10;
+
+/// Large and rarely changed; only the first inventory gains an item each frame.
+#[derive(Component, Clone, Debug, PartialEq, Eq)]
+struct Inventory {
+    index: usize,
+    items: Vec<u32>,
+}
+
+static STORED: AtomicUsize = AtomicUsize::new(0);
+
+/// A [`CloneStrategy`] which counts how many times it was asked to store a component.
+struct CountingStrategy<T>(PhantomData<T>);
+
+impl<T: Clone> Strategy for CountingStrategy<T> {
+    type Target = T;
+    type Stored = T;
+
+    fn store(target: &Self::Target) -> Self::Stored {
+        STORED.fetch_add(1, Ordering::Relaxed);
+        target.clone()
+    }
+
+    fn load(stored: &Self::Stored) -> Self::Target {
+        stored.clone()
+    }
+}
+
+fn setup_system(mut commands: Commands) {
+    for index in 0..ENTITIES {
+        commands
+            .spawn(Inventory {
+                index,
+                items: vec![0; 1000],
+            })
+            .add_rollback();
+    }
+}
+
+fn loot_system(frame: Res<RollbackFrameCount>, mut query: Query<&mut Inventory>) {
+    for mut inventory in query.iter_mut() {
+        if inventory.index == 0 {
+            inventory.items.push(i32::from(*frame) as u32);
+        }
+    }
+}
+
+fn create_app() -> App {
+    let mut app = common::create_app();
+
+    app.add_systems(Startup, setup_system)
+        .add_plugins(ChangedComponentSnapshotPlugin::<CountingStrategy<Inventory>>::default())
+        .add_systems(GgrsSchedule, loot_system);
+
+    app
+}
+
+#[test]
+fn it_only_stores_changed_components() {
+    let mut app = create_app();
+
+    for _ in 0..20 {
+        app.update();
+    }
+
+    let frame = common::frame(&app);
+    assert!(frame > 5);
+
+    for inventory in app.world.query::<&Inventory>().iter(&app.world) {
+        let added = inventory.items.len() - 1000;
+
+        match inventory.index {
+            0 => assert_eq!(added, frame as usize),
+            _ => assert_eq!(added, 0),
+        }
+    }
+
+    // Unchanged inventories are stored once, rather than once per saved frame
+    let stored = STORED.load(Ordering::Relaxed);
+    assert!(stored < ENTITIES * frame as usize / 2);
+
+    // Unchanged inventories share their stored value between snapshots
+    let snapshots = app
+        .world
+        .resource::<GgrsComponentSnapshots<Inventory, Arc<Inventory>>>();
+    let newest = snapshots.peek(frame - 1).unwrap();
+    let previous = snapshots.peek(frame - 2).unwrap();
+
+    for (rollback, stored) in newest.iter() {
+        let shared = Arc::ptr_eq(stored, previous.get(rollback).unwrap());
+        assert_eq!(shared, stored.index != 0);
+    }
+}
@@ -0,0 +1,110 @@
+//! Setup shared by the integration tests. Not every test uses every helper.
+#![allow(dead_code)]
+
+use bevy::{prelude::*, time::TimeUpdateStrategy, utils::Duration};
+use bevy_ggrs::*;
+use ggrs::{PlayerType, SessionBuilder};
+
+pub type TestConfig = GgrsConfig<u8, usize>;
+
+/// Slightly longer than a 60 FPS frame, so every update advances exactly one frame.
+pub const FRAME: Duration = Duration::from_micros(16_667);
+
+/// Provides an input of `0` for every local player.
+pub fn input_system(mut commands: Commands, local_players: Res<LocalPlayers>) {
+    let inputs = local_players.0.iter().map(|&handle| (handle, 0)).collect();
+    commands.insert_resource(LocalInputs::<TestConfig>(inputs));
+}
+
+/// The input of every local player on `frame`, which changes every few frames so remote peers
+/// mispredict it.
+pub fn changing_input(frame: i32) -> u8 {
+    (frame / 4) as u8
+}
+
+/// Provides [`changing_input`] for every local player.
+pub fn changing_input_system(
+    mut commands: Commands,
+    local_players: Res<LocalPlayers>,
+    frame: Res<RollbackFrameCount>,
+) {
+    let input = changing_input((*frame).into());
+    let inputs = local_players
+        .0
+        .iter()
+        .map(|&handle| (handle, input))
+        .collect();
+    commands.insert_resource(LocalInputs::<TestConfig>(inputs));
+}
+
+/// Counts how many times the [`LoadWorld`] schedule was run. Not rolled back.
+#[derive(Resource, Default)]
+pub struct Rollbacks(pub usize);
+
+fn count_rollbacks(mut rollbacks: ResMut<Rollbacks>) {
+    rollbacks.0 += 1;
+}
+
+/// A single player [`SyncTestSession`](ggrs::SyncTestSession) which resimulates the last two
+/// frames every frame.
+pub fn synctest_session() -> Session<TestConfig> {
+    Session::SyncTest(
+        SessionBuilder::<TestConfig>::new()
+            .with_num_players(1)
+            .with_check_distance(2)
+            .add_player(PlayerType::Local, 0)
+            .unwrap()
+            .start_synctest_session()
+            .unwrap(),
+    )
+}
+
+/// Creates an app which advances one frame per update, without a session or input system.
+pub fn create_base_app() -> App {
+    let mut app = App::new();
+
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(GgrsPlugin::<TestConfig>::default())
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME));
+
+    app
+}
+
+/// Creates an app running a [`synctest_session`], with [`input_system`] providing inputs.
+pub fn create_app() -> App {
+    let mut app = create_base_app();
+    app.insert_resource(synctest_session())
+        .add_systems(ReadInputs, input_system);
+    app
+}
+
+pub fn frame(app: &App) -> i32 {
+    (*app.world.resource::<RollbackFrameCount>()).into()
+}
+
+/// Creates two apps connected over `network`, where the first player's input changes every few
+/// frames and the second player's input is always `0`.
+pub fn create_p2p_apps(network: &LoopbackNetwork) -> Vec<App> {
+    let mut apps = vec![create_base_app(), create_base_app()];
+    apps[0].add_systems(ReadInputs, changing_input_system);
+    apps[1].add_systems(ReadInputs, input_system);
+
+    for app in &mut apps {
+        app.init_resource::<Rollbacks>()
+            .add_systems(LoadWorld, count_rollbacks);
+    }
+
+    network
+        .connect_apps::<TestConfig>(&mut apps, |builder| builder)
+        .unwrap();
+
+    apps
+}
+
+/// A [`LoopbackNetwork`] with enough latency that remote inputs arrive several frames late.
+pub fn laggy_network() -> LoopbackNetwork {
+    LoopbackNetwork::new(NetworkConditions {
+        latency: Duration::from_millis(50),
+        ..default()
+    })
+}
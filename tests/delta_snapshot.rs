@@ -0,0 +1,100 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_ggrs::*;
+
+const ENTITIES: u32 = 20;
+
+/// Only changes on the first entity.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+struct Counter {
+    index: u32,
+    value: u32,
+}
+
+/// Present on the second entity every third frame.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+struct Flag;
+
+fn setup_system(mut commands: Commands) {
+    for index in 0..ENTITIES {
+        commands.spawn(Counter { index, value: 0 }).add_rollback();
+    }
+}
+
+fn update_system(
+    mut commands: Commands,
+    frame: Res<RollbackFrameCount>,
+    mut query: Query<(Entity, &mut Counter)>,
+) {
+    let frame = i32::from(*frame);
+
+    for (entity, mut counter) in query.iter_mut() {
+        match counter.index {
+            0 => counter.value += 1,
+            1 if frame % 3 == 0 => {
+                commands.entity(entity).insert(Flag);
+            }
+            1 => {
+                commands.entity(entity).remove::<Flag>();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn create_app() -> App {
+    let mut app = common::create_app();
+
+    app.add_systems(Startup, setup_system)
+        .add_plugins(ComponentDeltaSnapshotPlugin::<CopyStrategy<Counter>>::default())
+        .add_plugins(ComponentDeltaSnapshotPlugin::<CopyStrategy<Flag>>::default())
+        .add_systems(GgrsSchedule, update_system);
+
+    app
+}
+
+#[test]
+fn it_rolls_back_using_deltas() {
+    let mut app = create_app();
+
+    for _ in 0..20 {
+        app.update();
+    }
+
+    let frame = common::frame(&app);
+    assert!(frame > 5);
+
+    for (counter, flag) in app
+        .world
+        .query::<(&Counter, Option<&Flag>)>()
+        .iter(&app.world)
+    {
+        match counter.index {
+            0 => assert_eq!(counter.value, frame as u32),
+            1 => {
+                assert_eq!(counter.value, 0);
+                assert_eq!(flag.is_some(), frame % 3 == 0);
+            }
+            _ => {
+                assert_eq!(counter.value, 0);
+                assert!(flag.is_none());
+            }
+        }
+    }
+}
+
+#[test]
+fn it_only_stores_changed_components() {
+    let mut app = create_app();
+
+    for _ in 0..20 {
+        app.update();
+    }
+
+    let snapshots = app.world.resource::<GgrsDeltaSnapshots<Counter, Counter>>();
+
+    // Every entity once, plus roughly one change per stored frame
+    let full_copies = ENTITIES as usize * 3;
+    assert!(snapshots.stored_len() < full_copies);
+}
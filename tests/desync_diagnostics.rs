@@ -0,0 +1,102 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_ggrs::*;
+
+#[derive(Component, Clone, Copy, Hash)]
+struct Health(u32);
+
+#[derive(Resource, Clone, Copy, Hash, Default)]
+struct FrameCounter(u32);
+
+fn setup_system(mut commands: Commands) {
+    for health in 0..3 {
+        commands.spawn(Health(health)).add_rollback();
+    }
+}
+
+fn damage_system(mut counter: ResMut<FrameCounter>, mut health: Query<&mut Health>) {
+    counter.0 += 1;
+
+    for mut health in health.iter_mut() {
+        health.0 += 1;
+    }
+}
+
+fn create_app() -> App {
+    let mut app = common::create_app();
+
+    app.add_systems(Startup, setup_system)
+        .add_plugins(DesyncDiagnosticsPlugin::default())
+        .init_resource::<FrameCounter>()
+        .rollback_component_with_copy::<Health>()
+        .checksum_component_with_hash::<Health>()
+        .rollback_resource_with_copy::<FrameCounter>()
+        .checksum_resource_with_hash::<FrameCounter>()
+        .add_systems(GgrsSchedule, damage_system);
+
+    app
+}
+
+#[test]
+fn it_records_checksum_parts_per_type() {
+    let mut app = create_app();
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let history = app.world.resource::<ChecksumHistory>();
+    let report = history.iter().last().expect("No frames were recorded");
+
+    assert!(report
+        .part(std::any::type_name::<Health>())
+        .is_some_and(|part| part.entities.len() == 3));
+    assert!(report.part(std::any::type_name::<FrameCounter>()).is_some());
+    assert!(report.diff(report).is_empty());
+}
+
+#[test]
+fn it_reports_diverging_types_and_entities() {
+    let mut app = create_app();
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let history = app.world.resource::<ChecksumHistory>();
+    let local = history
+        .iter()
+        .last()
+        .expect("No frames were recorded")
+        .clone();
+
+    // Simulate a remote peer which disagrees on the health of a single entity
+    let mut remote = local.clone();
+    let health = remote
+        .parts
+        .iter_mut()
+        .find(|part| part.name == std::any::type_name::<Health>())
+        .unwrap();
+    health.checksum ^= 1;
+    health.entities[1].1 ^= 1;
+    let diverged = health.entities[1].0;
+
+    app.world
+        .resource_mut::<Events<RemoteChecksumReport>>()
+        .send(RemoteChecksumReport(remote));
+
+    app.update();
+
+    let reports = app.world.resource::<Events<DesyncReport>>();
+    let report = reports
+        .iter_current_update_events()
+        .next()
+        .expect("No desync report was produced");
+
+    assert_eq!(report.frame, local.frame);
+    assert_eq!(report.mismatches.len(), 1);
+    assert_eq!(report.mismatches[0].name, std::any::type_name::<Health>());
+    assert_eq!(report.mismatches[0].entities.len(), 1);
+    assert_eq!(report.mismatches[0].entities[0].order, diverged);
+}
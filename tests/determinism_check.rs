@@ -0,0 +1,167 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_ggrs::*;
+
+#[derive(Component, Clone, Copy, Hash)]
+struct Health(u32);
+
+#[derive(Component, Clone, Copy, Hash)]
+struct Armor(u32);
+
+/// Not rolled back, so it differs every time a frame is advanced.
+#[derive(Resource, Default)]
+struct Calls(u32);
+
+#[derive(Resource, Default)]
+struct Detections(Vec<NonDeterminismDetected>);
+
+fn setup_system(mut commands: Commands) {
+    for value in 0..3 {
+        commands.spawn((Health(value), Armor(value))).add_rollback();
+    }
+}
+
+fn damage_system(mut health: Query<&mut Health>) {
+    for mut health in health.iter_mut() {
+        health.0 += 1;
+    }
+}
+
+fn flaky_armor_system(mut calls: ResMut<Calls>, mut armor: Query<&mut Armor>) {
+    calls.0 += 1;
+
+    for mut armor in armor.iter_mut() {
+        armor.0 = calls.0;
+    }
+}
+
+fn collect_detections(
+    mut events: EventReader<NonDeterminismDetected>,
+    mut detections: ResMut<Detections>,
+) {
+    detections.0.extend(events.read().cloned());
+}
+
+fn create_app() -> App {
+    let mut app = common::create_app();
+
+    app.add_systems(Startup, setup_system)
+        .init_resource::<Calls>()
+        .init_resource::<Detections>()
+        .rollback_component_with_copy::<Health>()
+        .checksum_component_with_hash::<Health>()
+        .rollback_component_with_copy::<Armor>()
+        .checksum_component_with_hash::<Armor>()
+        .add_systems(GgrsSchedule, damage_system)
+        .add_systems(Update, collect_detections);
+
+    app
+}
+
+#[test]
+fn it_names_the_non_deterministic_system() {
+    let mut app = create_app();
+    app.add_plugins(DeterminismCheckPlugin)
+        .add_systems(GgrsSchedule, flaky_armor_system.after(damage_system));
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let detections = &app.world.resource::<Detections>().0;
+    let first = detections
+        .first()
+        .expect("Non-determinism was not detected");
+
+    assert!(first.system.ends_with("flaky_armor_system"));
+    assert_eq!(first.mismatches.len(), 1);
+    assert_eq!(first.mismatches[0].name, std::any::type_name::<Armor>());
+    assert_eq!(first.mismatches[0].entities.len(), 3);
+}
+
+#[test]
+fn it_accepts_deterministic_systems() {
+    let mut app = create_app();
+    app.add_plugins(DeterminismCheckPlugin);
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    assert!(app.world.resource::<Detections>().0.is_empty());
+    assert!(common::frame(&app) > 0);
+}
+
+#[test]
+fn it_does_not_save_additional_snapshots() {
+    let mut checked = create_app();
+    checked.add_plugins(DeterminismCheckPlugin);
+
+    let mut unchecked = create_app();
+    unchecked.add_plugins(DesyncDiagnosticsPlugin::default());
+
+    for _ in 0..10 {
+        checked.update();
+        unchecked.update();
+    }
+
+    let frame = i32::from(*checked.world.resource::<RollbackFrameCount>());
+    assert_eq!(
+        frame,
+        i32::from(*unchecked.world.resource::<RollbackFrameCount>())
+    );
+
+    // Both apps store snapshots and checksums for exactly the same frames
+    let snapshots = |app: &App| {
+        let snapshots = app.world.resource::<GgrsComponentSnapshots<Health>>();
+        (0..=frame)
+            .filter(|&frame| snapshots.peek(frame).is_some())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(snapshots(&checked), snapshots(&unchecked));
+
+    let checksums = |app: &App| {
+        app.world
+            .resource::<ChecksumHistory>()
+            .iter()
+            .map(|report| (report.frame, report.checksum))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(checksums(&checked), checksums(&unchecked));
+
+    assert_eq!(
+        checked.world.resource::<RollbackEntityMap>().len(),
+        unchecked.world.resource::<RollbackEntityMap>().len()
+    );
+}
+
+#[test]
+fn it_checks_when_started_after_several_frames() {
+    let mut app = create_app();
+    app.add_plugins(DeterminismCheckPlugin)
+        .add_systems(GgrsSchedule, flaky_armor_system.after(damage_system));
+
+    // Captures the systems to check before the schedule is first run
+    app.finish();
+    app.world.remove_resource::<DeterminismCheck>();
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    assert!(app.world.resource::<Detections>().0.is_empty());
+
+    app.init_resource::<DeterminismCheck>();
+
+    for _ in 0..5 {
+        app.update();
+    }
+
+    let detections = &app.world.resource::<Detections>().0;
+    let first = detections
+        .first()
+        .expect("Non-determinism was not detected");
+
+    assert!(first.system.ends_with("flaky_armor_system"));
+}
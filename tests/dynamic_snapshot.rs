@@ -0,0 +1,116 @@
+mod common;
+
+use std::any::TypeId;
+
+use bevy::prelude::*;
+use bevy_ggrs::*;
+
+#[derive(Component, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component)]
+struct Health(u32);
+
+/// Present on odd frames only.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component)]
+struct Odd;
+
+/// Counts every time a frame is advanced, including resimulations, unless rolled back.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component)]
+struct Simulated(u32);
+
+fn setup_system(mut commands: Commands) {
+    commands.spawn((Health(0), Simulated(0))).add_rollback();
+}
+
+fn update_system(
+    mut commands: Commands,
+    frame: Res<RollbackFrameCount>,
+    mut query: Query<(Entity, &mut Health, &mut Simulated)>,
+) {
+    let odd = i32::from(*frame) % 2 == 1;
+
+    for (entity, mut health, mut simulated) in query.iter_mut() {
+        health.0 += 1;
+        simulated.0 += 1;
+
+        if odd {
+            commands.entity(entity).insert(Odd);
+        } else {
+            commands.entity(entity).remove::<Odd>();
+        }
+    }
+}
+
+fn create_app(plugin: DynamicSnapshotPlugin) -> App {
+    let mut app = common::create_app();
+
+    app.add_systems(Startup, setup_system)
+        .register_type::<Health>()
+        .register_type::<Odd>()
+        .register_type::<Simulated>()
+        .add_plugins(plugin)
+        .add_systems(GgrsSchedule, update_system);
+
+    app
+}
+
+fn run(app: &mut App) -> (i32, Health, Option<Odd>, Simulated) {
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let frame = common::frame(&app);
+    assert!(frame > 2);
+
+    let (&health, odd, &simulated) = app
+        .world
+        .query::<(&Health, Option<&Odd>, &Simulated)>()
+        .single(&app.world);
+
+    (frame, health, odd.copied(), simulated)
+}
+
+#[test]
+fn it_rolls_back_all_reflected_components() {
+    let mut app = create_app(DynamicSnapshotPlugin::default());
+
+    let (frame, health, odd, simulated) = run(&mut app);
+
+    assert_eq!(health, Health(frame as u32));
+    assert_eq!(simulated, Simulated(frame as u32));
+    assert_eq!(odd.is_some(), frame % 2 == 1);
+}
+
+#[test]
+fn it_skips_denied_components() {
+    let mut app = create_app(DynamicSnapshotPlugin::default().deny::<Simulated>());
+
+    let (frame, health, _, simulated) = run(&mut app);
+
+    assert_eq!(health, Health(frame as u32));
+    // Resimulated frames were not rolled back
+    assert!(simulated.0 > frame as u32);
+}
+
+#[test]
+fn it_only_includes_allowed_components() {
+    let mut app = create_app(DynamicSnapshotPlugin::default().allow::<Health>());
+
+    let (frame, health, _, simulated) = run(&mut app);
+
+    assert_eq!(health, Health(frame as u32));
+    assert!(simulated.0 > frame as u32);
+}
+
+#[test]
+fn it_registers_included_components_for_rollback() {
+    let mut app = create_app(DynamicSnapshotPlugin::default().deny::<Simulated>());
+
+    run(&mut app);
+
+    let types = app.world.resource::<RollbackTypes>();
+    assert!(types.contains_component(TypeId::of::<Health>()));
+    assert!(types.contains_component(TypeId::of::<Odd>()));
+    assert!(!types.contains_component(TypeId::of::<Simulated>()));
+}
@@ -7,33 +7,26 @@ use bevy::{
 };
 use bevy_ggrs::{
     AddRollbackCommandExtension, GgrsConfig, GgrsPlugin, GgrsSchedule, LocalInputs, LocalPlayers,
-    PlayerInputs, ReadInputs, Rollback, Session,
+    LoopbackNetwork, NetworkConditions, PlayerInputs, ReadInputs, Rollback, Session, Synchronized,
 };
 use bytemuck::{Pod, Zeroable};
-use ggrs::{Config, P2PSession, PlayerHandle, PlayerType, SessionBuilder, UdpNonBlockingSocket};
-use serial_test::serial;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use ggrs::{Config, SessionBuilder};
 
 #[test]
-#[serial]
 fn it_runs_advance_frame_schedule_systems() -> Result<(), Box<dyn std::error::Error>> {
-    let (player1, player2) = create_players();
-    let session1 = start_session(&player1, &player2)?;
-    let mut app1 = create_app::<TestConfig>(session1);
-    let session2 = start_session(&player2, &player1)?;
-    let mut app2 = create_app::<TestConfig>(session2);
+    let network = LoopbackNetwork::default();
+    let mut apps = start_match(&network)?;
 
-    let inputs1 = HashMap::from([(player1.handle, BoxInput { inp: 0 })]);
+    let inputs1 = HashMap::from([(0, BoxInput { inp: 0 })]);
     let inputs_resource = LocalInputs::<TestConfig>(inputs1);
-    app1.insert_resource(inputs_resource);
+    apps[0].insert_resource(inputs_resource);
 
     for _ in 0..50 {
-        app1.update();
-        app2.update();
+        network.update_apps(&mut apps, FRAME);
     }
 
-    let frame_count1 = app1.world.get_resource::<FrameCount>().unwrap();
-    let frame_count2 = app2.world.get_resource::<FrameCount>().unwrap();
+    let frame_count1 = apps[0].world.get_resource::<FrameCount>().unwrap();
+    let frame_count2 = apps[1].world.get_resource::<FrameCount>().unwrap();
 
     // We've run Bevy for 50 frames, bevy_ggrs, however needs a couple of frames
     // to sync before it starts to run the advance frame schedule, so the
@@ -46,38 +39,69 @@ fn it_runs_advance_frame_schedule_systems() -> Result<(), Box<dyn std::error::Er
 }
 
 #[test]
-#[serial]
 fn it_syncs_rollback_components() -> Result<(), Box<dyn std::error::Error>> {
-    let (player1, player2) = create_players();
-    let session1 = start_session(&player1, &player2)?;
-    let mut app1 = create_app::<TestConfig>(session1);
-    let session2 = start_session(&player2, &player1)?;
-    let mut app2 = create_app::<TestConfig>(session2);
+    let network = LoopbackNetwork::default();
+    let mut apps = start_match(&network)?;
 
     for _ in 0..50 {
-        press_key(&mut app1, KeyCode::W);
-        app1.update();
-        app2.update();
+        press_key(&mut apps[0], KeyCode::W);
+        network.update_apps(&mut apps, FRAME);
     }
 
-    let mut app2_query = app2.world.query::<(&Transform, &PlayerComponent)>();
-    for (transform, player) in app2_query.iter(&app2.world) {
-        if player.handle == player1.handle {
-            assert!(transform.translation.z < 0., "Remote player moves forward");
-        }
+    assert_remote_player_moved(&mut apps[1]);
+    Ok(())
+}
+
+#[test]
+fn it_syncs_rollback_components_over_poor_network() -> Result<(), Box<dyn std::error::Error>> {
+    let network = LoopbackNetwork::new(NetworkConditions {
+        latency: Duration::from_millis(40),
+        jitter: Duration::from_millis(20),
+        reorder: 0.1,
+        ..default()
+    })
+    .with_seed(7);
+    let mut apps = start_match(&network)?;
+
+    for _ in 0..150 {
+        press_key(&mut apps[0], KeyCode::W);
+        network.update_apps(&mut apps, FRAME);
     }
+
+    assert_remote_player_moved(&mut apps[1]);
     Ok(())
 }
 
-fn create_app<T: Config>(session: P2PSession<T>) -> App {
+#[test]
+fn it_forwards_session_events() -> Result<(), Box<dyn std::error::Error>> {
+    let network = LoopbackNetwork::default();
+    let mut apps = start_match(&network)?;
+
+    apps[0]
+        .init_resource::<SynchronizedCount>()
+        .add_systems(Update, count_synchronized);
+
+    for _ in 0..50 {
+        network.update_apps(&mut apps, FRAME);
+    }
+
+    let synchronized = apps[0].world.resource::<SynchronizedCount>();
+    assert_eq!(
+        synchronized.0, 1,
+        "Synchronized with the remote player once"
+    );
+
+    Ok(())
+}
+
+const FRAME: Duration = Duration::from_micros(16_667);
+
+fn create_app<T: Config>() -> App {
     let mut app = App::new();
     app.add_plugins(MinimalPlugins)
         .add_plugins(InputPlugin::default())
         .add_plugins(GgrsPlugin::<T>::default())
-        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
-            1.0 / 60.0,
-        )))
-        .insert_resource(Session::P2P(session))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME))
         .insert_resource(FrameCount { frame: 0 })
         .add_systems(GgrsSchedule, (move_player_system, increase_frame_system))
         .add_systems(ReadInputs, read_local_inputs)
@@ -85,7 +109,7 @@ fn create_app<T: Config>(session: P2PSession<T>) -> App {
     app
 }
 
-type TestConfig = GgrsConfig<BoxInput>;
+type TestConfig = GgrsConfig<BoxInput, usize>;
 
 #[repr(C)]
 #[derive(Copy, Clone, PartialEq, Eq, Pod, Zeroable)]
@@ -93,45 +117,24 @@ pub struct BoxInput {
     pub inp: u8,
 }
 
-pub struct TestPlayer {
-    handle: PlayerHandle,
-    address: SocketAddr,
-}
-
-fn create_players() -> (TestPlayer, TestPlayer) {
-    const PLAYER1_PORT: u16 = 8081;
-    const REMOTE_PORT: u16 = 8082;
-    let remote_addr1 = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), PLAYER1_PORT);
-    let remote_addr2 = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), REMOTE_PORT);
-    return (
-        TestPlayer {
-            handle: 0,
-            address: remote_addr1,
-        },
-        TestPlayer {
-            handle: 1,
-            address: remote_addr2,
-        },
-    );
+fn start_match(network: &LoopbackNetwork) -> Result<Vec<App>, Box<dyn std::error::Error>> {
+    let mut apps = vec![create_app::<TestConfig>(), create_app::<TestConfig>()];
+    network.connect_apps::<TestConfig>(&mut apps, |builder: SessionBuilder<TestConfig>| {
+        builder
+            .with_max_prediction_window(12)
+            .expect("prediction window can't be 0") // (optional) set max prediction window
+            .with_input_delay(2) // (optional) set input delay for the local player
+    })?;
+    Ok(apps)
 }
 
-fn start_session(
-    local_player: &TestPlayer,
-    remote_player: &TestPlayer,
-) -> Result<P2PSession<TestConfig>, Box<dyn std::error::Error>> {
-    let mut session_builder = SessionBuilder::<TestConfig>::new()
-        .with_num_players(2)
-        .with_max_prediction_window(12)
-        .expect("prediction window can't be 0") // (optional) set max prediction window
-        .with_input_delay(2); // (optional) set input delay for the local player
-    session_builder = session_builder.add_player(PlayerType::Local, local_player.handle)?;
-    session_builder = session_builder.add_player(
-        PlayerType::Remote(remote_player.address),
-        remote_player.handle,
-    )?;
-    let socket = UdpNonBlockingSocket::bind_to_port(local_player.address.port())?;
-    let session = session_builder.start_p2p_session(socket)?;
-    Ok(session)
+fn assert_remote_player_moved(app: &mut App) {
+    let mut query = app.world.query::<(&Transform, &PlayerComponent)>();
+    for (transform, player) in query.iter(&app.world) {
+        if player.handle == 0 {
+            assert!(transform.translation.z < 0., "Remote player moves forward");
+        }
+    }
 }
 
 const INPUT_UP: u8 = 1 << 0;
@@ -154,6 +157,16 @@ pub fn read_local_inputs(
     commands.insert_resource(LocalInputs::<TestConfig>(local_inputs));
 }
 
+#[derive(Resource, Default)]
+struct SynchronizedCount(usize);
+
+fn count_synchronized(
+    mut count: ResMut<SynchronizedCount>,
+    mut events: EventReader<Synchronized<TestConfig>>,
+) {
+    count.0 += events.read().count();
+}
+
 pub fn increase_frame_system(mut frame_count: ResMut<FrameCount>) {
     frame_count.frame += 1;
 }
@@ -205,6 +218,7 @@ pub fn spawn_players(mut commands: Commands, session: Res<Session<TestConfig>>)
         Session::SyncTest(s) => s.num_players(),
         Session::P2P(s) => s.num_players(),
         Session::Spectator(s) => s.num_players(),
+        Session::Replay(s) => s.num_players(),
     };
 
     for handle in 0..num_players {
@@ -0,0 +1,81 @@
+mod common;
+
+use bevy::{prelude::*, utils::Duration};
+use bevy_ggrs::*;
+use common::{input_system, TestConfig, FRAME};
+use ggrs::{PlayerType, SessionBuilder};
+
+#[derive(Resource, Default)]
+struct SynchronizedCount(usize);
+
+fn count_synchronized(
+    mut count: ResMut<SynchronizedCount>,
+    mut events: EventReader<Synchronized<TestConfig>>,
+) {
+    count.0 += events.read().count();
+}
+
+fn create_app(
+    network: &LoopbackNetwork,
+    simulator: &NetworkSimulator,
+    local: usize,
+) -> Result<App, Box<dyn std::error::Error>> {
+    let remote = 1 - local;
+    let session = SessionBuilder::<TestConfig>::new()
+        .add_player(PlayerType::Local, local)?
+        .add_player(PlayerType::Remote(remote), remote)?
+        .start_p2p_session(simulator.wrap(network.socket(local)))?;
+
+    let mut app = common::create_base_app();
+    app.insert_resource(Session::P2P(session))
+        .insert_resource(simulator.clone())
+        .init_resource::<SynchronizedCount>()
+        .add_systems(ReadInputs, input_system)
+        .add_systems(Update, count_synchronized);
+
+    Ok(app)
+}
+
+#[test]
+fn it_applies_conditions_changed_at_runtime() -> Result<(), Box<dyn std::error::Error>> {
+    let network = LoopbackNetwork::default();
+    let simulator = NetworkSimulator::new(NetworkConditions {
+        latency: Duration::from_millis(100),
+        ..default()
+    })
+    .with_manual_clock();
+    let mut apps = vec![
+        create_app(&network, &simulator, 0)?,
+        create_app(&network, &simulator, 1)?,
+    ];
+
+    // The simulator's clock is never advanced, so every message is still delayed
+    for _ in 0..30 {
+        network.update_apps(&mut apps, FRAME);
+    }
+
+    assert_eq!(
+        apps[0].world.resource::<SynchronizedCount>().0,
+        0,
+        "No messages get through"
+    );
+
+    // Any clone of the simulator, such as the resource, controls every wrapped socket
+    apps[0]
+        .world
+        .resource::<NetworkSimulator>()
+        .set_conditions(NetworkConditions::default());
+
+    // Once the queued messages are released, every reply is sent without delay
+    simulator.advance(Duration::from_millis(100));
+
+    for _ in 0..30 {
+        network.update_apps(&mut apps, FRAME);
+    }
+
+    for app in &apps {
+        assert_eq!(app.world.resource::<SynchronizedCount>().0, 1);
+    }
+
+    Ok(())
+}
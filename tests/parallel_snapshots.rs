@@ -0,0 +1,193 @@
+mod common;
+
+use bevy::{
+    ecs::{
+        entity::{EntityMapper, MapEntities},
+        schedule::ScheduleLabel,
+    },
+    prelude::*,
+};
+use bevy_ggrs::*;
+
+#[derive(Component, Clone, Copy, Default, Hash)]
+struct Position(i32, i32);
+
+#[derive(Component, Clone, Copy, Default, Hash)]
+struct Velocity(i32, i32);
+
+#[derive(Component, Clone, Copy, Default, Hash)]
+struct Ttl(u32);
+
+/// Snapshotted as deltas.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+struct Heading(i32);
+
+/// Snapshotted sharing unchanged values.
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Tags(Vec<u32>);
+
+/// Refers to the entity being followed, so must be mapped after rollback.
+#[derive(Component, Clone, Copy)]
+struct Follow(Entity);
+
+impl MapEntities for Follow {
+    fn map_entities(&mut self, entity_mapper: &mut EntityMapper) {
+        self.0 = entity_mapper.get_or_reserve(self.0);
+    }
+}
+
+/// The entity every other entity follows, so must be mapped after rollback.
+#[derive(Resource, Clone, Copy)]
+struct Leader(Entity);
+
+impl MapEntities for Leader {
+    fn map_entities(&mut self, entity_mapper: &mut EntityMapper) {
+        self.0 = entity_mapper.get_or_reserve(self.0);
+    }
+}
+
+#[derive(Resource, Clone, Copy, Default, Hash)]
+struct Score(u32);
+
+#[derive(Resource, Clone, Default, Hash)]
+struct Log(Vec<u32>);
+
+#[derive(Resource, Default)]
+struct Mismatches(Vec<SyncTestMismatch>);
+
+fn setup_system(mut commands: Commands) {
+    let leader = commands.spawn(Position::default()).add_rollback().id();
+    commands.insert_resource(Leader(leader));
+
+    for _ in 0..10 {
+        commands
+            .spawn((
+                Position::default(),
+                Velocity(1, 2),
+                Ttl(100),
+                Heading(0),
+                Tags(vec![0; 100]),
+                Follow(leader),
+            ))
+            .add_rollback();
+    }
+}
+
+fn update_system(
+    mut score: ResMut<Score>,
+    mut log: ResMut<Log>,
+    mut query: Query<(&mut Position, &Velocity, &mut Ttl, &mut Heading)>,
+) {
+    for (mut position, velocity, mut ttl, mut heading) in query.iter_mut() {
+        position.0 += velocity.0;
+        position.1 += velocity.1;
+        ttl.0 -= 1;
+        heading.0 += 1;
+    }
+
+    score.0 += 1;
+    log.0.push(score.0);
+}
+
+fn collect_mismatches(
+    mut events: EventReader<SyncTestMismatch>,
+    mut mismatches: ResMut<Mismatches>,
+) {
+    mismatches.0.extend(events.read().cloned());
+}
+
+fn create_app() -> App {
+    let mut app = common::create_app();
+
+    app.add_systems(Startup, setup_system)
+        .add_plugins(SyncTestDiagnosticsPlugin::default())
+        .init_resource::<Score>()
+        .init_resource::<Log>()
+        .init_resource::<Mismatches>()
+        .rollback_component_with_copy::<Position>()
+        .rollback_component_with_copy::<Velocity>()
+        .rollback_component_with_clone::<Ttl>()
+        .rollback_component_with_copy::<Follow>()
+        .rollback_resource_with_copy::<Score>()
+        .rollback_resource_with_clone::<Log>()
+        .rollback_resource_with_copy::<Leader>()
+        .add_plugins(ComponentDeltaSnapshotPlugin::<CopyStrategy<Heading>>::default())
+        .add_plugins(ChangedComponentSnapshotPlugin::<CloneStrategy<Tags>>::default())
+        .update_component_with_map_entities::<Follow>()
+        .update_resource_with_map_entities::<Leader>()
+        .checksum_component_with_hash::<Position>()
+        .checksum_component_with_hash::<Velocity>()
+        .checksum_component_with_hash::<Ttl>()
+        .checksum_resource_with_hash::<Score>()
+        .checksum_resource_with_hash::<Log>()
+        .add_systems(GgrsSchedule, update_system)
+        .add_systems(Update, collect_mismatches);
+
+    app
+}
+
+/// Builds `label` without running it, as running it moves its systems out of the graph, and
+/// lists the names of all systems which conflict without being ordered.
+fn conflicts(app: &mut App, label: impl ScheduleLabel) -> Vec<(String, String)> {
+    let label = label.intern();
+
+    app.world
+        .resource_scope(|world: &mut World, mut schedules: Mut<Schedules>| {
+            let ignored = schedules.ignored_scheduling_ambiguities.clone();
+            let graph = schedules.get_mut(label).unwrap().graph_mut();
+
+            graph.initialize(world);
+            graph
+                .build_schedule(world.components(), label, &ignored)
+                .unwrap();
+
+            graph
+                .conflicting_systems()
+                .iter()
+                .map(|&(a, b, _)| {
+                    (
+                        graph.system_at(a).name().to_string(),
+                        graph.system_at(b).name().to_string(),
+                    )
+                })
+                .collect()
+        })
+}
+
+#[test]
+fn snapshot_systems_do_not_conflict_across_types() {
+    let mut app = create_app();
+
+    // Each type is saved, loaded and hashed by its own system. Only mapping entities requires
+    // exclusive systems, which are explicitly left unordered.
+    assert_eq!(conflicts(&mut app, LoadWorld), vec![]);
+    assert_eq!(conflicts(&mut app, SaveWorld), vec![]);
+    assert_eq!(conflicts(&mut app, ChecksumWorld), vec![]);
+}
+
+#[test]
+fn it_rolls_back_every_type_in_parallel() {
+    let mut app = create_app();
+
+    // Synctest rolls back every frame, so every snapshot schedule runs
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let frame = common::frame(&app);
+    assert!(frame > 2);
+    assert_eq!(app.world.resource::<Score>().0, frame as u32);
+    assert!(app.world.resource::<Mismatches>().0.is_empty());
+
+    let leader = app.world.resource::<Leader>().0;
+    assert!(app.world.get::<Position>(leader).is_some());
+
+    let mut query = app.world.query::<(&Follow, &Heading, &Tags)>();
+    assert_eq!(query.iter(&app.world).count(), 10);
+
+    for (follow, heading, tags) in query.iter(&app.world) {
+        assert_eq!(follow.0, leader);
+        assert_eq!(*heading, Heading(frame));
+        assert_eq!(tags.0.len(), 100);
+    }
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use std::any::TypeId;
+
+use bevy::{
+    ecs::{
+        entity::{EntityMapper, MapEntities},
+        reflect::ReflectMapEntities,
+    },
+    prelude::*,
+};
+use bevy_ggrs::*;
+
+#[derive(Component, Reflect, Default, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[reflect(Component, Rollback, Checksum)]
+struct Position(i32);
+
+/// Not hashable, so not included in checksums.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component, Rollback)]
+struct Velocity(f32);
+
+/// Not hashable, and only spawned once the session is running.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component, Rollback)]
+struct Spin(f32);
+
+/// Has no sensible default, so only implements [`FromReflect`].
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Rollback, MapEntities)]
+struct Target(Entity);
+
+impl MapEntities for Target {
+    fn map_entities(&mut self, entity_mapper: &mut EntityMapper) {
+        self.0 = entity_mapper.get_or_reserve(self.0);
+    }
+}
+
+#[derive(Resource, Default)]
+struct Mismatches(Vec<SyncTestMismatch>);
+
+/// Registered with the type registry, but not for rollback.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct Cosmetic;
+
+fn setup_system(mut commands: Commands) {
+    let first = commands
+        .spawn((Position(0), Velocity(1.0)))
+        .add_rollback()
+        .id();
+    commands.spawn((Position(10), Target(first))).add_rollback();
+}
+
+fn move_system(mut query: Query<&mut Position>) {
+    for mut position in query.iter_mut() {
+        position.0 += 1;
+    }
+}
+
+fn spin_system(mut commands: Commands, frame: Res<RollbackFrameCount>) {
+    if i32::from(*frame) == 5 {
+        commands.spawn((Position(0), Spin(1.0))).add_rollback();
+    }
+}
+
+fn collect_mismatches(
+    mut events: EventReader<SyncTestMismatch>,
+    mut mismatches: ResMut<Mismatches>,
+) {
+    mismatches.0.extend(events.read().cloned());
+}
+
+fn create_app() -> App {
+    let mut app = common::create_app();
+
+    app.add_systems(Startup, setup_system)
+        .register_type::<Position>()
+        .register_type::<Velocity>()
+        .register_type::<Spin>()
+        .register_type::<Target>()
+        .register_type::<Cosmetic>()
+        .add_plugins(ReflectRollbackPlugin)
+        .add_systems(GgrsSchedule, move_system);
+
+    app
+}
+
+#[test]
+fn it_registers_types_with_reflect_rollback() {
+    let app = create_app();
+
+    let types = app.world.resource::<RollbackTypes>();
+    assert!(types.contains_component(TypeId::of::<Position>()));
+    assert!(types.contains_component(TypeId::of::<Velocity>()));
+    assert!(types.contains_component(TypeId::of::<Target>()));
+    assert!(!types.contains_component(TypeId::of::<Cosmetic>()));
+}
+
+#[test]
+fn it_only_checksums_reflected_types_with_reflect_checksum() {
+    let mut app = create_app();
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let parts = app
+        .world
+        .query::<&ChecksumPartName>()
+        .iter(&app.world)
+        .map(|name| name.0)
+        .collect::<Vec<_>>();
+
+    assert!(parts.contains(&ChecksumPartName::of::<Position>().0));
+    assert!(!parts.contains(&ChecksumPartName::of::<Velocity>().0));
+    assert!(!parts.contains(&ChecksumPartName::of::<Target>().0));
+}
+
+#[test]
+fn it_rolls_back_types_without_from_world() {
+    let mut app = create_app();
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let frame = common::frame(&app);
+    assert!(frame > 2);
+
+    // Loaded through `FromReflect`, the target still points at a live entity after rolling back
+    let target = app.world.query::<&Target>().single(&app.world).0;
+    assert_eq!(app.world.get::<Position>(target), Some(&Position(frame)));
+    assert_eq!(app.world.get::<Velocity>(target), Some(&Velocity(1.0)));
+}
+
+#[test]
+fn it_rolls_back_unhashable_types_spawned_after_the_first_frame() {
+    let mut app = create_app();
+    app.add_plugins(SyncTestDiagnosticsPlugin::default())
+        .init_resource::<Mismatches>()
+        .add_systems(GgrsSchedule, spin_system.before(move_system))
+        .add_systems(Update, collect_mismatches);
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    assert!(common::frame(&app) > 5);
+    assert!(app.world.resource::<Mismatches>().0.is_empty());
+
+    let spin = app.world.query::<&Spin>().single(&app.world);
+    assert_eq!(*spin, Spin(1.0));
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use std::any::TypeId;
+
+use bevy::{
+    ecs::entity::{EntityMapper, MapEntities},
+    prelude::*,
+};
+use bevy_ggrs::*;
+
+#[derive(Component, Clone, Copy, Debug, Hash, PartialEq, Eq, Rollback)]
+#[rollback(copy, checksum)]
+struct Position(i32);
+
+#[derive(Component, Clone, Debug, Rollback)]
+#[rollback(clone, map_entities)]
+struct Target(Entity);
+
+impl MapEntities for Target {
+    fn map_entities(&mut self, entity_mapper: &mut EntityMapper) {
+        self.0 = entity_mapper.get_or_reserve(self.0);
+    }
+}
+
+#[derive(Resource, Clone, Copy, Default, Hash, Rollback)]
+#[rollback(resource, clone, checksum)]
+struct Score(u32);
+
+fn setup_system(mut commands: Commands) {
+    let first = commands.spawn(Position(0)).add_rollback().id();
+    commands.spawn((Position(10), Target(first))).add_rollback();
+}
+
+fn move_system(mut query: Query<&mut Position>, mut score: ResMut<Score>) {
+    for mut position in query.iter_mut() {
+        position.0 += 1;
+    }
+
+    score.0 += 1;
+}
+
+fn create_app() -> App {
+    let mut app = common::create_app();
+
+    app.add_systems(Startup, setup_system)
+        .init_resource::<Score>()
+        .register_rollback_types::<(Position, Target, Score)>()
+        .add_systems(GgrsSchedule, move_system);
+
+    app
+}
+
+#[test]
+fn it_registers_annotated_types() {
+    let app = create_app();
+
+    let types = app.world.resource::<RollbackTypes>();
+    assert!(types.contains_component(TypeId::of::<Position>()));
+    assert!(types.contains_component(TypeId::of::<Target>()));
+    assert!(types.contains_resource(TypeId::of::<Score>()));
+
+    assert!(app.is_plugin_added::<ComponentSnapshotPlugin<CopyStrategy<Position>>>());
+    assert!(app.is_plugin_added::<ComponentSnapshotPlugin<CloneStrategy<Target>>>());
+    assert!(app.is_plugin_added::<ResourceSnapshotPlugin<CloneStrategy<Score>>>());
+
+    assert!(app.is_plugin_added::<ComponentChecksumPlugin<Position>>());
+    assert!(app.is_plugin_added::<ResourceChecksumPlugin<Score>>());
+    assert!(!app.is_plugin_added::<ComponentChecksumPlugin<Target>>());
+
+    assert!(app.is_plugin_added::<ComponentMapEntitiesPlugin<Target>>());
+    assert!(!app.is_plugin_added::<ComponentMapEntitiesPlugin<Position>>());
+}
+
+#[test]
+fn it_rolls_back_annotated_types() {
+    let mut app = create_app();
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let frame = common::frame(&app);
+    assert!(frame > 2);
+
+    // Resimulated frames were rolled back rather than accumulated
+    assert_eq!(app.world.resource::<Score>().0, frame as u32);
+
+    let mut positions = app
+        .world
+        .query::<&Position>()
+        .iter(&app.world)
+        .map(|position| position.0)
+        .collect::<Vec<_>>();
+    positions.sort();
+    assert_eq!(positions, vec![frame, 10 + frame]);
+
+    // The target still points at a live entity with a position after rolling back
+    let target = app.world.query::<&Target>().single(&app.world).0;
+    assert_eq!(app.world.get::<Position>(target), Some(&Position(frame)));
+}
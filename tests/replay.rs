@@ -0,0 +1,363 @@
+mod common;
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_ggrs::*;
+use common::{changing_input, frame, synctest_session, Rollbacks, TestConfig, FRAME};
+use ggrs::InputStatus;
+
+/// Rolled back sum of all inputs.
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq, Eq)]
+struct Total(u32);
+
+/// Not rolled back, the [`Total`] after each frame.
+#[derive(Resource, Default)]
+struct TotalHistory(HashMap<i32, u32>);
+
+/// Rolled back sum of all inputs, stored as deltas.
+#[derive(Component, Clone, Copy, Default, Debug, PartialEq, Eq)]
+struct DeltaTotal(u32);
+
+fn input_system(mut commands: Commands, mut counter: Local<u8>) {
+    *counter = counter.wrapping_add(7);
+    commands.insert_resource(LocalInputs::<TestConfig>(HashMap::from([(0, *counter)])));
+}
+
+fn sum_system(
+    mut total: ResMut<Total>,
+    mut history: ResMut<TotalHistory>,
+    inputs: Res<PlayerInputs<TestConfig>>,
+    frame: Res<RollbackFrameCount>,
+) {
+    total.0 += inputs[0].0 as u32;
+    history.0.insert((*frame).into(), total.0);
+}
+
+fn delta_sum_system(inputs: Res<PlayerInputs<TestConfig>>, mut query: Query<&mut DeltaTotal>) {
+    for mut total in query.iter_mut() {
+        total.0 += inputs[0].0 as u32;
+    }
+}
+
+fn create_app(session: Session<TestConfig>) -> App {
+    let mut app = common::create_base_app();
+
+    app.insert_resource(session)
+        .add_plugins(InputRecordingPlugin::<TestConfig>::default())
+        .init_resource::<Total>()
+        .init_resource::<TotalHistory>()
+        .add_systems(ReadInputs, input_system)
+        .rollback_resource_with_copy::<Total>()
+        .add_systems(GgrsSchedule, sum_system);
+
+    app
+}
+
+fn record() -> (InputRecording<TestConfig>, TotalHistory) {
+    let mut app = create_app(synctest_session());
+    app.insert_resource(InputRecorder::<TestConfig>::new(1));
+
+    for _ in 0..30 {
+        app.update();
+    }
+
+    let recording = app
+        .world
+        .remove_resource::<InputRecorder<TestConfig>>()
+        .unwrap()
+        .into_recording();
+    let history = app.world.remove_resource::<TotalHistory>().unwrap();
+
+    (recording, history)
+}
+
+#[test]
+fn it_records_confirmed_frames() {
+    let (recording, _) = record();
+
+    assert!(!recording.is_empty());
+    assert_eq!(recording.num_players(), 1);
+
+    // Inputs are recorded in order, with no gaps
+    for (frame, inputs) in recording.iter().enumerate() {
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].0, (7 * (frame + 1)) as u8);
+        assert_eq!(inputs[0].1, InputStatus::Confirmed);
+    }
+}
+
+#[test]
+fn it_records_frames_relative_to_the_session_start() {
+    // Capture a world part way through a match, which has no rolled back types of its own
+    let mut donor = common::create_app();
+
+    for _ in 0..20 {
+        donor.update();
+    }
+
+    let serialized = SerializedWorld::from_world(&donor.world).unwrap();
+    let offset = serialized.frame();
+    assert!(offset > 10);
+
+    // Continue the match in a new session, which starts recording from its own frame 0
+    let mut app = create_app(synctest_session());
+    serialized.apply(&mut app.world).unwrap();
+    app.insert_resource(InputRecorder::<TestConfig>::new(1));
+
+    for _ in 0..30 {
+        app.update();
+    }
+
+    assert!(frame(&app) > offset + 20);
+
+    let recording = app
+        .world
+        .remove_resource::<InputRecorder<TestConfig>>()
+        .unwrap()
+        .into_recording();
+
+    assert!(recording.len() > 20);
+
+    for (frame, inputs) in recording.iter().enumerate() {
+        assert_eq!(inputs[0].0, (7 * (frame + 1)) as u8);
+        assert_eq!(inputs[0].1, InputStatus::Confirmed);
+    }
+}
+
+#[test]
+fn it_records_corrected_remote_inputs_after_rolling_back() {
+    let network = common::laggy_network();
+    let mut apps = common::create_p2p_apps(&network);
+
+    for app in &mut apps {
+        app.add_plugins(InputRecordingPlugin::<TestConfig>::default())
+            .insert_resource(InputRecorder::<TestConfig>::new(2));
+    }
+
+    for _ in 0..150 {
+        network.update_apps(&mut apps, FRAME);
+    }
+
+    // The second peer mispredicted the first player's input, and rolled back to correct it
+    assert!(apps[1].world.resource::<Rollbacks>().0 > 0);
+
+    let recording = apps[1]
+        .world
+        .resource::<InputRecorder<TestConfig>>()
+        .recording();
+    assert!(recording.len() > 20);
+
+    for (frame, inputs) in recording.iter().enumerate() {
+        assert_eq!(
+            inputs[0],
+            (changing_input(frame as i32), InputStatus::Confirmed)
+        );
+        assert_eq!(inputs[1], (0, InputStatus::Confirmed));
+    }
+
+    // Both peers agree on every confirmed frame
+    let local = apps[0]
+        .world
+        .resource::<InputRecorder<TestConfig>>()
+        .recording();
+    assert!(local.iter().zip(recording.iter()).all(|(a, b)| a == b));
+}
+
+#[test]
+fn it_round_trips_recordings() {
+    let (recording, _) = record();
+
+    let mut bytes = Vec::new();
+    recording.write_to(&mut bytes).unwrap();
+    let loaded = InputRecording::<TestConfig>::read_from(bytes.as_slice()).unwrap();
+
+    assert_eq!(loaded.num_players(), recording.num_players());
+    assert_eq!(loaded.len(), recording.len());
+    assert!(loaded.iter().eq(recording.iter()));
+
+    // Corrupted recordings are rejected
+    bytes[0] = 0;
+    assert!(InputRecording::<TestConfig>::read_from(bytes.as_slice()).is_err());
+}
+
+#[test]
+fn it_replays_recordings() {
+    let (recording, history) = record();
+    let frames = recording.len();
+
+    let mut app = create_app(Session::Replay(ReplaySession::new(recording)));
+
+    for _ in 0..(frames + 10) {
+        app.update();
+    }
+
+    let Session::Replay(session) = app.world.resource::<Session<TestConfig>>() else {
+        panic!("Session was replaced");
+    };
+    assert!(session.is_finished());
+
+    assert_eq!(frame(&app), frames as i32);
+    assert_eq!(app.world.resource::<Total>().0, history.0[&frame(&app)]);
+}
+
+fn update(app: &mut App, updates: usize) {
+    for _ in 0..updates {
+        app.update();
+    }
+}
+
+#[test]
+fn it_pauses_and_steps_replays() {
+    let (recording, _) = record();
+
+    let mut app = create_app(Session::Replay(ReplaySession::new(recording)));
+    app.init_resource::<ReplayController>();
+
+    for _ in 0..5 {
+        app.update();
+    }
+
+    app.world.resource_mut::<ReplayController>().pause();
+    let paused_at = frame(&app);
+
+    for _ in 0..5 {
+        app.update();
+    }
+
+    assert_eq!(frame(&app), paused_at);
+
+    app.world.resource_mut::<ReplayController>().step(1);
+
+    for _ in 0..5 {
+        app.update();
+    }
+
+    assert_eq!(frame(&app), paused_at + 1);
+}
+
+#[test]
+fn it_seeks_using_keyframes() {
+    let (recording, history) = record();
+    let frames = recording.len() as i32;
+
+    let session = ReplaySession::new(recording).with_keyframe_interval(10);
+    let mut app = create_app(Session::Replay(session));
+    app.init_resource::<ReplayController>();
+
+    for _ in 0..(frames + 10) {
+        app.update();
+    }
+
+    assert_eq!(frame(&app), frames);
+
+    // Rewind to a frame between keyframes
+    app.world
+        .resource_mut::<ReplayController>()
+        .pause()
+        .seek(15);
+    update(&mut app, 3);
+
+    assert_eq!(frame(&app), 15);
+    assert_eq!(app.world.resource::<Total>().0, history.0[&15]);
+
+    // Seek forwards again
+    app.world
+        .resource_mut::<ReplayController>()
+        .seek(frames - 1);
+    app.update();
+
+    assert_eq!(frame(&app), frames - 1);
+    assert_eq!(app.world.resource::<Total>().0, history.0[&(frames - 1)]);
+
+    // Rewind to the very start
+    app.world
+        .resource_mut::<ReplayController>()
+        .rewind(frames as usize);
+    update(&mut app, 3);
+
+    assert_eq!(frame(&app), 0);
+    assert_eq!(app.world.resource::<Total>().0, 0);
+}
+
+#[test]
+fn it_rewinds_to_the_start_without_overflowing() {
+    let (recording, _) = record();
+
+    let session = ReplaySession::new(recording).with_keyframe_interval(10);
+    let mut app = create_app(Session::Replay(session));
+    app.init_resource::<ReplayController>();
+    update(&mut app, 20);
+
+    assert!(frame(&app) > 10);
+
+    app.world
+        .resource_mut::<ReplayController>()
+        .pause()
+        .rewind(usize::MAX);
+    update(&mut app, 3);
+
+    assert_eq!(frame(&app), 0);
+    assert_eq!(app.world.resource::<Total>().0, 0);
+}
+
+fn delta_total(app: &mut App) -> u32 {
+    app.world.query::<&DeltaTotal>().single(&app.world).0
+}
+
+#[test]
+fn it_seeks_using_delta_keyframes() {
+    let (recording, history) = record();
+    let frames = recording.len() as i32;
+    assert!(frames > 20);
+
+    let session = ReplaySession::new(recording).with_keyframe_interval(10);
+    let mut app = create_app(Session::Replay(session));
+    app.init_resource::<ReplayController>()
+        .add_plugins(ComponentDeltaSnapshotPlugin::<CopyStrategy<DeltaTotal>>::default())
+        .add_systems(Startup, |mut commands: Commands| {
+            commands.spawn(DeltaTotal::default()).add_rollback();
+        })
+        .add_systems(GgrsSchedule, delta_sum_system);
+
+    for _ in 0..(frames + 10) {
+        app.update();
+    }
+
+    assert_eq!(frame(&app), frames);
+    assert_eq!(delta_total(&mut app), history.0[&frames]);
+
+    // Only deltas which have been folded are retained as keyframes
+    let keyframes = app
+        .world
+        .resource::<GgrsDeltaSnapshots<DeltaTotal>>()
+        .keyframes()
+        .collect::<Vec<_>>();
+    assert_eq!(keyframes, vec![0, 10]);
+
+    // Rewind to a frame between keyframes, while a newer keyframe is still a delta
+    app.world
+        .resource_mut::<ReplayController>()
+        .pause()
+        .seek(15);
+    update(&mut app, 3);
+
+    assert_eq!(frame(&app), 15);
+    assert_eq!(delta_total(&mut app), history.0[&15]);
+
+    // Seek forwards again, past the keyframe folded by rewinding
+    app.world
+        .resource_mut::<ReplayController>()
+        .seek(frames - 1);
+    app.update();
+
+    assert_eq!(frame(&app), frames - 1);
+    assert_eq!(delta_total(&mut app), history.0[&(frames - 1)]);
+
+    // Rewind to the very start
+    app.world
+        .resource_mut::<ReplayController>()
+        .rewind(frames as usize);
+    update(&mut app, 3);
+
+    assert_eq!(frame(&app), 0);
+    assert_eq!(delta_total(&mut app), 0);
+}
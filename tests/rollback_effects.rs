@@ -0,0 +1,181 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_ggrs::*;
+use common::{changing_input, Rollbacks, TestConfig, FRAME};
+
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+struct Sound(i32);
+
+/// Not rolled back, so it differs every time a frame is advanced.
+#[derive(Resource, Default)]
+struct Calls(i32);
+
+#[derive(Resource, Default)]
+struct Played(Vec<Sound>);
+
+#[derive(Resource, Default)]
+struct Stopped(Vec<EffectCancelled<Sound>>);
+
+fn sound_system(frame: Res<RollbackFrameCount>, mut sounds: RollbackEffectWriter<Sound>) {
+    sounds.send(Sound(i32::from(*frame)));
+}
+
+fn flaky_sound_system(mut calls: ResMut<Calls>, mut sounds: RollbackEffectWriter<Sound>) {
+    calls.0 += 1;
+    sounds.send(Sound(calls.0));
+}
+
+/// Plays the first player's input, which the second peer mispredicts.
+fn input_sound_system(
+    inputs: Res<PlayerInputs<TestConfig>>,
+    mut sounds: RollbackEffectWriter<Sound>,
+) {
+    sounds.send(Sound(inputs[0].0 as i32));
+}
+
+/// The sound played by [`input_sound_system`] when advancing to `frame`.
+fn input_sound(frame: i32) -> Sound {
+    Sound(changing_input(frame - 1) as i32)
+}
+
+fn play_sounds(
+    mut sounds: EventReader<Sound>,
+    mut cancelled: EventReader<EffectCancelled<Sound>>,
+    mut played: ResMut<Played>,
+    mut stopped: ResMut<Stopped>,
+) {
+    played.0.extend(sounds.read().copied());
+    stopped.0.extend(cancelled.read().cloned());
+}
+
+fn create_app(release: EffectRelease) -> App {
+    let mut app = common::create_app();
+
+    app.init_resource::<Calls>()
+        .init_resource::<Played>()
+        .init_resource::<Stopped>()
+        .add_rollback_effect::<Sound>(release)
+        .add_systems(Update, play_sounds);
+
+    app
+}
+
+/// Creates two connected apps playing [`input_sound_system`], where the second peer rolls back
+/// once the first player's input arrives.
+fn create_p2p_apps(network: &LoopbackNetwork, release: EffectRelease) -> Vec<App> {
+    let mut apps = common::create_p2p_apps(network);
+
+    for app in &mut apps {
+        app.init_resource::<Played>()
+            .init_resource::<Stopped>()
+            .add_rollback_effect::<Sound>(release)
+            .add_systems(GgrsSchedule, input_sound_system)
+            .add_systems(Update, play_sounds);
+    }
+
+    apps
+}
+
+#[test]
+fn it_releases_predicted_effects_once() {
+    let mut app = create_app(EffectRelease::Predicted);
+    app.add_systems(GgrsSchedule, sound_system);
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let frame = common::frame(&app);
+    assert!(frame > 2);
+
+    let played = &app.world.resource::<Played>().0;
+    assert_eq!(*played, (1..=frame).map(Sound).collect::<Vec<_>>());
+    assert!(app.world.resource::<Stopped>().0.is_empty());
+}
+
+#[test]
+fn it_releases_confirmed_effects_once_confirmed() {
+    let mut app = create_app(EffectRelease::Confirmed);
+    app.add_systems(GgrsSchedule, sound_system);
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let frame = common::frame(&app);
+    let confirmed = i32::from(*app.world.resource::<ConfirmedFrameCount>());
+    assert!(confirmed > 0 && confirmed < frame);
+
+    let played = &app.world.resource::<Played>().0;
+    assert!(!played.is_empty());
+    assert!(played.iter().all(|&Sound(frame)| frame <= confirmed));
+    assert_eq!(
+        *played,
+        (1..=played.len() as i32).map(Sound).collect::<Vec<_>>()
+    );
+    assert!(app.world.resource::<Stopped>().0.is_empty());
+}
+
+#[test]
+fn it_cancels_predicted_effects_which_are_not_queued_again() {
+    let mut app = create_app(EffectRelease::Predicted);
+    app.add_systems(GgrsSchedule, flaky_sound_system);
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let played = &app.world.resource::<Played>().0;
+    let stopped = &app.world.resource::<Stopped>().0;
+    assert!(!stopped.is_empty());
+
+    for cancelled in stopped {
+        assert!(played.contains(&cancelled.effect));
+    }
+}
+
+#[test]
+fn it_cancels_effects_predicted_from_late_remote_inputs() {
+    let network = common::laggy_network();
+    let mut apps = create_p2p_apps(&network, EffectRelease::Predicted);
+
+    for _ in 0..150 {
+        network.update_apps(&mut apps, FRAME);
+    }
+
+    assert!(apps[1].world.resource::<Rollbacks>().0 > 0);
+
+    // Only effects played with a mispredicted input were cancelled
+    let played = &apps[1].world.resource::<Played>().0;
+    let stopped = &apps[1].world.resource::<Stopped>().0;
+    assert!(!stopped.is_empty());
+
+    for cancelled in stopped {
+        assert!(played.contains(&cancelled.effect));
+        assert_ne!(cancelled.effect, input_sound(cancelled.frame));
+    }
+}
+
+#[test]
+fn it_releases_confirmed_effects_from_late_remote_inputs() {
+    let network = common::laggy_network();
+    let mut apps = create_p2p_apps(&network, EffectRelease::Confirmed);
+
+    for _ in 0..150 {
+        network.update_apps(&mut apps, FRAME);
+    }
+
+    assert!(apps[1].world.resource::<Rollbacks>().0 > 0);
+
+    // Effects queued with a mispredicted input were never released
+    let played = &apps[1].world.resource::<Played>().0;
+    assert!(played.len() > 20);
+    assert_eq!(
+        *played,
+        (1..=played.len() as i32)
+            .map(input_sound)
+            .collect::<Vec<_>>()
+    );
+    assert!(apps[1].world.resource::<Stopped>().0.is_empty());
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_ggrs::*;
+use common::{changing_input, Rollbacks, TestConfig, FRAME};
+
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+struct Hit(u32);
+
+/// Rolled back total of all damage read.
+#[derive(Resource, Clone, Copy, Default, Hash)]
+struct Damage(u32);
+
+/// Not rolled back, so it differs every time a frame is advanced.
+#[derive(Resource, Default)]
+struct Calls(u32);
+
+#[derive(Resource, Default)]
+struct Outcomes(Vec<RollbackEventOutcome<Hit>>);
+
+fn attack_system(mut hits: RollbackEventWriter<Hit>) {
+    hits.send(Hit(1));
+    hits.send(Hit(2));
+}
+
+fn flaky_attack_system(mut calls: ResMut<Calls>, mut hits: RollbackEventWriter<Hit>) {
+    calls.0 += 1;
+    hits.send(Hit(calls.0));
+}
+
+/// Sends the first player's input, which the second peer mispredicts.
+fn input_attack_system(inputs: Res<PlayerInputs<TestConfig>>, mut hits: RollbackEventWriter<Hit>) {
+    hits.send(Hit(inputs[0].0 as u32));
+}
+
+fn damage_system(hits: RollbackEventReader<Hit>, mut damage: ResMut<Damage>) {
+    for hit in hits.read() {
+        damage.0 += hit.0;
+    }
+}
+
+fn collect_outcomes(
+    mut events: EventReader<RollbackEventOutcome<Hit>>,
+    mut outcomes: ResMut<Outcomes>,
+) {
+    outcomes.0.extend(events.read().cloned());
+}
+
+fn create_app() -> App {
+    let mut app = common::create_app();
+
+    app.init_resource::<Damage>()
+        .init_resource::<Calls>()
+        .init_resource::<Outcomes>()
+        .add_rollback_event::<Hit>()
+        .rollback_resource_with_copy::<Damage>()
+        .checksum_resource_with_hash::<Damage>()
+        .add_systems(Update, collect_outcomes);
+
+    app
+}
+
+/// Asserts that only events which were predicted, and not yet resolved, were resolved.
+fn assert_resolved_once(outcomes: &Outcomes) {
+    for (index, outcome) in outcomes.0.iter().enumerate() {
+        let earlier = &outcomes.0[..index];
+
+        match outcome.status {
+            RollbackEventStatus::Predicted => {}
+            RollbackEventStatus::Cancelled | RollbackEventStatus::Confirmed => {
+                let predicted = earlier
+                    .iter()
+                    .filter(|earlier| {
+                        earlier.frame == outcome.frame && earlier.event == outcome.event
+                    })
+                    .fold(0, |count, earlier| match earlier.status {
+                        RollbackEventStatus::Predicted => count + 1,
+                        _ => count - 1,
+                    });
+
+                assert_eq!(predicted, 1, "{outcome:?} was not predicted");
+            }
+        }
+    }
+}
+
+fn count(outcomes: &Outcomes, status: RollbackEventStatus) -> usize {
+    outcomes
+        .0
+        .iter()
+        .filter(|outcome| outcome.status == status)
+        .count()
+}
+
+#[test]
+fn it_reads_each_event_once_despite_resimulation() {
+    let mut app = create_app();
+    app.add_systems(GgrsSchedule, (attack_system, damage_system).chain());
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let frame = common::frame(&app);
+    assert!(frame > 2);
+
+    // Events sent during a frame are read during the next
+    let expected = 3 * (frame as u32 - 1);
+    assert_eq!(app.world.resource::<Damage>().0, expected);
+
+    let outcomes = app.world.resource::<Outcomes>();
+    assert_eq!(
+        count(outcomes, RollbackEventStatus::Predicted),
+        2 * frame as usize
+    );
+    assert_eq!(count(outcomes, RollbackEventStatus::Cancelled), 0);
+    assert!(count(outcomes, RollbackEventStatus::Confirmed) > 0);
+}
+
+#[test]
+fn it_cancels_events_which_are_not_sent_again() {
+    let mut app = create_app();
+    app.add_systems(GgrsSchedule, flaky_attack_system);
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let outcomes = app.world.resource::<Outcomes>();
+    assert!(count(outcomes, RollbackEventStatus::Cancelled) > 0);
+    assert_resolved_once(outcomes);
+}
+
+#[test]
+fn it_cancels_events_predicted_from_late_remote_inputs() {
+    let network = common::laggy_network();
+    let mut apps = common::create_p2p_apps(&network);
+
+    for app in &mut apps {
+        app.init_resource::<Outcomes>()
+            .add_rollback_event::<Hit>()
+            .add_systems(GgrsSchedule, input_attack_system)
+            .add_systems(Update, collect_outcomes);
+    }
+
+    for _ in 0..150 {
+        network.update_apps(&mut apps, FRAME);
+    }
+
+    // The second peer mispredicted the first player's input, and rolled back to correct it
+    assert!(apps[1].world.resource::<Rollbacks>().0 > 0);
+
+    let outcomes = apps[1].world.resource::<Outcomes>();
+    assert!(count(outcomes, RollbackEventStatus::Cancelled) > 0);
+    assert!(count(outcomes, RollbackEventStatus::Confirmed) > 20);
+    assert_resolved_once(outcomes);
+
+    // Only events sent with the correct input were confirmed, once per frame
+    let mut confirmed = outcomes
+        .0
+        .iter()
+        .filter(|outcome| outcome.status == RollbackEventStatus::Confirmed)
+        .peekable();
+    let first = confirmed.peek().unwrap().frame;
+
+    for (frame, outcome) in (first..).zip(confirmed) {
+        assert_eq!(outcome.frame, frame);
+        assert_eq!(outcome.event, Hit(changing_input(frame - 1) as u32));
+    }
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_ggrs::*;
+
+/// The frame an entity was spawned on, and the order it was spawned in.
+#[derive(Component, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+struct Spawned(i32, u32);
+
+fn setup_system(mut commands: Commands) {
+    for index in 0..3 {
+        commands.spawn(Spawned(0, index)).add_rollback();
+    }
+}
+
+fn spawn_system(mut commands: Commands, frame: Res<RollbackFrameCount>) {
+    let frame = i32::from(*frame);
+
+    for index in 0..2 {
+        commands.spawn(Spawned(frame, index)).add_rollback();
+    }
+}
+
+fn create_app() -> App {
+    let mut app = common::create_app();
+
+    app.add_systems(Startup, setup_system)
+        .rollback_component_with_copy::<Spawned>()
+        .checksum_component_with_hash::<Spawned>()
+        .add_systems(GgrsSchedule, spawn_system);
+
+    app
+}
+
+#[test]
+fn it_allocates_rollbacks_by_frame_and_spawn_order() {
+    let mut app = create_app();
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let frame = common::frame(&app);
+    assert!(frame > 2);
+
+    let spawned = app
+        .world
+        .query::<(&Rollback, &Spawned)>()
+        .iter(&app.world)
+        .map(|(&rollback, &spawned)| (rollback, spawned))
+        .collect::<Vec<_>>();
+
+    // Startup entities, then two entities per frame, despite every frame being resimulated
+    assert_eq!(spawned.len(), 3 + 2 * frame as usize);
+
+    for (rollback, Spawned(frame, index)) in spawned {
+        assert_eq!(rollback.frame(), frame);
+        assert_eq!(rollback.index(), index);
+    }
+}
+
+#[test]
+fn it_rolls_back_allocated_rollbacks() {
+    let mut app = create_app();
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let frame = common::frame(&app);
+    let ordered = app.world.resource::<RollbackOrdered>();
+
+    // Rollbacks allocated during resimulated frames are not accumulated
+    assert_eq!(ordered.len(), 3 + 2 * frame as usize);
+
+    let sorted = ordered.iter_sorted().collect::<Vec<_>>();
+    let mut expected = sorted.clone();
+    expected.sort_by_key(|rollback| (rollback.frame(), rollback.index()));
+    assert_eq!(sorted, expected);
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_ggrs::*;
+
+#[derive(Component, Clone, Copy, Hash)]
+struct Health(u32);
+
+/// Mutated in the GgrsSchedule, but not rolled back.
+#[derive(Component, Default)]
+struct Poison(u32);
+
+/// Mutated in the GgrsSchedule, but not rolled back, and ignored.
+#[derive(Component, Default)]
+struct Sparkle(u32);
+
+/// Never mutated, and not rolled back.
+#[derive(Component, Default)]
+struct Nickname(u32);
+
+/// Mutated in the GgrsSchedule, but not rolled back.
+#[derive(Resource, Default)]
+struct Calls(u32);
+
+fn setup_system(mut commands: Commands) {
+    for value in 0..3 {
+        commands
+            .spawn((Health(value), Nickname(value)))
+            .add_rollback();
+    }
+}
+
+fn damage_system(mut health: Query<&mut Health>) {
+    for mut health in health.iter_mut() {
+        health.0 += 1;
+    }
+}
+
+fn poison_system(mut calls: ResMut<Calls>, mut poison: Query<(&mut Poison, &mut Sparkle)>) {
+    calls.0 += 1;
+
+    for (mut poison, mut sparkle) in poison.iter_mut() {
+        poison.0 += 1;
+        sparkle.0 += 1;
+    }
+}
+
+fn create_app() -> App {
+    let mut app = common::create_app();
+
+    app.add_systems(Startup, setup_system)
+        .add_plugins(RollbackLintPlugin)
+        .rollback_component_with_copy::<Health>()
+        .add_systems(GgrsSchedule, damage_system);
+
+    app.world
+        .resource_mut::<RollbackLint>()
+        .ignore_component::<Sparkle>();
+
+    app
+}
+
+#[test]
+fn it_accepts_rolled_back_state() {
+    let mut app = create_app();
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    assert_eq!(app.world.resource::<RollbackLint>().flagged().count(), 0);
+    assert!(common::frame(&app) > 0);
+}
+
+#[test]
+fn it_flags_each_mutated_type_once() {
+    let mut app = create_app();
+    app.init_resource::<Calls>()
+        .add_systems(GgrsSchedule, poison_system.after(damage_system));
+
+    app.update();
+
+    let entities = app
+        .world
+        .query_filtered::<Entity, With<Rollback>>()
+        .iter(&app.world)
+        .collect::<Vec<_>>();
+
+    for entity in entities {
+        app.world
+            .entity_mut(entity)
+            .insert((Poison::default(), Sparkle::default()));
+    }
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let flagged = app
+        .world
+        .resource::<RollbackLint>()
+        .flagged()
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        flagged,
+        vec![
+            std::any::type_name::<Calls>(),
+            std::any::type_name::<Poison>()
+        ]
+    );
+}
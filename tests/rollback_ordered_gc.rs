@@ -0,0 +1,135 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_ggrs::*;
+
+/// Despawned once it reaches zero.
+#[derive(Component, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+struct Lifetime(u32);
+
+/// Never despawned.
+#[derive(Component, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+struct Permanent;
+
+fn setup_system(mut commands: Commands) {
+    for _ in 0..3 {
+        commands.spawn(Permanent).add_rollback();
+    }
+}
+
+fn spawn_system(mut commands: Commands) {
+    commands.spawn(Lifetime(2)).add_rollback();
+}
+
+fn despawn_system(mut commands: Commands, mut query: Query<(Entity, &mut Lifetime)>) {
+    for (entity, mut lifetime) in query.iter_mut() {
+        if lifetime.0 == 0 {
+            commands.entity(entity).despawn();
+        } else {
+            lifetime.0 -= 1;
+        }
+    }
+}
+
+fn create_app() -> App {
+    let mut app = common::create_app();
+
+    app.add_systems(Startup, setup_system)
+        .rollback_component_with_copy::<Lifetime>()
+        .rollback_component_with_copy::<Permanent>()
+        .checksum_component_with_hash::<Lifetime>()
+        .add_systems(GgrsSchedule, (despawn_system, spawn_system).chain());
+
+    app
+}
+
+#[test]
+fn it_compacts_rollbacks_despawned_before_the_confirmed_frame() {
+    let mut app = create_app();
+
+    for _ in 0..20 {
+        app.update();
+    }
+
+    let frame = common::frame(&app);
+    let confirmed = i32::from(*app.world.resource::<ConfirmedFrameCount>());
+    assert!(confirmed > 5);
+
+    let ordered = app.world.resource::<RollbackOrdered>();
+
+    // Every spawned entity is still counted, despite most having been compacted
+    assert_eq!(ordered.total(), 3 + frame as usize);
+    assert!(ordered.len() < ordered.total());
+
+    for rollback in ordered.iter_sorted() {
+        assert!(rollback.frame() > confirmed - 4 || rollback.frame() == 0);
+    }
+}
+
+#[test]
+fn it_preserves_the_order_of_remaining_rollbacks() {
+    let mut app = create_app();
+
+    app.update();
+
+    let permanent = app
+        .world
+        .query_filtered::<&Rollback, With<Permanent>>()
+        .iter(&app.world)
+        .map(|&rollback| {
+            let order = app.world.resource::<RollbackOrdered>().order(rollback);
+            (rollback, order)
+        })
+        .collect::<Vec<_>>();
+
+    for _ in 0..20 {
+        app.update();
+    }
+
+    let ordered = app.world.resource::<RollbackOrdered>();
+
+    for (rollback, order) in permanent {
+        assert_eq!(ordered.order(rollback), order);
+        assert_eq!(ordered.get(order), Some(rollback));
+    }
+
+    // Remaining rollbacks are still sorted by order
+    let orders = ordered
+        .iter_sorted()
+        .map(|rollback| ordered.order(rollback))
+        .collect::<Vec<_>>();
+    let mut expected = orders.clone();
+    expected.sort();
+    assert_eq!(orders, expected);
+}
+
+#[test]
+fn it_no_longer_orders_compacted_rollbacks() {
+    let mut app = create_app();
+
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let first = app
+        .world
+        .query_filtered::<&Rollback, With<Lifetime>>()
+        .iter(&app.world)
+        .copied()
+        .min_by_key(Rollback::frame)
+        .expect("No entities were spawned");
+
+    let ordered = app.world.resource::<RollbackOrdered>();
+    let order = ordered
+        .try_order(first)
+        .expect("Rollback was not registered");
+    assert_eq!(ordered.order(first), order);
+
+    for _ in 0..20 {
+        app.update();
+    }
+
+    let ordered = app.world.resource::<RollbackOrdered>();
+    assert_eq!(ordered.try_order(first), None);
+    assert_eq!(ordered.get(order), None);
+}
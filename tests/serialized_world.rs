@@ -0,0 +1,225 @@
+mod common;
+
+use bevy::{
+    ecs::{
+        entity::{EntityMapper, MapEntities},
+        reflect::ReflectMapEntities,
+    },
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_ggrs::*;
+use common::{frame, input_system, synctest_session};
+
+#[derive(Component, Reflect, Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[reflect(Component)]
+struct Health(u32);
+
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Component, MapEntities)]
+struct Target(Entity);
+
+impl FromWorld for Target {
+    fn from_world(_world: &mut World) -> Self {
+        Self(Entity::PLACEHOLDER)
+    }
+}
+
+impl MapEntities for Target {
+    fn map_entities(&mut self, entity_mapper: &mut EntityMapper) {
+        self.0 = entity_mapper.get_or_reserve(self.0);
+    }
+}
+
+#[derive(Resource, Reflect, Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[reflect(Resource)]
+struct Score(u32);
+
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Resource, MapEntitiesResource)]
+struct Leader(Entity);
+
+impl FromWorld for Leader {
+    fn from_world(_world: &mut World) -> Self {
+        Self(Entity::PLACEHOLDER)
+    }
+}
+
+impl MapEntities for Leader {
+    fn map_entities(&mut self, entity_mapper: &mut EntityMapper) {
+        self.0 = entity_mapper.get_or_reserve(self.0);
+    }
+}
+
+fn setup_system(mut commands: Commands) {
+    let doomed = commands.spawn(Health(100)).add_rollback().id();
+    let first = commands.spawn(Health(10)).add_rollback().id();
+    commands.spawn((Health(20), Target(first))).add_rollback();
+    commands.spawn(Target(doomed)).add_rollback();
+    commands.insert_resource(Leader(first));
+}
+
+fn damage_system(mut score: ResMut<Score>, mut health: Query<&mut Health>) {
+    score.0 += 1;
+
+    for mut health in health.iter_mut() {
+        health.0 += 1;
+    }
+}
+
+fn create_app() -> App {
+    let mut app = common::create_base_app();
+
+    app.register_type::<Health>()
+        .register_type::<Target>()
+        .register_type::<Score>()
+        .register_type::<Leader>()
+        .init_resource::<Score>()
+        .add_systems(ReadInputs, input_system)
+        .rollback_component_with_copy::<Health>()
+        .rollback_component_with_copy::<Target>()
+        .update_component_with_map_entities::<Target>()
+        .rollback_resource_with_copy::<Score>()
+        .rollback_resource_with_copy::<Leader>()
+        .update_resource_with_map_entities::<Leader>()
+        .add_systems(GgrsSchedule, damage_system);
+
+    app
+}
+
+/// Collects `(order, Health, Target order)` for every rollback entity.
+fn collect(world: &mut World) -> Vec<(usize, Option<Health>, Option<usize>)> {
+    let ordered = world.resource::<RollbackOrdered>().clone();
+    let rollbacks = world
+        .query::<(Entity, &Rollback)>()
+        .iter(world)
+        .map(|(entity, &rollback)| (entity, rollback))
+        .collect::<HashMap<_, _>>();
+
+    let mut entities = world
+        .query::<(&Rollback, Option<&Health>, Option<&Target>)>()
+        .iter(world)
+        .map(|(&rollback, health, target)| {
+            let target = target
+                .and_then(|target| rollbacks.get(&target.0))
+                .map(|&rollback| ordered.order(rollback));
+
+            (ordered.order(rollback), health.copied(), target)
+        })
+        .collect::<Vec<_>>();
+
+    entities.sort_by_key(|(order, ..)| *order);
+    entities
+}
+
+#[test]
+fn it_round_trips_the_rollback_state() {
+    let mut app = create_app();
+    app.add_systems(Startup, setup_system)
+        .insert_resource(synctest_session());
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    // Despawn the first entity, which should be preserved as a gap in the ordering
+    let doomed = app
+        .world
+        .resource::<RollbackOrdered>()
+        .iter_sorted()
+        .next()
+        .unwrap();
+    let doomed = app
+        .world
+        .query::<(Entity, &Rollback)>()
+        .iter(&app.world)
+        .find(|(_, &rollback)| rollback == doomed)
+        .map(|(entity, _)| entity)
+        .unwrap();
+    app.world.despawn(doomed);
+
+    let bytes = SerializedWorld::from_world(&app.world)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+
+    let mut loaded = create_app();
+    loaded.update();
+
+    let serialized = SerializedWorld::from_bytes(&bytes).unwrap();
+    serialized.apply(&mut loaded.world).unwrap();
+
+    assert_eq!(serialized.frame(), frame(&app));
+    assert_eq!(frame(&loaded), frame(&app));
+
+    assert_eq!(
+        app.world.resource::<Score>(),
+        loaded.world.resource::<Score>()
+    );
+    assert_eq!(
+        app.world.resource::<Time<GgrsTime>>().elapsed(),
+        loaded.world.resource::<Time<GgrsTime>>().elapsed()
+    );
+    assert_eq!(
+        app.world.resource::<RollbackOrdered>().len(),
+        loaded.world.resource::<RollbackOrdered>().len()
+    );
+
+    let original = collect(&mut app.world);
+    assert_eq!(original.len(), 3);
+    assert_eq!(original, collect(&mut loaded.world));
+}
+
+fn leader_health(world: &World) -> Option<Health> {
+    world.get::<Health>(world.resource::<Leader>().0).copied()
+}
+
+#[test]
+fn it_maps_entities_in_resources() {
+    let mut app = create_app();
+    app.add_systems(Startup, setup_system)
+        .insert_resource(synctest_session());
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let bytes = SerializedWorld::from_world(&app.world)
+        .unwrap()
+        .to_bytes()
+        .unwrap();
+
+    // Offset the entities spawned when applying, so unmapped entities would not line up
+    let mut loaded = create_app();
+    for _ in 0..10 {
+        loaded.world.spawn_empty();
+    }
+
+    SerializedWorld::from_bytes(&bytes)
+        .unwrap()
+        .apply(&mut loaded.world)
+        .unwrap();
+
+    assert!(leader_health(&app.world).is_some());
+    assert_eq!(leader_health(&loaded.world), leader_health(&app.world));
+}
+
+#[test]
+fn it_reports_unregistered_types() {
+    #[derive(Component, Clone, Copy)]
+    struct Unregistered;
+
+    let mut app = create_app();
+    app.rollback_component_with_copy::<Unregistered>()
+        .add_systems(Startup, |mut commands: Commands| {
+            commands.spawn(Unregistered).add_rollback();
+        });
+    app.update();
+
+    let error = SerializedWorld::from_world(&app.world).unwrap_err();
+
+    assert!(matches!(
+        error,
+        WorldSerializationError::UnregisteredType(_)
+    ));
+}
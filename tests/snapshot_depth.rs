@@ -0,0 +1,130 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_ggrs::*;
+
+#[derive(Component, Clone, Copy, Default)]
+struct Counter(u32);
+
+/// Rolled back using deltas, which are sized the same way.
+#[derive(Component, Clone, Copy, Default)]
+struct Health(u32);
+
+#[derive(Resource, Clone, Copy, Default)]
+struct Score(u32);
+
+/// Counts how many times [`SnapshotDepth`] or [`MaxPredictionWindow`] were seen changing.
+#[derive(Resource, Default)]
+struct DepthChanges(u32);
+
+fn setup_system(mut commands: Commands) {
+    commands
+        .spawn((Counter::default(), Health::default()))
+        .add_rollback();
+}
+
+fn update_system(mut score: ResMut<Score>, mut query: Query<&mut Counter>) {
+    for mut counter in query.iter_mut() {
+        counter.0 += 1;
+    }
+
+    score.0 += 1;
+}
+
+fn count_depth_changes(
+    depth: Res<SnapshotDepth>,
+    window: Res<MaxPredictionWindow>,
+    mut changes: ResMut<DepthChanges>,
+) {
+    if depth.is_changed() || window.is_changed() {
+        changes.0 += 1;
+    }
+}
+
+fn create_app() -> App {
+    let mut app = common::create_app();
+
+    app.add_systems(Startup, setup_system)
+        .init_resource::<Score>()
+        .rollback_component_with_copy::<Counter>()
+        .rollback_resource_with_copy::<Score>()
+        .add_plugins(ComponentDeltaSnapshotPlugin::<CopyStrategy<Health>>::default())
+        .add_systems(GgrsSchedule, update_system);
+
+    app
+}
+
+fn assert_depth(app: &App, expected: usize) {
+    let depth = usize::from(*app.world.resource::<SnapshotDepth>());
+    assert_eq!(depth, expected);
+
+    assert_eq!(
+        app.world
+            .resource::<GgrsComponentSnapshots<Counter>>()
+            .depth(),
+        depth
+    );
+    assert_eq!(
+        app.world.resource::<GgrsResourceSnapshots<Score>>().depth(),
+        depth
+    );
+    assert_eq!(
+        app.world
+            .resource::<GgrsComponentSnapshots<Entity>>()
+            .depth(),
+        depth
+    );
+    assert_eq!(
+        app.world
+            .resource::<GgrsDeltaSnapshots<Health, Health>>()
+            .depth(),
+        depth
+    );
+}
+
+#[test]
+fn it_derives_depth_from_max_prediction() {
+    let mut app = create_app();
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let max_prediction = usize::from(*app.world.resource::<MaxPredictionWindow>());
+    assert!(max_prediction > 0);
+
+    assert_depth(&app, SnapshotDepth::new(max_prediction, 0).into());
+
+    let frame = common::frame(&app);
+    assert_eq!(app.world.resource::<Score>().0, frame as u32);
+}
+
+#[test]
+fn it_includes_margin_in_depth() {
+    let mut app = create_app();
+    app.insert_resource(SnapshotDepthMargin(5));
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let max_prediction = usize::from(*app.world.resource::<MaxPredictionWindow>());
+
+    assert_depth(&app, usize::from(SnapshotDepth::new(max_prediction, 0)) + 5);
+}
+
+#[test]
+fn it_only_updates_depth_when_it_changes() {
+    let mut app = create_app();
+    app.init_resource::<DepthChanges>()
+        .add_systems(Update, count_depth_changes);
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    assert!(common::frame(&app) > 2);
+
+    // Only seen as changed on the first run, when the resources were added
+    assert_eq!(app.world.resource::<DepthChanges>().0, 1);
+}
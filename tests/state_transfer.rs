@@ -0,0 +1,331 @@
+mod common;
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    prelude::*,
+    utils::{Duration, HashMap},
+};
+use bevy_ggrs::*;
+use common::{frame, input_system, synctest_session, TestConfig, FRAME};
+
+/// A minimal in-memory network which drops every third datagram.
+#[derive(Clone, Default)]
+struct LossyNetwork {
+    queues: Arc<Mutex<HashMap<usize, VecDeque<(usize, Vec<u8>)>>>>,
+    sent: Arc<Mutex<usize>>,
+}
+
+struct LossySocket {
+    addr: usize,
+    network: LossyNetwork,
+}
+
+impl StateTransferSocket<usize> for LossySocket {
+    fn send_to(&mut self, data: &[u8], addr: &usize) {
+        let mut sent = self.network.sent.lock().unwrap();
+        *sent += 1;
+
+        if *sent % 3 == 0 {
+            return;
+        }
+
+        self.network
+            .queues
+            .lock()
+            .unwrap()
+            .entry(*addr)
+            .or_default()
+            .push_back((self.addr, data.to_vec()));
+    }
+
+    fn receive_all(&mut self) -> Vec<(usize, Vec<u8>)> {
+        self.network
+            .queues
+            .lock()
+            .unwrap()
+            .entry(self.addr)
+            .or_default()
+            .drain(..)
+            .collect()
+    }
+}
+
+#[derive(Component, Reflect, Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[reflect(Component)]
+struct Health(u32);
+
+fn setup_system(mut commands: Commands) {
+    // Enough entities to require several chunks
+    for health in 0..1000 {
+        commands.spawn(Health(health)).add_rollback();
+    }
+}
+
+fn damage_system(mut health: Query<&mut Health>) {
+    for mut health in health.iter_mut() {
+        health.0 += 1;
+    }
+}
+
+/// Creates an app which can't apply received world states, as [`Health`] isn't registered
+/// with the [`AppTypeRegistry`].
+fn create_unregistered_app() -> App {
+    let mut app = common::create_base_app();
+
+    app.add_plugins(StateTransferPlugin::<TestConfig>::default())
+        .add_systems(ReadInputs, input_system)
+        .rollback_component_with_copy::<Health>()
+        .add_systems(GgrsSchedule, damage_system);
+
+    app
+}
+
+fn create_app() -> App {
+    let mut app = create_unregistered_app();
+    app.register_type::<Health>();
+    app
+}
+
+/// Creates a host which has advanced a [`SyncTestSession`] for a number of frames.
+fn create_host(socket: impl StateTransferSocket<usize> + 'static) -> App {
+    let mut host = create_app();
+    host.add_systems(Startup, setup_system)
+        .insert_resource(StateTransferHost::<TestConfig>::new(socket))
+        .insert_resource(synctest_session());
+
+    for _ in 0..20 {
+        host.update();
+    }
+
+    host
+}
+
+/// Begins a transfer for every request the host received, returning the frames being sent.
+fn accept_requests(host: &mut App) -> Vec<i32> {
+    let requests = host
+        .world
+        .resource_mut::<Events<StateTransferRequested<TestConfig>>>()
+        .drain()
+        .collect::<Vec<_>>();
+
+    requests
+        .into_iter()
+        .map(|StateTransferRequested { addr }| {
+            StateTransferPlugin::<TestConfig>::begin_transfer(&mut host.world, addr)
+                .expect("Failed to begin the transfer")
+        })
+        .collect()
+}
+
+fn transferred_frame(app: &App) -> Option<i32> {
+    app.world
+        .resource::<Events<StateTransferComplete>>()
+        .iter_current_update_events()
+        .next()
+        .map(|event| event.frame)
+}
+
+fn healths(world: &mut World) -> Vec<(usize, Health)> {
+    let ordered = world.resource::<RollbackOrdered>().clone();
+    let mut healths = world
+        .query::<(&Rollback, &Health)>()
+        .iter(world)
+        .map(|(&rollback, &health)| (ordered.order(rollback), health))
+        .collect::<Vec<_>>();
+
+    healths.sort_by_key(|(order, _)| *order);
+    healths
+}
+
+#[test]
+fn it_transfers_the_confirmed_world() {
+    let network = LossyNetwork::default();
+
+    let mut host = create_host(LossySocket {
+        addr: 0,
+        network: network.clone(),
+    });
+
+    let confirmed: i32 = (*host.world.resource::<ConfirmedFrameCount>()).into();
+
+    let mut client = create_app();
+    client.insert_resource(StateTransferClient::<TestConfig>::new(
+        LossySocket {
+            addr: 1,
+            network: network.clone(),
+        },
+        0,
+    ));
+
+    let mut transferred = None;
+
+    for _ in 0..200 {
+        client.update();
+
+        for frame in accept_requests(&mut host) {
+            assert_eq!(frame, confirmed);
+        }
+
+        host.update();
+
+        transferred = transferred_frame(&client);
+        if transferred.is_some() {
+            break;
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let frame = transferred.expect("The transfer did not complete");
+    assert_eq!(frame, confirmed);
+
+    // The host was rewound to the transferred frame, and awaits a new session
+    assert!(host.world.get_resource::<Session<TestConfig>>().is_none());
+    let host_frame: i32 = (*host.world.resource::<RollbackFrameCount>()).into();
+    let client_frame: i32 = (*client.world.resource::<RollbackFrameCount>()).into();
+    let client_offset: i32 = (*client.world.resource::<RollbackFrameOffset>()).into();
+    assert_eq!(host_frame, frame);
+    assert_eq!(client_frame, frame);
+    assert_eq!(client_offset, frame);
+
+    assert_eq!(healths(&mut host.world), healths(&mut client.world));
+}
+
+#[test]
+fn it_rejects_transfers_larger_than_the_maximum() {
+    let network = LoopbackNetwork::default();
+    let mut host = create_host(network.socket(0));
+
+    let mut client = create_app();
+    client.insert_resource(
+        StateTransferClient::<TestConfig>::new(network.socket(1), 0).with_max_transfer_size(1024),
+    );
+
+    for _ in 0..20 {
+        client.update();
+        accept_requests(&mut host);
+        host.update();
+        network.advance(FRAME);
+
+        assert_eq!(transferred_frame(&client), None);
+    }
+
+    // Nothing was allocated for the oversized transfer
+    let client = client.world.resource::<StateTransferClient<TestConfig>>();
+    assert!(!client.is_complete());
+    assert_eq!(client.progress(), None);
+
+    let host = host.world.resource::<StateTransferHost<TestConfig>>();
+    let (acked, _) = host.progress(&1).expect("The transfer was never started");
+    assert_eq!(acked, 0);
+}
+
+#[test]
+fn it_requests_the_world_again_if_it_cannot_be_applied() {
+    let network = LoopbackNetwork::default();
+    let mut host = create_host(network.socket(0));
+
+    let mut client = create_unregistered_app();
+    client.insert_resource(StateTransferClient::<TestConfig>::new(network.socket(1), 0));
+
+    let mut transfers = 0;
+
+    for _ in 0..50 {
+        client.update();
+        transfers += accept_requests(&mut host).len();
+        host.update();
+        network.advance(FRAME);
+
+        assert_eq!(transferred_frame(&client), None);
+    }
+
+    assert!(
+        transfers > 1,
+        "Only requested the world state {transfers} time(s)"
+    );
+    assert!(!client
+        .world
+        .resource::<StateTransferClient<TestConfig>>()
+        .is_complete());
+}
+
+#[test]
+fn it_restarts_the_match_from_the_transferred_frame() -> Result<(), Box<dyn std::error::Error>> {
+    let network = LoopbackNetwork::default();
+
+    let mut apps = vec![create_app(), create_app()];
+    for app in apps.iter_mut() {
+        app.add_systems(Startup, setup_system);
+    }
+
+    network.connect_apps::<TestConfig>(&mut apps, |builder| builder)?;
+
+    for _ in 0..60 {
+        network.update_apps(&mut apps, FRAME);
+    }
+
+    // The first peer hosts the transfer, while the existing peer leaves its session to rejoin
+    // alongside a new peer
+    apps[0].insert_resource(StateTransferHost::<TestConfig>::new(network.socket(0)));
+    apps[1].world.remove_resource::<Session<TestConfig>>();
+    apps[1].insert_resource(StateTransferClient::<TestConfig>::new(network.socket(1), 0));
+
+    let mut joiner = create_app();
+    joiner.insert_resource(StateTransferClient::<TestConfig>::new(network.socket(2), 0));
+    apps.push(joiner);
+
+    let mut sent = Vec::new();
+    let mut transferred = [None; 2];
+
+    for _ in 0..50 {
+        network.update_apps(&mut apps, FRAME);
+        sent.extend(accept_requests(&mut apps[0]));
+
+        for (frame, app) in transferred.iter_mut().zip(&apps[1..]) {
+            *frame = frame.or(transferred_frame(app));
+        }
+
+        if transferred.iter().all(Option::is_some) {
+            break;
+        }
+    }
+
+    // Both peers were sent the same frame
+    let start = sent[0];
+    assert!(start > 0);
+    assert!(sent.iter().all(|&frame| frame == start));
+    assert_eq!(transferred, [Some(start); 2]);
+
+    for app in apps.iter() {
+        assert_eq!(frame(app), start);
+        assert_eq!(
+            i32::from(*app.world.resource::<RollbackFrameOffset>()),
+            start
+        );
+    }
+
+    // All peers start a new session together, continuing from the transferred frame
+    let rematch = LoopbackNetwork::default();
+    rematch.connect_apps::<TestConfig>(&mut apps, |builder| builder)?;
+
+    for _ in 0..60 {
+        rematch.update_apps(&mut apps, FRAME);
+    }
+
+    for app in apps.iter_mut() {
+        let frame = frame(app);
+        assert!(frame > start + 20);
+
+        // Every entity has been damaged once per frame since it was spawned
+        let expected = (0..1000)
+            .map(|order| (order, Health((order + frame as usize) as u32)))
+            .collect::<Vec<_>>();
+        assert_eq!(healths(&mut app.world), expected);
+    }
+
+    Ok(())
+}
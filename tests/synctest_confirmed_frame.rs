@@ -0,0 +1,27 @@
+mod common;
+
+use bevy_ggrs::*;
+
+/// The check distance of [`common::synctest_session`].
+const CHECK_DISTANCE: usize = 2;
+
+/// Regression test: a SyncTest only ever rolls back `check_distance` frames, so every frame
+/// older than that must be reported as confirmed, allowing snapshots to be discarded.
+#[test]
+fn it_confirms_frames_older_than_check_distance() {
+    let mut app = common::create_app();
+
+    for _ in 0..20 {
+        app.update();
+    }
+
+    let frame = common::frame(&app);
+    let confirmed = i32::from(*app.world.resource::<ConfirmedFrameCount>());
+
+    assert!(frame > CHECK_DISTANCE as i32 + 1);
+
+    // Confirmation lags the frame being advanced from by exactly `check_distance`
+    assert!(confirmed > 0);
+    assert!(confirmed >= frame - CHECK_DISTANCE as i32 - 1);
+    assert!(confirmed <= frame - CHECK_DISTANCE as i32);
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_ggrs::*;
+
+#[derive(Component, Clone, Copy, Hash)]
+struct Health(u32);
+
+/// Marks the entity which is updated non-deterministically.
+#[derive(Component)]
+struct Flaky;
+
+/// Not rolled back, so it differs between the original simulation and the resimulation.
+#[derive(Resource, Default)]
+struct Calls(u32);
+
+#[derive(Resource, Default)]
+struct Mismatches(Vec<SyncTestMismatch>);
+
+fn setup_system(mut commands: Commands) {
+    commands.spawn(Health(0)).add_rollback();
+    commands.spawn((Health(0), Flaky)).add_rollback();
+    commands.spawn(Health(0)).add_rollback();
+}
+
+fn damage_system(mut calls: ResMut<Calls>, mut health: Query<(&mut Health, Has<Flaky>)>) {
+    calls.0 += 1;
+
+    for (mut health, flaky) in health.iter_mut() {
+        health.0 += if flaky { calls.0 } else { 1 };
+    }
+}
+
+fn collect_mismatches(
+    mut events: EventReader<SyncTestMismatch>,
+    mut mismatches: ResMut<Mismatches>,
+) {
+    mismatches.0.extend(events.read().cloned());
+}
+
+#[test]
+fn it_pinpoints_the_diverging_component_and_entity() {
+    let mut app = common::create_app();
+
+    app.add_systems(Startup, setup_system)
+        .add_plugins(SyncTestDiagnosticsPlugin::default())
+        .init_resource::<Calls>()
+        .init_resource::<Mismatches>()
+        .rollback_component_with_copy::<Health>()
+        .checksum_component_with_hash::<Health>()
+        .add_systems(GgrsSchedule, damage_system)
+        .add_systems(Update, collect_mismatches);
+
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let flaky = *app
+        .world
+        .query_filtered::<&Rollback, With<Flaky>>()
+        .single(&app.world);
+    let flaky = app.world.resource::<RollbackOrdered>().order(flaky);
+
+    let mismatches = &app.world.resource::<Mismatches>().0;
+    let first = mismatches.first().expect("No mismatch was diagnosed");
+
+    assert_eq!(first.mismatches.len(), 1);
+    assert_eq!(first.mismatches[0].name, std::any::type_name::<Health>());
+    assert_eq!(first.mismatches[0].entities.len(), 1);
+    assert_eq!(first.mismatches[0].entities[0].order, flaky);
+}
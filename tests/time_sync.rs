@@ -0,0 +1,180 @@
+mod common;
+
+use std::num::NonZeroUsize;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy, utils::Duration};
+use bevy_ggrs::*;
+use common::{frame, input_system, TestConfig, FRAME};
+
+fn create_app(policy: TimeSyncPolicy, delta: Duration) -> App {
+    let mut app = common::create_app();
+
+    app.insert_resource(policy)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(delta));
+
+    app
+}
+
+#[test]
+fn it_limits_steps_per_update() {
+    let policy = TimeSyncPolicy {
+        max_catch_up_steps: NonZeroUsize::new(2),
+        ..default()
+    };
+
+    // Every update accumulates enough time for 10 frames
+    let mut app = create_app(policy, Duration::from_secs_f64(10.5 / 60.0));
+
+    // The first update only initialises time
+    app.update();
+
+    for _ in 0..5 {
+        let before = frame(&app);
+        app.update();
+        let after = frame(&app);
+
+        assert!(after - before <= 2, "Advanced {} frames", after - before);
+    }
+}
+
+#[test]
+fn it_consumes_all_time_without_limit() {
+    let mut app = create_app(
+        TimeSyncPolicy::default(),
+        Duration::from_secs_f64(10.5 / 60.0),
+    );
+
+    app.update();
+    let before = frame(&app);
+    app.update();
+    let after = frame(&app);
+
+    assert_eq!(after - before, 10);
+}
+
+#[test]
+fn it_reports_reaching_the_step_limit() {
+    let policy = TimeSyncPolicy {
+        max_catch_up_steps: NonZeroUsize::new(2),
+        catch_up_overflow: CatchUpOverflow::Drop,
+        ..default()
+    };
+
+    let mut app = create_app(policy, Duration::from_secs_f64(10.5 / 60.0));
+
+    app.update();
+    app.update();
+
+    let events = app.world.resource::<Events<CatchUpLimitReached>>();
+    let event = events
+        .iter_current_update_events()
+        .next()
+        .expect("No CatchUpLimitReached event was sent");
+
+    assert_eq!(event.steps, 2);
+    assert!(event.leftover > Duration::from_secs_f64(1.0 / 60.0));
+}
+
+/// Runs a single hitch of 10.5 frames with a limit of 2 steps per update, followed by several
+/// short updates, returning the number of frames advanced by each update after the hitch.
+fn frames_after_hitch(catch_up_overflow: CatchUpOverflow) -> Vec<i32> {
+    let policy = TimeSyncPolicy {
+        max_catch_up_steps: NonZeroUsize::new(2),
+        catch_up_overflow,
+        ..default()
+    };
+
+    let mut app = create_app(policy, Duration::from_secs_f64(10.5 / 60.0));
+
+    // The first update only initialises time
+    app.update();
+
+    let before = frame(&app);
+    app.update();
+    assert_eq!(frame(&app) - before, 2);
+
+    // Each update afterwards accumulates less than a frame of time on its own
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        0.3 / 60.0,
+    )));
+
+    (0..4)
+        .map(|_| {
+            let before = frame(&app);
+            app.update();
+            frame(&app) - before
+        })
+        .collect()
+}
+
+#[test]
+fn it_drops_leftover_time() {
+    assert_eq!(frames_after_hitch(CatchUpOverflow::Drop), vec![0, 0, 0, 1]);
+}
+
+#[test]
+fn it_carries_leftover_time() {
+    assert_eq!(frames_after_hitch(CatchUpOverflow::Carry), vec![2, 2, 2, 2]);
+}
+
+#[test]
+fn it_spreads_leftover_time_over_updates() {
+    // At most one extra frame is released per update
+    assert_eq!(
+        frames_after_hitch(CatchUpOverflow::Spread),
+        vec![1, 1, 1, 2]
+    );
+}
+
+fn create_p2p_app(policy: TimeSyncPolicy) -> App {
+    let mut app = common::create_base_app();
+
+    app.insert_resource(policy)
+        .add_systems(ReadInputs, input_system);
+
+    app
+}
+
+#[test]
+fn it_skips_frames_when_recommended_to_wait() -> Result<(), Box<dyn std::error::Error>> {
+    // Never run slow, so only skipped frames can change how far apart the peers are
+    let policy = TimeSyncPolicy {
+        slow_down_factor: 1.0,
+        ..default()
+    };
+
+    let network = LoopbackNetwork::default();
+    let mut apps = vec![
+        create_p2p_app(policy),
+        create_p2p_app(TimeSyncPolicy {
+            honour_wait_recommendations: false,
+            ..policy
+        }),
+    ];
+    network.connect_apps::<TestConfig>(&mut apps, |builder| {
+        builder
+            .with_max_prediction_window(12)
+            .expect("prediction window can't be 0")
+    })?;
+
+    for _ in 0..50 {
+        network.update_apps(&mut apps, FRAME);
+    }
+
+    assert!(frame(&apps[0]) > 25);
+    let before = frame(&apps[1]) - frame(&apps[0]);
+
+    apps[0]
+        .world
+        .send_event(WaitRecommendation { skip_frames: 3 });
+
+    for _ in 0..10 {
+        network.update_apps(&mut apps, FRAME);
+    }
+
+    // Only the peer honouring the recommendation skipped any frames
+    let after = frame(&apps[1]) - frame(&apps[0]);
+    assert_eq!(after - before, 3);
+
+    Ok(())
+}